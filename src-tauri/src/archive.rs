@@ -0,0 +1,530 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+
+use chrono::Local;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::audit::{self, audit_entry_from_row, verify_chain, AuditEntry, AUDIT_COLUMNS};
+use crate::backup::with_suffix;
+use crate::case_import::{import_profile_from_row, ImportProfile, IMPORT_PROFILE_COLUMNS};
+use crate::database_init::{
+    case_edit_from_row, case_from_row, case_transfer_from_row, hearing_from_row, Case, CaseEdit, CaseTransfer, Db,
+    Hearing, CASE_COLUMNS, CASE_EDIT_COLUMNS, CASE_TRANSFER_COLUMNS, HEARING_COLUMNS,
+};
+use crate::disposal::{disposal_reason_from_row, DisposalReason, DISPOSAL_REASON_COLUMNS};
+use crate::error::{AppError, AppResult};
+use crate::mediators::{mediator_from_row, Mediator, MEDIATOR_COLUMNS};
+use crate::migrations;
+use crate::roster;
+
+/// The `format` field of every archive, so any other JSON file is turned
+/// away before it is read any further.
+pub const ARCHIVE_FORMAT: &str = "caserep-register";
+
+/// Raised whenever a field is renamed, removed or changes meaning. Adding a
+/// field is not a new version: older archives simply lack it.
+pub const ARCHIVE_VERSION: u32 = 1;
+
+/// The whole register as a single JSON document, written by `export_json`
+/// and read back by `import_json`.
+///
+/// Every record keeps its id, so the links between them (a hearing's
+/// mediator, a case's disposal reason, an audit entry's case) survive the
+/// round trip, and the audit log is copied entry for entry so its hash chain
+/// still verifies afterwards. Dates are `YYYY-MM-DD`, times `HH:MM:SS` and
+/// timestamps `YYYY-MM-DD HH:MM:SS` in local time. Outcomes and natures of
+/// case use the same codes as everywhere else in the app. Names written next
+/// to an id (`assigned_to`, `mediator`, `disposal_reason`, ...) are there for
+/// whoever reads the file and are ignored on import.
+///
+/// The backup schedule and its run history describe the machine rather than
+/// the register, and the search index is rebuilt from the cases, so none of
+/// them are included.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RegisterArchive {
+    /// Always `ARCHIVE_FORMAT`.
+    pub format: String,
+    /// `ARCHIVE_VERSION` of the app that wrote the file. Archives from a
+    /// newer version are refused.
+    pub version: u32,
+    /// Schema version of the register it was taken from, for reference only.
+    pub schema_version: i32,
+    pub exported_at: String,
+    pub exported_by: String,
+    /// Printed on reports and exports. Archives written before the name was
+    /// kept in the register lack it, and the register keeps its own.
+    #[serde(default)]
+    pub centre_name: Option<String>,
+    pub mediators: Vec<Mediator>,
+    pub disposal_reasons: Vec<DisposalReason>,
+    pub cases: Vec<ArchivedCase>,
+    #[serde(default)]
+    pub import_profiles: Vec<ImportProfile>,
+    /// Oldest entry first.
+    pub audit_log: Vec<AuditEntry>,
+}
+
+/// A case with everything recorded against it, each list oldest first.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchivedCase {
+    #[serde(flatten)]
+    pub case: Case,
+    #[serde(default)]
+    pub hearings: Vec<Hearing>,
+    #[serde(default)]
+    pub edits: Vec<CaseEdit>,
+    #[serde(default)]
+    pub transfers: Vec<CaseTransfer>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ArchiveInfo {
+    pub path: String,
+    pub mediators: usize,
+    pub cases: usize,
+    pub hearings: usize,
+    pub audit_entries: usize,
+    /// Whether the audit log's hash chain held in the register the archive
+    /// was written from, or in the register it was just imported into.
+    pub audit_verified: bool,
+}
+
+impl Db {
+    /// Writes the whole register to `path` as a `RegisterArchive`. Everything
+    /// is read in one transaction, so a case saved halfway through cannot
+    /// show up without its hearings.
+    pub fn export_json(&self, path: &Path) -> AppResult<ArchiveInfo> {
+        let (archive, verified) = {
+            let conn = self.reader()?;
+            let tx = conn.unchecked_transaction()?;
+            let archive = read_archive(&tx, &self.operator())?;
+            (archive, verify_chain(&tx)?.ok)
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let partial = with_suffix(path, "partial");
+        let mut file = BufWriter::new(File::create(&partial)?);
+        serde_json::to_writer_pretty(&mut file, &archive).map_err(|e| AppError::ExportFailed {
+            path: path.display().to_string(),
+            detail: e.to_string(),
+        })?;
+        file.flush()?;
+        drop(file);
+        fs::rename(&partial, path)?;
+
+        println!("Register archived to {}", path.display());
+        Ok(archive.info(path, verified))
+    }
+
+    /// Rebuilds the register from an archive written by `export_json`.
+    ///
+    /// Only an empty register is accepted, since the archive's ids would
+    /// clash with anything already there: open a new register, then import.
+    /// The disposal reasons every new register starts with are replaced by
+    /// the archive's. Nothing is kept unless the whole archive goes in.
+    pub fn import_json(&self, path: &Path) -> AppResult<ArchiveInfo> {
+        let archive = RegisterArchive::read(path)?;
+        let operator = self.operator();
+        let conn = self.writer();
+        let tx = conn.unchecked_transaction()?;
+
+        let in_use: bool = tx.query_row(
+            "SELECT EXISTS (SELECT 1 FROM case_table) OR EXISTS (SELECT 1 FROM mediators)
+                 OR EXISTS (SELECT 1 FROM import_profiles) OR EXISTS (SELECT 1 FROM audit_log)",
+            [],
+            |row| row.get(0),
+        )?;
+        if in_use {
+            return Err(AppError::RegisterNotEmpty);
+        }
+
+        // A foreign key pointing nowhere or a duplicate id means the file was
+        // edited by hand; say so rather than reporting a database fault.
+        restore(&tx, &archive).map_err(|e| AppError::InvalidArchive {
+            path: path.display().to_string(),
+            detail: e.to_string(),
+        })?;
+
+        let verified = verify_chain(&tx)?.ok;
+        audit::record(
+            &tx,
+            &operator,
+            "import_json",
+            None,
+            None,
+            Some(&json!({
+                "imported_from": path.display().to_string(),
+                "exported_at": archive.exported_at,
+                "exported_by": archive.exported_by,
+                "cases": archive.cases.len(),
+                "audit_verified": verified,
+            })),
+        )?;
+        tx.commit()?;
+
+        println!("Register rebuilt from {}", path.display());
+        Ok(archive.info(path, verified))
+    }
+}
+
+impl RegisterArchive {
+    fn read(path: &Path) -> AppResult<Self> {
+        let invalid = |detail: String| AppError::InvalidArchive {
+            path: path.display().to_string(),
+            detail,
+        };
+
+        let file = File::open(path).map_err(|e| invalid(e.to_string()))?;
+        let archive: RegisterArchive =
+            serde_json::from_reader(BufReader::new(file)).map_err(|e| invalid(e.to_string()))?;
+
+        if archive.format != ARCHIVE_FORMAT {
+            return Err(invalid(format!("it is not a register archive (format \"{}\")", archive.format)));
+        }
+        if archive.version > ARCHIVE_VERSION {
+            return Err(invalid(format!(
+                "it was written by a newer version of the app (archive version {}, this app reads up to {})",
+                archive.version, ARCHIVE_VERSION
+            )));
+        }
+
+        Ok(archive)
+    }
+
+    fn info(&self, path: &Path, audit_verified: bool) -> ArchiveInfo {
+        ArchiveInfo {
+            path: path.display().to_string(),
+            mediators: self.mediators.len(),
+            cases: self.cases.len(),
+            hearings: self.cases.iter().map(|c| c.hearings.len()).sum(),
+            audit_entries: self.audit_log.len(),
+            audit_verified,
+        }
+    }
+}
+
+fn read_archive(conn: &Connection, operator: &str) -> AppResult<RegisterArchive> {
+    let mut hearings = by_case(
+        conn,
+        &format!("SELECT {}, h.case_id FROM hearings h ORDER BY h.id", HEARING_COLUMNS),
+        8,
+        hearing_from_row,
+    )?;
+    let mut edits = by_case(
+        conn,
+        &format!("SELECT {}, case_id FROM case_edits ORDER BY id", CASE_EDIT_COLUMNS),
+        5,
+        case_edit_from_row,
+    )?;
+    let mut transfers = by_case(
+        conn,
+        &format!("SELECT {}, t.case_id FROM case_transfers t ORDER BY t.id", CASE_TRANSFER_COLUMNS),
+        8,
+        case_transfer_from_row,
+    )?;
+
+    let cases = all(conn, &format!("SELECT {} FROM case_table ORDER BY id", CASE_COLUMNS), case_from_row)?
+        .into_iter()
+        .map(|case| ArchivedCase {
+            hearings: hearings.remove(&case.id).unwrap_or_default(),
+            edits: edits.remove(&case.id).unwrap_or_default(),
+            transfers: transfers.remove(&case.id).unwrap_or_default(),
+            case,
+        })
+        .collect();
+
+    Ok(RegisterArchive {
+        format: ARCHIVE_FORMAT.to_string(),
+        version: ARCHIVE_VERSION,
+        schema_version: migrations::current_version(conn)?,
+        exported_at: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        exported_by: operator.to_string(),
+        centre_name: Some(roster::centre_name(conn)?),
+        mediators: all(conn, &format!("SELECT {} FROM mediators ORDER BY id", MEDIATOR_COLUMNS), mediator_from_row)?,
+        disposal_reasons: all(
+            conn,
+            &format!("SELECT {} FROM disposal_reasons ORDER BY id", DISPOSAL_REASON_COLUMNS),
+            disposal_reason_from_row,
+        )?,
+        cases,
+        import_profiles: all(
+            conn,
+            &format!("SELECT {} FROM import_profiles ORDER BY id", IMPORT_PROFILE_COLUMNS),
+            import_profile_from_row,
+        )?,
+        audit_log: all(conn, &format!("SELECT {} FROM audit_log ORDER BY id", AUDIT_COLUMNS), audit_entry_from_row)?,
+    })
+}
+
+fn all<T>(conn: &Connection, sql: &str, from_row: fn(&rusqlite::Row) -> rusqlite::Result<T>) -> rusqlite::Result<Vec<T>> {
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt.query_map([], from_row)?.collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(rows)
+}
+
+/// Rows of `sql` grouped by the case id in column `case_id_idx`.
+fn by_case<T>(
+    conn: &Connection,
+    sql: &str,
+    case_id_idx: usize,
+    from_row: fn(&rusqlite::Row) -> rusqlite::Result<T>,
+) -> rusqlite::Result<HashMap<i32, Vec<T>>> {
+    let mut grouped: HashMap<i32, Vec<T>> = HashMap::new();
+    let mut stmt = conn.prepare(sql)?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        grouped.entry(row.get(case_id_idx)?).or_default().push(from_row(row)?);
+    }
+    Ok(grouped)
+}
+
+/// Inserts every record of `archive` under its original id, parents first.
+fn restore(conn: &Connection, archive: &RegisterArchive) -> rusqlite::Result<()> {
+    if let Some(name) = &archive.centre_name {
+        conn.execute("UPDATE centre_details SET name = ?1 WHERE id = 1", [name])?;
+    }
+
+    for m in &archive.mediators {
+        conn.execute(
+            "INSERT INTO mediators (id, display_name, honorific, phone, email, active, joined_on, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                m.id,
+                m.display_name,
+                m.honorific,
+                m.phone,
+                m.email,
+                m.active,
+                m.joined_on.map(|d| d.to_string()),
+                m.created_at,
+            ],
+        )?;
+    }
+
+    conn.execute("DELETE FROM disposal_reasons", [])?;
+    for r in &archive.disposal_reasons {
+        conn.execute(
+            "INSERT INTO disposal_reasons (id, label, active, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![r.id, r.label, r.active, r.created_at],
+        )?;
+    }
+
+    for archived in &archive.cases {
+        let case = &archived.case;
+        conn.execute(
+            "INSERT INTO case_table (
+                id, case_no, year, nature_of_case, received_from, date, time_slot,
+                party1, party2, mediator_id, ndoh_date, ndoh_time, disposal_of_case,
+                connected, disposal_date, disposal_reason_id, disposal_remarks
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)",
+            params![
+                case.id,
+                case.case_no,
+                case.year,
+                case.nature_of_case,
+                case.received_from,
+                case.date.to_string(),
+                case.time_slot.to_string(),
+                case.party1,
+                case.party2,
+                case.mediator_id,
+                case.ndoh_date.map(|d| d.to_string()),
+                case.ndoh_time.map(|t| t.to_string()),
+                case.disposal_of_case.map(|d| d.code()),
+                case.connected,
+                case.disposal_date.map(|d| d.to_string()),
+                case.disposal_reason_id,
+                case.disposal_remarks,
+            ],
+        )?;
+
+        for h in &archived.hearings {
+            conn.execute(
+                "INSERT INTO hearings (id, case_id, hearing_date, hearing_time, mediator_id, outcome, remarks, recorded_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    h.id,
+                    case.id,
+                    h.hearing_date.to_string(),
+                    h.hearing_time.map(|t| t.to_string()),
+                    h.mediator_id,
+                    h.outcome.map(|o| o.code()),
+                    h.remarks,
+                    h.recorded_at,
+                ],
+            )?;
+        }
+
+        for e in &archived.edits {
+            conn.execute(
+                "INSERT INTO case_edits (id, case_id, edited_at, edited_by, reason, changes)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![e.id, case.id, e.edited_at, e.edited_by, e.reason, e.changes.to_string()],
+            )?;
+        }
+
+        for t in &archived.transfers {
+            conn.execute(
+                "INSERT INTO case_transfers (id, case_id, from_mediator_id, to_mediator_id, transferred_on, reason, recorded_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    t.id,
+                    case.id,
+                    t.from_mediator_id,
+                    t.to_mediator_id,
+                    t.transferred_on.to_string(),
+                    t.reason,
+                    t.recorded_at,
+                ],
+            )?;
+        }
+    }
+
+    for p in &archive.import_profiles {
+        conn.execute(
+            "INSERT INTO import_profiles (id, name, mapping, updated_at) VALUES (?1, ?2, ?3, ?4)",
+            params![p.id, p.name, json!(p.mapping).to_string(), p.updated_at],
+        )?;
+    }
+
+    // Copied exactly as stored, hashes included, so `verify_audit_log` keeps
+    // vouching for the history that came before the import.
+    for a in &archive.audit_log {
+        conn.execute(
+            "INSERT INTO audit_log (
+                id, recorded_at, operator, command, case_id, case_no, year,
+                before_value, after_value, prev_hash, hash
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                a.id,
+                a.recorded_at,
+                a.operator,
+                a.command,
+                a.case_id,
+                a.case_no,
+                a.year,
+                a.before.as_ref().map(Value::to_string),
+                a.after.as_ref().map(Value::to_string),
+                a.prev_hash,
+                a.hash,
+            ],
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Local;
+
+    use super::*;
+    use crate::database_init::{CaseKey, DisposalOfCase};
+    use crate::mediators::MediatorDetails;
+    use crate::roster::Roster;
+    use crate::state::AppState;
+    use crate::test_support::{dispose, intake, register, ScratchDir};
+
+    const TABLES: [&str; 7] = [
+        "mediators",
+        "disposal_reasons",
+        "case_table",
+        "hearings",
+        "case_edits",
+        "case_transfers",
+        "audit_log",
+    ];
+
+    fn row_counts(db: &Db) -> Vec<i64> {
+        let conn = db.reader().unwrap();
+        TABLES
+            .iter()
+            .map(|table| conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0)).unwrap())
+            .collect()
+    }
+
+    // Three cases with a hearing, a correction and a transfer between them.
+    fn register_in_use(dir: &ScratchDir) -> Db {
+        let db = register(dir);
+        db.set_centre_name("Rohini Mediation Centre").unwrap();
+        let second = db
+            .add_mediator(MediatorDetails {
+                display_name: "Surender Singh".to_string(),
+                honorific: "Sh".to_string(),
+                phone: None,
+                email: None,
+                joined_on: None,
+            })
+            .unwrap();
+        for case_no in 1..=3 {
+            db.insert_case(intake(case_no, 2024)).unwrap();
+        }
+        let today = Local::now().date_naive();
+        dispose(&db, 1, 2024, DisposalOfCase::Settled, today);
+
+        let mut corrected = intake(2, 2024);
+        corrected.party1 = "Ramesh Chand".to_string();
+        db.edit_case(CaseKey { case_no: 2, year: 2024 }, corrected, "clerk", "party misspelt")
+            .unwrap();
+        db.reassign_case(CaseKey { case_no: 3, year: 2024 }, second.id, today, "leave")
+            .unwrap();
+        db
+    }
+
+    // A new register next to a roster file, opened the way the app does.
+    fn new_register(dir: &ScratchDir, seed: bool) -> Db {
+        let roster = toml::to_string(&Roster::from_names(["Sh Nishant Dewan"])).unwrap();
+        fs::write(dir.join("mediators.toml"), roster).unwrap();
+        AppState::new(dir.join("recent.json"))
+            .open(dir.join("cases.db"), seed)
+            .unwrap()
+    }
+
+    #[test]
+    fn archive_round_trips_into_a_new_register() {
+        let from_dir = ScratchDir::new("archive-from");
+        let from = register_in_use(&from_dir);
+        let path = from_dir.join("register.json");
+        let exported = from.export_json(&path).unwrap();
+        assert!(exported.audit_verified);
+        assert_eq!((exported.cases, exported.hearings, exported.mediators), (3, 1, 2));
+
+        let to_dir = ScratchDir::new("archive-to");
+        let to = new_register(&to_dir, false);
+        assert!(to.needs_setup().unwrap());
+        let imported = to.import_json(&path).unwrap();
+        assert!(imported.audit_verified);
+
+        // Everything came across, plus the audit entry for the import itself.
+        let mut expected = row_counts(&from);
+        *expected.last_mut().unwrap() += 1;
+        assert_eq!(row_counts(&to), expected);
+        assert!(to.verify_audit_log().unwrap().ok);
+        assert_eq!(to.centre_name().unwrap(), "Rohini Mediation Centre");
+
+        let hits = to.search_cases("Ramesh", 10).unwrap();
+        let found: Vec<(i32, i32)> = hits.iter().map(|h| (h.case.case_no, h.case.year)).collect();
+        assert_eq!(found, [(2, 2024)]);
+        let moved = to.get_case_timeline(CaseKey { case_no: 3, year: 2024 }).unwrap();
+        assert_eq!(moved.transfers.len(), 1);
+    }
+
+    #[test]
+    fn a_register_seeded_from_its_roster_file_refuses_an_archive() {
+        let from_dir = ScratchDir::new("archive-seeded-from");
+        let path = from_dir.join("register.json");
+        register_in_use(&from_dir).export_json(&path).unwrap();
+
+        let to_dir = ScratchDir::new("archive-seeded-to");
+        let to = new_register(&to_dir, true);
+        assert!(!to.needs_setup().unwrap());
+        assert!(matches!(to.import_json(&path), Err(AppError::RegisterNotEmpty)));
+    }
+}
@@ -0,0 +1,471 @@
+use chrono::{Local, NaiveDate};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::database_init::{CaseKey, Db};
+use crate::error::AppResult;
+use crate::pool;
+
+/// `prev_hash` of the first entry in the log.
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+pub(crate) const AUDIT_COLUMNS: &str = "id, recorded_at, operator, command, case_id, case_no, year, \
+     before_value, after_value, prev_hash, hash";
+
+/// The case a write touched, as it was identified at the time.
+#[derive(Debug, Clone, Copy)]
+pub struct AuditCase {
+    pub id: i32,
+    pub key: CaseKey,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub recorded_at: String,
+    pub operator: String,
+    pub command: String,
+    pub case_id: Option<i32>,
+    pub case_no: Option<i32>,
+    pub year: Option<i32>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditVerification {
+    pub ok: bool,
+    pub entries_checked: usize,
+    /// First entry whose hash or link to the previous entry doesn't hold.
+    pub first_bad_id: Option<i64>,
+    pub problem: Option<String>,
+    /// When the backup whose record of the log it was also checked against
+    /// was taken, if there has been one since backups started keeping it.
+    pub anchored_to: Option<String>,
+}
+
+/// Appends one entry to `audit_log`, chained to the entry before it. Call it
+/// on the same transaction as the write it describes so neither can land
+/// without the other.
+pub(crate) fn record(
+    conn: &Connection,
+    operator: &str,
+    command: &str,
+    case: Option<AuditCase>,
+    before: Option<&Value>,
+    after: Option<&Value>,
+) -> rusqlite::Result<()> {
+    let prev_hash: String = conn
+        .query_row("SELECT hash FROM audit_log ORDER BY id DESC LIMIT 1", [], |row| row.get(0))
+        .optional()?
+        .unwrap_or_else(|| GENESIS_HASH.to_string());
+
+    let recorded_at = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let before = before.map(Value::to_string);
+    let after = after.map(Value::to_string);
+    let case_id = case.map(|c| c.id);
+    let case_no = case.map(|c| c.key.case_no);
+    let year = case.map(|c| c.key.year);
+
+    let hash = entry_hash(
+        &prev_hash,
+        &recorded_at,
+        operator,
+        command,
+        case_id,
+        case_no,
+        year,
+        before.as_deref(),
+        after.as_deref(),
+    );
+
+    conn.execute(
+        "INSERT INTO audit_log (
+            recorded_at, operator, command, case_id, case_no, year,
+            before_value, after_value, prev_hash, hash
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            recorded_at,
+            operator,
+            command,
+            case_id,
+            case_no,
+            year,
+            before,
+            after,
+            prev_hash,
+            hash
+        ],
+    )?;
+
+    Ok(())
+}
+
+// Every field that describes the write goes into the hash, each on its own
+// line so that moving text from one field to the next changes the result.
+#[allow(clippy::too_many_arguments)]
+fn entry_hash(
+    prev_hash: &str,
+    recorded_at: &str,
+    operator: &str,
+    command: &str,
+    case_id: Option<i32>,
+    case_no: Option<i32>,
+    year: Option<i32>,
+    before: Option<&str>,
+    after: Option<&str>,
+) -> String {
+    let optional = |v: Option<String>| v.unwrap_or_else(|| "-".to_string());
+    let text = [
+        prev_hash.to_string(),
+        recorded_at.to_string(),
+        operator.to_string(),
+        command.to_string(),
+        optional(case_id.map(|n| n.to_string())),
+        optional(case_no.map(|n| n.to_string())),
+        optional(year.map(|y| y.to_string())),
+        optional(before.map(str::to_string)),
+        optional(after.map(str::to_string)),
+    ]
+    .join("\n");
+
+    format!("{:x}", Sha256::digest(text.as_bytes()))
+}
+
+impl Db {
+    /// Name recorded against every write until it is changed.
+    pub fn set_operator(&self, operator: &str) {
+        *pool::lock(&self.operator) = operator.to_string();
+    }
+
+    pub(crate) fn operator(&self) -> String {
+        pool::lock(&self.operator).clone()
+    }
+
+    /// Walks the whole log from the first entry, recomputing every hash and
+    /// checking each entry points at the one before it. An entry that was
+    /// edited, removed or inserted out of turn breaks the chain from there on.
+    ///
+    /// The chain alone cannot show entries cut off the end, or the whole log
+    /// rewritten with fresh hashes. Each successful backup records how long
+    /// the log was and the hash of its newest entry, and the log must still
+    /// hold that entry at that place. Someone who also edits `backup_runs`
+    /// can hide even that; the backup files themselves are the final word.
+    pub fn verify_audit_log(&self) -> AppResult<AuditVerification> {
+        verify_chain(&*self.reader()?)
+    }
+
+    /// Entries for one case (under any number it has carried), between two
+    /// dates, or both; oldest first.
+    pub fn query_audit_log(
+        &self,
+        case: Option<CaseKey>,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> AppResult<Vec<AuditEntry>> {
+        let conn = self.reader()?;
+
+        let mut query = format!("SELECT {} FROM audit_log WHERE 1 = 1", AUDIT_COLUMNS);
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+
+        if let Some(key) = case {
+            let case_id: i32 = conn
+                .query_row(
+                    "SELECT id FROM case_table WHERE case_no = ?1 AND year = ?2",
+                    params![key.case_no, key.year],
+                    |row| row.get(0),
+                )
+                .optional()?
+                .ok_or(key.not_found())?;
+            query += " AND case_id = ?";
+            params.push(Box::new(case_id));
+        }
+
+        if let Some(from) = from {
+            query += " AND date(recorded_at) >= ?";
+            params.push(Box::new(from.to_string()));
+        }
+
+        if let Some(to) = to {
+            query += " AND date(recorded_at) <= ?";
+            params.push(Box::new(to.to_string()));
+        }
+
+        query += " ORDER BY id";
+
+        let mut stmt = conn.prepare(&query)?;
+        let params_ref: Vec<&dyn rusqlite::ToSql> = params.iter().map(|b| b.as_ref()).collect();
+
+        let entries = stmt
+            .query_map(&params_ref[..], audit_entry_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(entries)
+    }
+}
+
+/// Checks the hash chain of the log on `conn`, as `Db::verify_audit_log`
+/// describes.
+pub(crate) fn verify_chain(conn: &Connection) -> AppResult<AuditVerification> {
+    let anchor: Option<(String, usize, String)> = conn
+        .query_row(
+            "SELECT started_at, audit_entries, audit_head FROM backup_runs
+             WHERE error IS NULL AND audit_head IS NOT NULL
+             ORDER BY id DESC LIMIT 1",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()?;
+    let anchored_to = anchor.as_ref().map(|(started_at, _, _)| started_at.clone());
+    let failed = |checked: usize, first_bad_id: Option<i64>, problem: String| AuditVerification {
+        ok: false,
+        entries_checked: checked,
+        first_bad_id,
+        problem: Some(problem),
+        anchored_to: anchored_to.clone(),
+    };
+
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM audit_log ORDER BY id", AUDIT_COLUMNS))?;
+    let mut rows = stmt.query([])?;
+
+    let mut expected_prev = GENESIS_HASH.to_string();
+    let mut checked = 0;
+
+    while let Some(row) = rows.next()? {
+        let id: i64 = row.get(0)?;
+        let prev_hash: String = row.get(9)?;
+        let hash: String = row.get(10)?;
+
+        let problem = if prev_hash != expected_prev {
+            Some("it does not follow the entry before it, so an entry may have been removed")
+        } else if hash
+            != entry_hash(
+                &prev_hash,
+                &row.get::<_, String>(1)?,
+                &row.get::<_, String>(2)?,
+                &row.get::<_, String>(3)?,
+                row.get(4)?,
+                row.get(5)?,
+                row.get(6)?,
+                row.get::<_, Option<String>>(7)?.as_deref(),
+                row.get::<_, Option<String>>(8)?.as_deref(),
+            )
+        {
+            Some("its contents no longer match its hash, so it may have been edited")
+        } else {
+            None
+        };
+
+        if let Some(problem) = problem {
+            return Ok(failed(checked, Some(id), format!("Audit entry {}: {}.", id, problem)));
+        }
+
+        checked += 1;
+        if let Some((started_at, entries, head)) = &anchor {
+            if checked == *entries && hash != *head {
+                return Ok(failed(
+                    checked - 1,
+                    Some(id),
+                    format!(
+                        "Audit entry {}: it is not the entry the backup of {} ended on, so the log may have been rewritten.",
+                        id, started_at
+                    ),
+                ));
+            }
+        }
+        expected_prev = hash;
+    }
+
+    if let Some((started_at, entries, _)) = &anchor {
+        if checked < *entries {
+            return Ok(failed(
+                checked,
+                None,
+                format!(
+                    "The log has {} entries but the backup of {} saw {}, so entries may have been removed from the end.",
+                    checked, started_at, entries
+                ),
+            ));
+        }
+    }
+
+    Ok(AuditVerification {
+        ok: true,
+        entries_checked: checked,
+        first_bad_id: None,
+        problem: None,
+        anchored_to,
+    })
+}
+
+pub(crate) fn audit_entry_from_row(row: &rusqlite::Row) -> rusqlite::Result<AuditEntry> {
+    Ok(AuditEntry {
+        id: row.get(0)?,
+        recorded_at: row.get(1)?,
+        operator: row.get(2)?,
+        command: row.get(3)?,
+        case_id: row.get(4)?,
+        case_no: row.get(5)?,
+        year: row.get(6)?,
+        before: json_column(row, 7)?,
+        after: json_column(row, 8)?,
+        prev_hash: row.get(9)?,
+        hash: row.get(10)?,
+    })
+}
+
+fn json_column(row: &rusqlite::Row, idx: usize) -> rusqlite::Result<Option<Value>> {
+    match row.get::<_, Option<String>>(idx)? {
+        Some(text) => serde_json::from_str(&text)
+            .map(Some)
+            .map_err(|_| crate::database_init::bad_stored_value(row, idx, &text)),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::migrations;
+    use crate::test_support::{register, ScratchDir};
+
+    // A fresh log with three entries, its append-only triggers dropped so the
+    // tests can tamper with it the way someone with the file open could.
+    fn log_of_three() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        migrations::migrate(&conn, Path::new("cases.db")).unwrap();
+        for n in 1..=3 {
+            let before = serde_json::json!({ "remarks": format!("before {}", n) });
+            let after = serde_json::json!({ "remarks": format!("after {}", n) });
+            record(&conn, "clerk", "update_case", None, Some(&before), Some(&after)).unwrap();
+        }
+        conn.execute_batch(
+            "DROP TRIGGER audit_log_no_update;
+             DROP TRIGGER audit_log_no_delete;",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn untouched_log_verifies() {
+        let result = verify_chain(&log_of_three()).unwrap();
+        assert!(result.ok);
+        assert_eq!(result.entries_checked, 3);
+        assert_eq!(result.first_bad_id, None);
+    }
+
+    #[test]
+    fn edited_before_value_is_caught_at_that_entry() {
+        let conn = log_of_three();
+        conn.execute(
+            "UPDATE audit_log SET before_value = '{\"remarks\":\"altered\"}' WHERE id = 2",
+            [],
+        )
+        .unwrap();
+
+        let result = verify_chain(&conn).unwrap();
+        assert!(!result.ok);
+        assert_eq!(result.entries_checked, 1);
+        assert_eq!(result.first_bad_id, Some(2));
+        assert!(result.problem.unwrap().contains("edited"));
+    }
+
+    #[test]
+    fn deleted_middle_entry_is_caught_at_the_next_one() {
+        let conn = log_of_three();
+        conn.execute("DELETE FROM audit_log WHERE id = 2", []).unwrap();
+
+        let result = verify_chain(&conn).unwrap();
+        assert!(!result.ok);
+        assert_eq!(result.entries_checked, 1);
+        assert_eq!(result.first_bad_id, Some(3));
+        assert!(result.problem.unwrap().contains("removed"));
+    }
+
+    #[test]
+    fn reordered_entries_are_caught() {
+        let conn = log_of_three();
+        conn.execute_batch(
+            "UPDATE audit_log SET id = 102 WHERE id = 2;
+             UPDATE audit_log SET id = 2 WHERE id = 3;
+             UPDATE audit_log SET id = 3 WHERE id = 102;",
+        )
+        .unwrap();
+
+        let result = verify_chain(&conn).unwrap();
+        assert!(!result.ok);
+        assert_eq!(result.first_bad_id, Some(2));
+    }
+
+    // Records a successful backup taken when the log ended at entry `id`.
+    fn anchor_at(conn: &Connection, id: i64) {
+        conn.execute(
+            "INSERT INTO backup_runs (started_at, kind, audit_entries, audit_head)
+             SELECT '2024-03-01 18:00:00', 'daily', COUNT(*), MAX(CASE WHEN id = ?1 THEN hash END)
+             FROM audit_log WHERE id <= ?1",
+            [id],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn entries_added_since_the_last_backup_verify() {
+        let conn = log_of_three();
+        anchor_at(&conn, 2);
+
+        let result = verify_chain(&conn).unwrap();
+        assert!(result.ok);
+        assert_eq!(result.entries_checked, 3);
+        assert_eq!(result.anchored_to.as_deref(), Some("2024-03-01 18:00:00"));
+    }
+
+    #[test]
+    fn entries_cut_off_the_end_are_caught_by_the_last_backup() {
+        let conn = log_of_three();
+        anchor_at(&conn, 3);
+        conn.execute("DELETE FROM audit_log WHERE id = 3", []).unwrap();
+
+        let result = verify_chain(&conn).unwrap();
+        assert!(!result.ok);
+        assert_eq!(result.entries_checked, 2);
+        assert_eq!(result.first_bad_id, None);
+        assert!(result.problem.unwrap().contains("removed from the end"));
+    }
+
+    #[test]
+    fn a_rewritten_log_is_caught_by_the_last_backup() {
+        let conn = log_of_three();
+        anchor_at(&conn, 3);
+        conn.execute("DELETE FROM audit_log", []).unwrap();
+        for n in 1..=3 {
+            let after = serde_json::json!({ "remarks": format!("rewritten {}", n) });
+            record(&conn, "clerk", "update_case", None, None, Some(&after)).unwrap();
+        }
+
+        let result = verify_chain(&conn).unwrap();
+        assert!(!result.ok);
+        assert_eq!(result.entries_checked, 2);
+        assert_eq!(result.first_bad_id, Some(6));
+        assert!(result.problem.unwrap().contains("rewritten"));
+    }
+
+    #[test]
+    fn each_backup_records_the_head_of_the_log() {
+        let dir = ScratchDir::new("audit-anchor");
+        let db = register(&dir);
+        db.run_manual_backup(&dir.join("backups").join("manual.db")).unwrap();
+
+        let entries = db.query_audit_log(None, None, None).unwrap();
+        let run = db.backup_status().unwrap().last_success.unwrap();
+        assert_eq!(run.audit_entries, Some(entries.len()));
+        assert_eq!(run.audit_head.as_ref(), entries.last().map(|e| &e.hash));
+        assert_eq!(db.verify_audit_log().unwrap().anchored_to, Some(run.started_at));
+    }
+}
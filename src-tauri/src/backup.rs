@@ -0,0 +1,252 @@
+use chrono::Local;
+use rusqlite::{Connection, OpenFlags, MAIN_DB};
+use serde::Serialize;
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::audit;
+use crate::database_init::Db;
+use crate::error::{AppError, AppResult};
+use crate::migrations;
+use crate::pool;
+
+#[derive(Debug, Serialize)]
+pub struct BackupInfo {
+    pub path: String,
+    pub size_bytes: u64,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RestoreInfo {
+    pub restored_from: String,
+    /// Copy of the register as it was just before the restore.
+    pub previous_copy: String,
+    pub schema_version: i32,
+}
+
+impl Db {
+    /// Default folder for manual backups: `backups/` next to the register.
+    pub fn backup_dir(&self) -> PathBuf {
+        self.path
+            .parent()
+            .map(|dir| dir.join("backups"))
+            .unwrap_or_else(|| PathBuf::from("backups"))
+    }
+
+    /// Writes a consistent copy of the live register to `dest` with SQLite's
+    /// online backup API, so it is safe to run while the app is in use. The
+    /// copy is built under a temporary name and only renamed into place once
+    /// it is complete. The folder must already exist.
+    pub fn backup_to(&self, dest: &Path) -> AppResult<BackupInfo> {
+        let partial = with_suffix(dest, "partial");
+        {
+            let conn = self.reader()?;
+            conn.backup(MAIN_DB, &partial, None)?;
+        }
+        fs::rename(&partial, dest)?;
+
+        let info = BackupInfo {
+            path: dest.display().to_string(),
+            size_bytes: fs::metadata(dest)?.len(),
+            created_at: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        };
+
+        println!("Backup written to {} ({} bytes)", info.path, info.size_bytes);
+        Ok(info)
+    }
+
+    /// Replaces the live register with the backup at `src`.
+    ///
+    /// The backup is checked first: it must pass `PRAGMA integrity_check`,
+    /// hold a case register, and not come from a newer app. The current
+    /// register is copied aside, then the backup is loaded into a fresh
+    /// connection that takes the place of the old one, so the app carries on
+    /// without a restart. Older backups are migrated up in a staging file
+    /// first, so one that fails to migrate leaves the live register alone.
+    pub fn restore_from(&self, src: &Path) -> AppResult<RestoreInfo> {
+        if fs::canonicalize(src).ok() == fs::canonicalize(&self.path).ok() {
+            return Err(AppError::InvalidBackup {
+                path: src.display().to_string(),
+                detail: "this is the register that is open now".to_string(),
+            });
+        }
+
+        let version = check_backup(src)?;
+        let operator = self.operator();
+
+        let mut conn = self.writer();
+
+        let stamp = Local::now().format("%Y%m%d-%H%M%S");
+        let previous = with_suffix(&self.path, &format!("pre-restore-{}.bak", stamp));
+        conn.backup(MAIN_DB, &previous, None)?;
+
+        // No other write can start while `conn` is held, so the old writer
+        // can be dropped as soon as the new one is ready. Readers pick up the
+        // restored pages on their next query.
+        let staging = with_suffix(&self.path, "restoring");
+        let result = stage_restore(src, &staging, &self.path).and_then(|()| {
+            let mut restored = pool::open_connection(&self.path, false)?;
+            restored.restore(MAIN_DB, &staging, None::<fn(rusqlite::backup::Progress)>)?;
+            restored.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))?;
+            Ok(restored)
+        });
+        let _ = fs::remove_file(&staging);
+        let restored = result?;
+
+        audit::record(
+            &restored,
+            &operator,
+            "restore_db",
+            None,
+            None,
+            Some(&json!({
+                "restored_from": src.display().to_string(),
+                "schema_version": version,
+                "previous_copy": previous.display().to_string(),
+            })),
+        )?;
+
+        *conn = restored;
+
+        println!("Restored register from {}", src.display());
+        Ok(RestoreInfo {
+            restored_from: src.display().to_string(),
+            previous_copy: previous.display().to_string(),
+            schema_version: version,
+        })
+    }
+}
+
+/// Loads the backup at `src` into `staging` and migrates it up to this
+/// build's schema. Pre-migration copies are named after `db_path`, as they
+/// would be for the live register.
+fn stage_restore(src: &Path, staging: &Path, db_path: &Path) -> AppResult<()> {
+    let mut conn = pool::open_connection(staging, false)?;
+    conn.restore(MAIN_DB, src, None::<fn(rusqlite::backup::Progress)>)?;
+    migrations::migrate(&conn, db_path)?;
+    Ok(())
+}
+
+/// Opens `src` read-only and returns its schema version if it is a sound
+/// register this build can load.
+fn check_backup(src: &Path) -> AppResult<i32> {
+    let invalid = |detail: String| AppError::InvalidBackup {
+        path: src.display().to_string(),
+        detail,
+    };
+
+    if !src.is_file() {
+        return Err(invalid("file not found".to_string()));
+    }
+
+    let conn = Connection::open_with_flags(src, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| invalid(e.to_string()))?;
+
+    let integrity: String = conn
+        .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+        .map_err(|e| invalid(e.to_string()))?;
+    if integrity != "ok" {
+        return Err(invalid(format!("integrity check failed: {}", integrity)));
+    }
+
+    let has_register: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'case_table')",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_register {
+        return Err(invalid("not a case register".to_string()));
+    }
+
+    let version = migrations::current_version(&conn)?;
+    let latest = migrations::latest_version();
+    if version > latest {
+        return Err(AppError::SchemaTooNew {
+            found: version,
+            supported: latest,
+        });
+    }
+
+    Ok(version)
+}
+
+pub(crate) fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations::MIGRATIONS;
+    use crate::test_support::ScratchDir;
+
+    // A register as the first release left it: schema 1, one case.
+    fn version_one_backup(path: &Path) -> Connection {
+        let conn = Connection::open(path).unwrap();
+        (MIGRATIONS[0].up)(&conn).unwrap();
+        conn.pragma_update(None, "user_version", 1).unwrap();
+        conn.execute(
+            "INSERT INTO case_table (
+                case_no, year, nature_of_case, received_from, date, time_slot,
+                party1, party2, assigned_to
+            ) VALUES (7, 2019, 'MACT', 'Court', '2019-03-01', '10:00', 'A', 'B', 'Mediator')",
+            [],
+        )
+        .unwrap();
+        conn
+    }
+
+    fn schema_version(db: &Db) -> i32 {
+        migrations::current_version(&db.reader().unwrap()).unwrap()
+    }
+
+    fn has_case_seven(db: &Db) -> bool {
+        db.reader()
+            .unwrap()
+            .query_row(
+                "SELECT EXISTS (SELECT 1 FROM case_table WHERE case_no = 7 AND year = 2019)",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn older_backup_is_migrated_as_it_is_restored() {
+        let dir = ScratchDir::new("restore-older");
+        let db = Db::init(dir.join("cases.db")).unwrap();
+        let src = dir.join("old.db");
+        drop(version_one_backup(&src));
+
+        let info = db.restore_from(&src).unwrap();
+
+        assert_eq!(info.schema_version, 1);
+        assert_eq!(schema_version(&db), migrations::latest_version());
+        assert!(has_case_seven(&db));
+        assert!(audit::verify_chain(&db.reader().unwrap()).unwrap().ok);
+        assert!(!with_suffix(&db.path, "restoring").exists());
+    }
+
+    #[test]
+    fn backup_that_fails_to_migrate_leaves_the_live_register_alone() {
+        let dir = ScratchDir::new("restore-failed");
+        let db = Db::init(dir.join("cases.db")).unwrap();
+        let src = dir.join("old.db");
+        // Schema 2 creates `hearings`, so a stray table by that name stops it.
+        version_one_backup(&src)
+            .execute_batch("CREATE TABLE hearings (note TEXT)")
+            .unwrap();
+
+        let err = db.restore_from(&src).unwrap_err();
+
+        assert_eq!(err.code(), "MIGRATION_FAILED");
+        assert_eq!(schema_version(&db), migrations::latest_version());
+        assert!(!has_case_seven(&db));
+        assert!(!with_suffix(&db.path, "restoring").exists());
+    }
+}
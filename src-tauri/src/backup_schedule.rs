@@ -0,0 +1,578 @@
+use chrono::{Datelike, Duration, Local, NaiveDateTime, NaiveTime};
+use rusqlite::{params, OptionalExtension};
+use serde::Serialize;
+use serde_json::json;
+use std::cmp::Reverse;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::audit;
+use crate::backup::BackupInfo;
+use crate::database_init::{parse_optional_time_column, Db};
+use crate::error::{AppError, AppResult};
+
+/// Scheduled snapshots are named `cases-auto-<stamp>.db`. Only files with
+/// this prefix are ever pruned, so manual backups in the same folder are safe.
+const AUTO_PREFIX: &str = "cases-auto-";
+const STAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+const RECORDED_AT_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// How long to wait before trying again after a scheduled backup fails,
+/// e.g. because the USB drive it goes to is not plugged in.
+const RETRY_AFTER_MINUTES: i64 = 15;
+
+const BACKUP_RUN_COLUMNS: &str = "id, started_at, kind, path, size_bytes, pruned, error, audit_entries, audit_head";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupSchedule {
+    /// Where scheduled snapshots go; `None` means `backups/` next to the register.
+    pub folder: Option<String>,
+    pub on_start: bool,
+    /// Time of day after which the daily snapshot is taken; `None` turns it off.
+    pub daily_at: Option<NaiveTime>,
+    pub keep_daily: u32,
+    pub keep_weekly: u32,
+    pub keep_monthly: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackupKind {
+    Startup,
+    Daily,
+    Manual,
+}
+
+impl BackupKind {
+    pub fn code(self) -> &'static str {
+        match self {
+            BackupKind::Startup => "startup",
+            BackupKind::Daily => "daily",
+            BackupKind::Manual => "manual",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct BackupRun {
+    pub id: i64,
+    pub started_at: String,
+    pub kind: String,
+    pub path: Option<String>,
+    pub size_bytes: Option<u64>,
+    /// Old snapshots removed by the retention policy after this one was taken.
+    pub pruned: u32,
+    /// Set when the run failed; the other fields then describe what was tried.
+    pub error: Option<String>,
+    /// Length of the audit log when a successful backup was taken, and the
+    /// hash of its newest entry. `verify_audit_log` checks the log still
+    /// starts with exactly these entries.
+    pub audit_entries: Option<usize>,
+    pub audit_head: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BackupStatus {
+    pub folder: String,
+    pub last_success: Option<BackupRun>,
+    pub last_run: Option<BackupRun>,
+}
+
+impl Db {
+    pub fn backup_schedule(&self) -> AppResult<BackupSchedule> {
+        let conn = self.reader()?;
+        let schedule = conn.query_row(
+            "SELECT folder, on_start, daily_at, keep_daily, keep_weekly, keep_monthly
+             FROM backup_schedule WHERE id = 1",
+            [],
+            |row| {
+                Ok(BackupSchedule {
+                    folder: row.get(0)?,
+                    on_start: row.get(1)?,
+                    daily_at: parse_optional_time_column(row, 2)?,
+                    keep_daily: row.get(3)?,
+                    keep_weekly: row.get(4)?,
+                    keep_monthly: row.get(5)?,
+                })
+            },
+        )?;
+        Ok(schedule)
+    }
+
+    pub fn set_backup_schedule(&self, schedule: BackupSchedule) -> AppResult<BackupSchedule> {
+        let before = self.backup_schedule()?;
+        let operator = self.operator();
+        let conn = self.writer();
+        let tx = conn.unchecked_transaction()?;
+
+        tx.execute(
+            "UPDATE backup_schedule
+             SET folder = ?1, on_start = ?2, daily_at = ?3,
+                 keep_daily = ?4, keep_weekly = ?5, keep_monthly = ?6
+             WHERE id = 1",
+            params![
+                schedule.folder,
+                schedule.on_start,
+                schedule.daily_at.map(|t| t.format("%H:%M:%S").to_string()),
+                schedule.keep_daily,
+                schedule.keep_weekly,
+                schedule.keep_monthly
+            ],
+        )?;
+
+        audit::record(&tx, &operator, "set_backup_schedule", None, Some(&json!(before)), Some(&json!(schedule)))?;
+        tx.commit()?;
+
+        println!("Backup schedule updated: {:?}", schedule);
+        Ok(schedule)
+    }
+
+    /// Folder scheduled snapshots are written to and pruned in.
+    pub fn scheduled_backup_dir(&self) -> AppResult<PathBuf> {
+        Ok(match self.backup_schedule()?.folder {
+            Some(folder) => PathBuf::from(folder),
+            None => self.backup_dir(),
+        })
+    }
+
+    /// Whether the daily snapshot for `now` is still owed: its time has
+    /// passed and no scheduled snapshot has succeeded since then. A failed
+    /// attempt holds off the next one for a while rather than retrying on
+    /// every few-second tick of the scheduler.
+    pub fn daily_backup_due(&self, now: NaiveDateTime) -> AppResult<bool> {
+        let Some(daily_at) = self.backup_schedule()?.daily_at else {
+            return Ok(false);
+        };
+        if now.time() < daily_at {
+            return Ok(false);
+        }
+
+        let due_since = now.date().and_time(daily_at).format(RECORDED_AT_FORMAT).to_string();
+        let retry_after = (now - Duration::minutes(RETRY_AFTER_MINUTES))
+            .format(RECORDED_AT_FORMAT)
+            .to_string();
+
+        let conn = self.reader()?;
+        let covered: bool = conn.query_row(
+            "SELECT EXISTS (
+                SELECT 1 FROM backup_runs
+                WHERE kind != ?1
+                  AND started_at >= ?2
+                  AND (error IS NULL OR started_at >= ?3)
+            )",
+            params![BackupKind::Manual.code(), due_since, retry_after],
+            |row| row.get(0),
+        )?;
+
+        Ok(!covered)
+    }
+
+    /// Takes a snapshot into the scheduled folder, prunes old snapshots there
+    /// by the retention policy and records how it went. A failed snapshot is
+    /// recorded rather than returned as an error; `Err` means the result
+    /// could not be recorded either.
+    ///
+    /// A chosen folder that is missing fails the run instead of being made
+    /// again: it is usually a drive that isn't plugged in, and snapshots
+    /// written to an empty mount point would be lost with it. Only the
+    /// default folder next to the register is created when needed.
+    pub fn run_scheduled_backup(&self, kind: BackupKind) -> AppResult<BackupRun> {
+        let now = Local::now().naive_local();
+        let schedule = self.backup_schedule()?;
+        let folder = self.scheduled_backup_dir()?;
+        let dest = folder.join(format!("{}{}.db", AUTO_PREFIX, now.format(STAMP_FORMAT)));
+
+        let result = if folder.is_dir() {
+            self.backup_to(&dest)
+        } else if schedule.folder.is_some() {
+            Err(AppError::BackupFolderMissing { path: folder.display().to_string() })
+        } else {
+            fs::create_dir_all(&folder)
+                .map_err(AppError::from)
+                .and_then(|()| self.backup_to(&dest))
+        };
+        let pruned = match result {
+            Ok(_) => prune_snapshots(&folder, &schedule),
+            Err(_) => 0,
+        };
+
+        let run = self.record_backup_run(kind, now, &dest, &result, pruned)?;
+        match &run.error {
+            None => println!("Scheduled {} backup written to {}", kind.code(), dest.display()),
+            Some(error) => eprintln!("Scheduled {} backup to {} failed: {}", kind.code(), dest.display(), error),
+        }
+        Ok(run)
+    }
+
+    /// A backup asked for from the UI, into a folder that is made if need
+    /// be. It is recorded alongside the scheduled ones but never pruned.
+    pub fn run_manual_backup(&self, dest: &Path) -> AppResult<BackupInfo> {
+        let now = Local::now().naive_local();
+        let result = match dest.parent() {
+            Some(parent) => fs::create_dir_all(parent).map_err(AppError::from),
+            None => Ok(()),
+        }
+        .and_then(|()| self.backup_to(dest));
+        self.record_backup_run(BackupKind::Manual, now, dest, &result, 0)?;
+        result
+    }
+
+    pub fn backup_status(&self) -> AppResult<BackupStatus> {
+        let folder = self.scheduled_backup_dir()?.display().to_string();
+        let conn = self.reader()?;
+
+        let last = |filter: &str| {
+            conn.query_row(
+                &format!(
+                    "SELECT {} FROM backup_runs {} ORDER BY id DESC LIMIT 1",
+                    BACKUP_RUN_COLUMNS, filter
+                ),
+                [],
+                |row| {
+                    Ok(BackupRun {
+                        id: row.get(0)?,
+                        started_at: row.get(1)?,
+                        kind: row.get(2)?,
+                        path: row.get(3)?,
+                        size_bytes: row.get(4)?,
+                        pruned: row.get(5)?,
+                        error: row.get(6)?,
+                        audit_entries: row.get(7)?,
+                        audit_head: row.get(8)?,
+                    })
+                },
+            )
+            .optional()
+        };
+
+        Ok(BackupStatus {
+            folder,
+            last_success: last("WHERE error IS NULL")?,
+            last_run: last("")?,
+        })
+    }
+
+    fn record_backup_run(
+        &self,
+        kind: BackupKind,
+        started_at: NaiveDateTime,
+        dest: &Path,
+        result: &AppResult<BackupInfo>,
+        pruned: u32,
+    ) -> AppResult<BackupRun> {
+        let conn = self.writer();
+        // Read on the writer, so no entry can land between this and the run
+        // being recorded. The backup itself holds at least these entries.
+        let (audit_entries, audit_head) = match result {
+            Ok(_) => conn.query_row(
+                "SELECT COUNT(*), (SELECT hash FROM audit_log ORDER BY id DESC LIMIT 1) FROM audit_log",
+                [],
+                |row| Ok((Some(row.get(0)?), row.get(1)?)),
+            )?,
+            Err(_) => (None, None),
+        };
+
+        let run = BackupRun {
+            id: 0,
+            started_at: started_at.format(RECORDED_AT_FORMAT).to_string(),
+            kind: kind.code().to_string(),
+            path: Some(dest.display().to_string()),
+            size_bytes: result.as_ref().ok().map(|info| info.size_bytes),
+            pruned,
+            error: result.as_ref().err().map(|e| e.to_string()),
+            audit_entries,
+            audit_head,
+        };
+
+        conn.execute(
+            "INSERT INTO backup_runs (started_at, kind, path, size_bytes, pruned, error, audit_entries, audit_head)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                run.started_at,
+                run.kind,
+                run.path,
+                run.size_bytes,
+                run.pruned,
+                run.error,
+                run.audit_entries,
+                run.audit_head
+            ],
+        )?;
+
+        Ok(BackupRun {
+            id: conn.last_insert_rowid(),
+            ..run
+        })
+    }
+}
+
+/// Deletes the scheduled snapshots in `folder` that the retention policy no
+/// longer keeps and returns how many went. A snapshot that can't be removed
+/// is left for the next run.
+fn prune_snapshots(folder: &Path, schedule: &BackupSchedule) -> u32 {
+    let entries = match fs::read_dir(folder) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("Could not list {} for pruning: {}", folder.display(), e);
+            return 0;
+        }
+    };
+
+    let snapshots: Vec<(NaiveDateTime, PathBuf)> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let stamp = name.strip_prefix(AUTO_PREFIX)?.strip_suffix(".db")?;
+            let taken_at = NaiveDateTime::parse_from_str(stamp, STAMP_FORMAT).ok()?;
+            Some((taken_at, entry.path()))
+        })
+        .collect();
+
+    let mut pruned = 0;
+    for path in snapshots_to_prune(snapshots, schedule) {
+        match fs::remove_file(&path) {
+            Ok(()) => pruned += 1,
+            Err(e) => eprintln!("Could not remove old backup {}: {}", path.display(), e),
+        }
+    }
+    pruned
+}
+
+/// Keeps the newest snapshot of each of the last `keep_daily` days,
+/// `keep_weekly` ISO weeks and `keep_monthly` months that have one; a
+/// snapshot kept by any of the three stays. Everything else is returned.
+pub fn snapshots_to_prune(
+    mut snapshots: Vec<(NaiveDateTime, PathBuf)>,
+    schedule: &BackupSchedule,
+) -> Vec<PathBuf> {
+    snapshots.sort_by_key(|(taken_at, _)| Reverse(*taken_at));
+
+    let mut keep = vec![false; snapshots.len()];
+    keep_newest_per(&snapshots, schedule.keep_daily, &mut keep, |t| {
+        (t.year(), t.ordinal())
+    });
+    keep_newest_per(&snapshots, schedule.keep_weekly, &mut keep, |t| {
+        (t.iso_week().year(), t.iso_week().week())
+    });
+    keep_newest_per(&snapshots, schedule.keep_monthly, &mut keep, |t| {
+        (t.year(), t.month())
+    });
+
+    snapshots
+        .into_iter()
+        .zip(keep)
+        .filter(|(_, keep)| !keep)
+        .map(|((_, path), _)| path)
+        .collect()
+}
+
+// `snapshots` is newest first, so each period's snapshots sit together and
+// the first one seen is the newest of its period.
+fn keep_newest_per<K: PartialEq>(
+    snapshots: &[(NaiveDateTime, PathBuf)],
+    limit: u32,
+    keep: &mut [bool],
+    period: impl Fn(&NaiveDateTime) -> K,
+) {
+    let mut last = None;
+    let mut kept = 0;
+
+    for (i, (taken_at, _)) in snapshots.iter().enumerate() {
+        if kept >= limit {
+            break;
+        }
+        let this = period(taken_at);
+        if last.as_ref() != Some(&this) {
+            keep[i] = true;
+            kept += 1;
+            last = Some(this);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{register, ScratchDir};
+
+    fn schedule(keep_daily: u32, keep_weekly: u32, keep_monthly: u32) -> BackupSchedule {
+        BackupSchedule {
+            folder: None,
+            on_start: false,
+            daily_at: None,
+            keep_daily,
+            keep_weekly,
+            keep_monthly,
+        }
+    }
+
+    fn snapshot(stamp: &str) -> (NaiveDateTime, PathBuf) {
+        let taken_at = NaiveDateTime::parse_from_str(stamp, STAMP_FORMAT).unwrap();
+        (taken_at, PathBuf::from(format!("{}{}.db", AUTO_PREFIX, stamp)))
+    }
+
+    fn names(paths: Vec<PathBuf>) -> Vec<String> {
+        let mut names: Vec<String> = paths
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn keep_counts_of_zero_prune_everything() {
+        let snapshots = vec![snapshot("20240301-180000"), snapshot("20240302-180000")];
+        assert_eq!(snapshots_to_prune(snapshots, &schedule(0, 0, 0)).len(), 2);
+    }
+
+    #[test]
+    fn fewer_snapshots_than_the_keep_count_are_all_kept() {
+        let snapshots = vec![
+            snapshot("20240301-180000"),
+            snapshot("20240302-180000"),
+            snapshot("20240303-180000"),
+        ];
+        assert!(snapshots_to_prune(snapshots, &schedule(7, 0, 0)).is_empty());
+    }
+
+    #[test]
+    fn only_the_newest_of_each_day_is_kept() {
+        let snapshots = vec![
+            snapshot("20240301-090000"),
+            snapshot("20240301-180000"),
+            snapshot("20240302-090000"),
+        ];
+        assert_eq!(
+            names(snapshots_to_prune(snapshots, &schedule(7, 0, 0))),
+            ["cases-auto-20240301-090000.db"]
+        );
+    }
+
+    #[test]
+    fn files_that_are_not_snapshots_are_left_alone() {
+        let dir = ScratchDir::new("prune");
+        let files = [
+            "cases-auto-20240301-180000.db",
+            "cases-auto-20240302-180000.db",
+            "cases-auto-not-a-stamp.db",
+            "cases-auto-20240301-180000.db.partial",
+            "manual.db",
+            "cases.db",
+        ];
+        for name in files {
+            fs::write(dir.join(name), b"").unwrap();
+        }
+
+        assert_eq!(prune_snapshots(dir.path(), &schedule(1, 0, 0)), 1);
+
+        let left: Vec<PathBuf> = fs::read_dir(dir.path()).unwrap().map(|e| e.unwrap().path()).collect();
+        assert_eq!(
+            names(left),
+            [
+                "cases-auto-20240301-180000.db.partial",
+                "cases-auto-20240302-180000.db",
+                "cases-auto-not-a-stamp.db",
+                "cases.db",
+                "manual.db",
+            ]
+        );
+    }
+
+    #[test]
+    fn the_newest_of_each_iso_week_is_kept() {
+        // Sunday 3 March ends week 9; Monday 4 March starts week 10.
+        let snapshots = vec![
+            snapshot("20240303-180000"),
+            snapshot("20240304-180000"),
+            snapshot("20240309-180000"),
+            snapshot("20240310-180000"),
+        ];
+        assert_eq!(
+            names(snapshots_to_prune(snapshots, &schedule(0, 2, 0))),
+            ["cases-auto-20240304-180000.db", "cases-auto-20240309-180000.db"]
+        );
+    }
+
+    #[test]
+    fn an_iso_week_running_into_the_new_year_is_one_week() {
+        // Monday 30 December 2024 starts the first ISO week of 2025.
+        let snapshots = vec![
+            snapshot("20241229-180000"),
+            snapshot("20241230-180000"),
+            snapshot("20250102-180000"),
+        ];
+        assert_eq!(
+            names(snapshots_to_prune(snapshots, &schedule(0, 2, 0))),
+            ["cases-auto-20241230-180000.db"]
+        );
+    }
+
+    #[test]
+    fn the_newest_of_each_month_is_kept() {
+        let snapshots = vec![
+            snapshot("20240115-180000"),
+            snapshot("20240131-235959"),
+            snapshot("20240210-180000"),
+            snapshot("20240301-000000"),
+            snapshot("20240320-180000"),
+        ];
+        assert_eq!(
+            names(snapshots_to_prune(snapshots, &schedule(0, 0, 2))),
+            [
+                "cases-auto-20240115-180000.db",
+                "cases-auto-20240131-235959.db",
+                "cases-auto-20240301-000000.db",
+            ]
+        );
+    }
+
+    #[test]
+    fn a_snapshot_kept_by_any_rule_stays() {
+        let snapshots = vec![
+            snapshot("20240220-180000"),
+            snapshot("20240228-180000"),
+            snapshot("20240301-180000"),
+            snapshot("20240303-180000"),
+            snapshot("20240304-180000"),
+            snapshot("20240305-180000"),
+        ];
+        // Daily keeps 5 and 4 March, weekly 5 and 3 March, monthly 5 March
+        // and 28 February.
+        assert_eq!(
+            names(snapshots_to_prune(snapshots, &schedule(2, 2, 2))),
+            ["cases-auto-20240220-180000.db", "cases-auto-20240301-180000.db"]
+        );
+    }
+
+    #[test]
+    fn a_scheduled_run_fails_when_its_chosen_folder_is_missing() {
+        let dir = ScratchDir::new("scheduled-missing");
+        let db = register(&dir);
+        let folder = dir.join("usb").join("backups");
+        db.set_backup_schedule(BackupSchedule {
+            folder: Some(folder.display().to_string()),
+            ..schedule(7, 4, 12)
+        })
+        .unwrap();
+
+        let run = db.run_scheduled_backup(BackupKind::Daily).unwrap();
+        assert!(run.error.is_some());
+        assert!(!folder.exists());
+
+        // Asked for by hand, the same folder is made.
+        db.run_manual_backup(&folder.join("manual.db")).unwrap();
+        assert!(folder.join("manual.db").is_file());
+        let run = db.run_scheduled_backup(BackupKind::Daily).unwrap();
+        assert_eq!(run.error, None);
+    }
+
+    #[test]
+    fn the_default_folder_is_made_for_a_scheduled_run() {
+        let dir = ScratchDir::new("scheduled-default");
+        let db = register(&dir);
+
+        let run = db.run_scheduled_backup(BackupKind::Startup).unwrap();
+        assert_eq!(run.error, None);
+        assert!(dir.join("backups").is_dir());
+    }
+}
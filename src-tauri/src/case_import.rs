@@ -0,0 +1,1355 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+use calamine::{open_workbook_auto, Data, Reader};
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveTime};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::audit;
+use crate::database_init::{bad_stored_value, case_by_id, Db, DisposalOfCase, NatureOfCase};
+use crate::error::{AppError, AppResult};
+use crate::mediators::{find_mediator, parse_legacy_name};
+
+pub(crate) const IMPORT_PROFILE_COLUMNS: &str = "id, name, mapping, updated_at";
+
+/// Rows shown under the headers when a file is previewed.
+const PREVIEW_ROWS: usize = 5;
+
+/// Years a legacy register can plausibly hold, for case years and dates
+/// alike. Anything outside is a misread cell rather than a real case.
+const YEARS: std::ops::RangeInclusive<i32> = 1950..=2100;
+
+/// Day-first formats in the order they are tried. Two-digit years come after
+/// four-digit ones so "04.03.21" isn't read as the year 21.
+const DATE_FORMATS: &[&str] = &[
+    "%Y-%m-%d",
+    "%d.%m.%Y",
+    "%d/%m/%Y",
+    "%d-%m-%Y",
+    "%Y/%m/%d",
+    "%d %b %Y",
+    "%d-%b-%Y",
+    "%d %B %Y",
+    "%d.%m.%y",
+    "%d/%m/%y",
+    "%d-%m-%y",
+    "%d-%b-%y",
+];
+
+const TIME_FORMATS: &[&str] = &["%H:%M", "%H:%M:%S", "%I:%M %p", "%I:%M%p", "%I.%M %p", "%H.%M"];
+
+/// A `case_table` field a column of the source file can be mapped to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportField {
+    CaseNo,
+    Year,
+    NatureOfCase,
+    ReceivedFrom,
+    Date,
+    TimeSlot,
+    Party1,
+    Party2,
+    Mediator,
+    NdohDate,
+    NdohTime,
+    DisposalOfCase,
+    DisposalDate,
+    DisposalReason,
+    DisposalRemarks,
+    Connected,
+    Remarks,
+}
+
+impl ImportField {
+    pub const ALL: [ImportField; 17] = [
+        ImportField::CaseNo,
+        ImportField::Year,
+        ImportField::NatureOfCase,
+        ImportField::ReceivedFrom,
+        ImportField::Date,
+        ImportField::TimeSlot,
+        ImportField::Party1,
+        ImportField::Party2,
+        ImportField::Mediator,
+        ImportField::NdohDate,
+        ImportField::NdohTime,
+        ImportField::DisposalOfCase,
+        ImportField::DisposalDate,
+        ImportField::DisposalReason,
+        ImportField::DisposalRemarks,
+        ImportField::Connected,
+        ImportField::Remarks,
+    ];
+
+    /// Same as the serde name.
+    pub fn code(self) -> &'static str {
+        match self {
+            ImportField::CaseNo => "case_no",
+            ImportField::Year => "year",
+            ImportField::NatureOfCase => "nature_of_case",
+            ImportField::ReceivedFrom => "received_from",
+            ImportField::Date => "date",
+            ImportField::TimeSlot => "time_slot",
+            ImportField::Party1 => "party1",
+            ImportField::Party2 => "party2",
+            ImportField::Mediator => "mediator",
+            ImportField::NdohDate => "ndoh_date",
+            ImportField::NdohTime => "ndoh_time",
+            ImportField::DisposalOfCase => "disposal_of_case",
+            ImportField::DisposalDate => "disposal_date",
+            ImportField::DisposalReason => "disposal_reason",
+            ImportField::DisposalRemarks => "disposal_remarks",
+            ImportField::Connected => "connected",
+            ImportField::Remarks => "remarks",
+        }
+    }
+
+    /// Matches the column titles of `export_cases`, so an export can be
+    /// imported into another register without mapping anything by hand.
+    pub fn label(self) -> &'static str {
+        match self {
+            ImportField::CaseNo => "Case No",
+            ImportField::Year => "Year",
+            ImportField::NatureOfCase => "Nature of Case",
+            ImportField::ReceivedFrom => "Received From",
+            ImportField::Date => "Intake Date",
+            ImportField::TimeSlot => "Time Slot",
+            ImportField::Party1 => "Party 1",
+            ImportField::Party2 => "Party 2",
+            ImportField::Mediator => "Mediator",
+            ImportField::NdohDate => "Next Hearing",
+            ImportField::NdohTime => "Next Hearing Time",
+            ImportField::DisposalOfCase => "Disposal",
+            ImportField::DisposalDate => "Disposal Date",
+            ImportField::DisposalReason => "Disposal Reason",
+            ImportField::DisposalRemarks => "Disposal Remarks",
+            ImportField::Connected => "Connected",
+            ImportField::Remarks => "Remarks",
+        }
+    }
+
+    /// Fields every import must map a column to. The year may instead come
+    /// from case numbers written as "123/2021".
+    pub fn required(self) -> bool {
+        matches!(
+            self,
+            ImportField::CaseNo
+                | ImportField::NatureOfCase
+                | ImportField::ReceivedFrom
+                | ImportField::Date
+                | ImportField::Party1
+                | ImportField::Party2
+                | ImportField::Mediator
+        )
+    }
+}
+
+#[derive(Serialize)]
+pub struct ImportFieldOption {
+    pub code: &'static str,
+    pub label: &'static str,
+    pub required: bool,
+}
+
+/// How the columns of one kind of legacy sheet map onto the register. Saved
+/// as an import profile so next year's sheet can reuse it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportMapping {
+    /// Worksheet to read from a workbook; the first one when unset.
+    #[serde(default)]
+    pub sheet: Option<String>,
+    /// Source column header for each field the file provides.
+    #[serde(default)]
+    pub columns: BTreeMap<ImportField, String>,
+    /// A chrono format tried before the usual ones, for registers that
+    /// wrote dates some other way.
+    #[serde(default)]
+    pub date_format: Option<String>,
+    /// Used for rows with no time slot; without it such rows are errors.
+    #[serde(default)]
+    pub default_time_slot: Option<String>,
+    /// Adds mediators and disposal reasons the register doesn't know yet, as
+    /// retired entries, instead of failing their rows.
+    #[serde(default)]
+    pub create_missing: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportProfile {
+    pub id: i32,
+    pub name: String,
+    pub mapping: ImportMapping,
+    pub updated_at: String,
+}
+
+/// The cells of a CSV file or worksheet as text, with the header row split
+/// off. `line` is the row number a spreadsheet program would show.
+#[derive(Debug)]
+pub struct ImportSource {
+    pub sheets: Vec<String>,
+    pub sheet: Option<String>,
+    pub headers: Vec<String>,
+    pub rows: Vec<SourceRow>,
+}
+
+#[derive(Debug)]
+pub struct SourceRow {
+    pub line: usize,
+    pub cells: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportPreview {
+    pub sheets: Vec<String>,
+    pub sheet: Option<String>,
+    pub headers: Vec<String>,
+    pub sample: Vec<Vec<String>>,
+    pub rows: usize,
+    /// Columns whose header already names a field, by label or code.
+    pub suggested: BTreeMap<ImportField, String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportIssue {
+    pub row: usize,
+    pub field: ImportField,
+    pub value: String,
+    pub message: String,
+}
+
+/// A row whose case number and year are already on the register. It is
+/// left out of the import; the case already there is not touched.
+#[derive(Debug, Serialize)]
+pub struct ImportConflict {
+    pub row: usize,
+    pub case_no: i32,
+    pub year: i32,
+    pub party1: String,
+    pub party2: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ImportReport {
+    pub rows: usize,
+    pub valid: usize,
+    pub errors: Vec<ImportIssue>,
+    pub conflicts: Vec<ImportConflict>,
+    pub new_mediators: Vec<String>,
+    pub new_disposal_reasons: Vec<String>,
+    pub imported: usize,
+    pub committed: bool,
+}
+
+/// One valid row, normalised and ready to insert.
+#[derive(Debug)]
+struct ImportedCase {
+    row: usize,
+    case_no: i32,
+    year: i32,
+    nature_of_case: NatureOfCase,
+    received_from: String,
+    date: NaiveDate,
+    time_slot: NaiveTime,
+    party1: String,
+    party2: String,
+    mediator: String,
+    ndoh_date: Option<NaiveDate>,
+    ndoh_time: Option<NaiveTime>,
+    disposal_of_case: Option<DisposalOfCase>,
+    disposal_date: Option<NaiveDate>,
+    disposal_reason: Option<String>,
+    disposal_remarks: Option<String>,
+    connected: i32,
+    remarks: Option<String>,
+}
+
+/// Mediators, disposal reasons and cases already on the register, keyed the
+/// way rows are matched against them.
+struct Register {
+    mediators: HashMap<String, i32>,
+    reasons: HashMap<String, i32>,
+    cases: HashMap<(i32, i32), (String, String)>,
+}
+
+impl ImportSource {
+    /// Reads a `.csv` file, or a worksheet of an `.xlsx`, `.xls` or `.ods`
+    /// workbook: `sheet` when given, otherwise the first one.
+    pub fn read(path: &Path, sheet: Option<&str>) -> AppResult<Self> {
+        let invalid = |detail: String| AppError::InvalidImport {
+            path: path.display().to_string(),
+            detail,
+        };
+
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase())
+            .unwrap_or_default();
+        let source = match extension.as_str() {
+            "csv" => read_csv(path).map_err(|e| invalid(e.to_string()))?,
+            "xlsx" | "xlsm" | "xls" | "ods" => read_workbook(path, sheet).map_err(invalid)?,
+            _ => return Err(invalid("expected a .csv, .xlsx, .xls or .ods file".to_string())),
+        };
+
+        if source.headers.iter().all(|h| h.is_empty()) {
+            return Err(invalid("the sheet has no header row".to_string()));
+        }
+        Ok(source)
+    }
+
+    pub fn preview(&self) -> ImportPreview {
+        ImportPreview {
+            sheets: self.sheets.clone(),
+            sheet: self.sheet.clone(),
+            headers: self.headers.clone(),
+            sample: self.rows.iter().take(PREVIEW_ROWS).map(|r| r.cells.clone()).collect(),
+            rows: self.rows.len(),
+            suggested: self.suggested_columns(),
+        }
+    }
+
+    fn suggested_columns(&self) -> BTreeMap<ImportField, String> {
+        let key = |text: &str| text.to_lowercase().replace(['_', ' '], "");
+        ImportField::ALL
+            .into_iter()
+            .filter_map(|field| {
+                let header = self.headers.iter().find(|h| {
+                    key(h) == key(field.label()) || key(h) == key(field.code())
+                })?;
+                Some((field, header.clone()))
+            })
+            .collect()
+    }
+
+    /// Position of each mapped column, checking that every required field
+    /// is mapped and every mapped header is in the file.
+    fn locate(&self, path: &Path, mapping: &ImportMapping) -> AppResult<HashMap<ImportField, usize>> {
+        let invalid = |detail: String| AppError::InvalidImport {
+            path: path.display().to_string(),
+            detail,
+        };
+
+        if let Some(field) = ImportField::ALL
+            .into_iter()
+            .find(|f| f.required() && !mapping.columns.contains_key(f))
+        {
+            return Err(invalid(format!("no column is mapped to {}", field.label())));
+        }
+
+        mapping
+            .columns
+            .iter()
+            .filter(|(_, header)| !header.trim().is_empty())
+            .map(|(field, header)| {
+                let index = self
+                    .headers
+                    .iter()
+                    .position(|h| h.eq_ignore_ascii_case(header.trim()))
+                    .ok_or_else(|| invalid(format!("there is no column {:?} for {}", header, field.label())))?;
+                Ok((*field, index))
+            })
+            .collect()
+    }
+}
+
+fn read_csv(path: &Path) -> csv::Result<ImportSource> {
+    let data = fs::read(path)?;
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(data.as_slice());
+
+    // Legacy sheets saved from Excel are often not UTF-8, so stray bytes
+    // become replacement characters rather than failing the whole file.
+    let mut records = Vec::new();
+    for record in reader.byte_records() {
+        let record = record?;
+        // The reader doesn't count the blank lines it skips, so the line a
+        // spreadsheet would show is worked out from where the record starts.
+        let mut start = record.position().map(|p| p.byte() as usize).unwrap_or(0);
+        while matches!(data.get(start), Some(b'\r' | b'\n')) {
+            start += 1;
+        }
+        let line = data[..start].iter().filter(|&&b| b == b'\n').count() + 1;
+        let cells: Vec<String> = record
+            .iter()
+            .map(|cell| String::from_utf8_lossy(cell).trim().to_string())
+            .collect();
+        records.push(SourceRow { line, cells });
+    }
+
+    if let Some(first) = records.first_mut().and_then(|r| r.cells.first_mut()) {
+        *first = first.trim_start_matches('\u{feff}').to_string();
+    }
+    Ok(split_headers(Vec::new(), None, records))
+}
+
+fn read_workbook(path: &Path, sheet: Option<&str>) -> Result<ImportSource, String> {
+    let mut workbook = open_workbook_auto(path).map_err(|e| e.to_string())?;
+    let sheets = workbook.sheet_names();
+    let name = match sheet.filter(|s| !s.is_empty()) {
+        Some(name) => sheets
+            .iter()
+            .find(|s| s.as_str() == name)
+            .ok_or_else(|| format!("the workbook has no sheet named {:?}", name))?
+            .clone(),
+        None => sheets.first().ok_or("the workbook has no sheets")?.clone(),
+    };
+
+    let range = workbook.worksheet_range(&name).map_err(|e| e.to_string())?;
+    let first_row = range.start().map(|(row, _)| row as usize).unwrap_or(0);
+    let records = range
+        .rows()
+        .enumerate()
+        .map(|(i, cells)| SourceRow {
+            line: first_row + i + 1,
+            cells: cells.iter().map(cell_text).collect(),
+        })
+        .collect();
+
+    Ok(split_headers(sheets, Some(name), records))
+}
+
+/// Takes the first non-blank row as the headers and drops blank rows.
+fn split_headers(sheets: Vec<String>, sheet: Option<String>, records: Vec<SourceRow>) -> ImportSource {
+    let mut rows = records
+        .into_iter()
+        .filter(|r| r.cells.iter().any(|c| !c.is_empty()));
+    let headers = rows.next().map(|r| r.cells).unwrap_or_default();
+
+    ImportSource {
+        sheets,
+        sheet,
+        headers,
+        rows: rows.collect(),
+    }
+}
+
+/// Spreadsheet cells as the text a CSV of the same sheet would hold. Date
+/// cells come out as ISO dates, or "HH:MM" when they only hold a time.
+fn cell_text(cell: &Data) -> String {
+    match cell {
+        Data::Empty | Data::Error(_) => String::new(),
+        Data::String(s) | Data::DateTimeIso(s) | Data::DurationIso(s) => s.trim().to_string(),
+        Data::Int(i) => i.to_string(),
+        Data::Float(f) if f.fract() == 0.0 => format!("{}", *f as i64),
+        Data::Float(f) => f.to_string(),
+        Data::Bool(b) => b.to_string(),
+        Data::DateTime(dt) => match dt.as_datetime() {
+            Some(value) if dt.as_f64() < 1.0 => value.time().format("%H:%M").to_string(),
+            Some(value) if value.time() == NaiveTime::MIN => value.date().to_string(),
+            Some(value) => value.format("%Y-%m-%d %H:%M").to_string(),
+            None => dt.as_f64().to_string(),
+        },
+    }
+}
+
+impl Db {
+    /// Checks every row of `source` against `mapping` and the register, and
+    /// unless `dry_run` is set or a row failed, adds the cases in a single
+    /// transaction. Rows already on the register are reported as conflicts
+    /// and skipped.
+    pub fn import_cases(
+        &self,
+        path: &Path,
+        source: &ImportSource,
+        mapping: &ImportMapping,
+        dry_run: bool,
+    ) -> AppResult<ImportReport> {
+        let columns = source.locate(path, mapping)?;
+        let default_time_slot = match mapping.default_time_slot.as_deref().filter(|t| !t.trim().is_empty()) {
+            Some(text) => Some(parse_time(text).ok_or_else(|| AppError::InvalidTime {
+                field: "default_time_slot",
+                value: text.to_string(),
+            })?),
+            None => None,
+        };
+
+        let operator = self.operator();
+        let conn = self.writer();
+        let tx = conn.unchecked_transaction()?;
+        let mut register = Register::load(&tx)?;
+
+        let mut report = ImportReport {
+            rows: source.rows.len(),
+            ..Default::default()
+        };
+        let mut first_seen: HashMap<(i32, i32), usize> = HashMap::new();
+        let mut cases = Vec::new();
+
+        for row in &source.rows {
+            let case = match read_row(row, &columns, mapping, default_time_slot) {
+                Ok(case) => case,
+                Err(issues) => {
+                    report.errors.extend(issues);
+                    continue;
+                }
+            };
+
+            let issue = |field: ImportField, value: &str, message: String| ImportIssue {
+                row: case.row,
+                field,
+                value: value.to_string(),
+                message,
+            };
+
+            let key = (case.case_no, case.year);
+            if let Some(first) = first_seen.get(&key) {
+                report.errors.push(issue(
+                    ImportField::CaseNo,
+                    &case.case_no.to_string(),
+                    format!("case {} of {} is also on row {}", case.case_no, case.year, first),
+                ));
+                continue;
+            }
+            first_seen.insert(key, case.row);
+
+            if let Some((party1, party2)) = register.cases.get(&key) {
+                report.conflicts.push(ImportConflict {
+                    row: case.row,
+                    case_no: case.case_no,
+                    year: case.year,
+                    party1: party1.clone(),
+                    party2: party2.clone(),
+                });
+                continue;
+            }
+
+            let mut failed = false;
+            let mediator = mediator_key(&case.mediator);
+            if !register.mediators.contains_key(&mediator) {
+                if !mapping.create_missing {
+                    report.errors.push(issue(
+                        ImportField::Mediator,
+                        &case.mediator,
+                        "no mediator by this name is on the register".to_string(),
+                    ));
+                    failed = true;
+                } else if !report.new_mediators.iter().any(|m| mediator_key(m) == mediator) {
+                    report.new_mediators.push(case.mediator.clone());
+                }
+            }
+            if let Some(reason) = &case.disposal_reason {
+                let key = reason.to_lowercase();
+                if !register.reasons.contains_key(&key) {
+                    if !mapping.create_missing {
+                        report.errors.push(issue(
+                            ImportField::DisposalReason,
+                            reason,
+                            "not on the list of disposal reasons".to_string(),
+                        ));
+                        failed = true;
+                    } else if !report.new_disposal_reasons.iter().any(|r| r.to_lowercase() == key) {
+                        report.new_disposal_reasons.push(reason.clone());
+                    }
+                }
+            }
+
+            if !failed {
+                cases.push(case);
+            }
+        }
+
+        report.valid = cases.len();
+        report.errors.sort_by_key(|issue| issue.row);
+        if dry_run || !report.errors.is_empty() {
+            return Ok(report);
+        }
+
+        for name in &report.new_mediators {
+            let id = add_retired_mediator(&tx, &operator, name)?;
+            register.mediators.insert(mediator_key(name), id);
+        }
+        for label in &report.new_disposal_reasons {
+            let id = add_retired_disposal_reason(&tx, &operator, label)?;
+            register.reasons.insert(label.to_lowercase(), id);
+        }
+        for case in &cases {
+            insert_imported_case(&tx, &operator, &register, case)?;
+        }
+
+        tx.commit()?;
+        report.imported = cases.len();
+        report.committed = true;
+
+        println!(
+            "Imported {} case(s) from {} ({} conflict(s) skipped)",
+            report.imported,
+            path.display(),
+            report.conflicts.len()
+        );
+        Ok(report)
+    }
+
+    pub fn get_import_profiles(&self) -> AppResult<Vec<ImportProfile>> {
+        let conn = self.reader()?;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM import_profiles ORDER BY name",
+            IMPORT_PROFILE_COLUMNS
+        ))?;
+
+        let profiles = stmt
+            .query_map([], import_profile_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(profiles)
+    }
+
+    /// Saves `mapping` under `name`, replacing a profile of the same name.
+    pub fn save_import_profile(&self, name: &str, mapping: &ImportMapping) -> AppResult<ImportProfile> {
+        let operator = self.operator();
+        let conn = self.writer();
+        let tx = conn.unchecked_transaction()?;
+
+        let before = find_import_profile_by_name(&tx, name)?;
+        tx.execute(
+            "INSERT INTO import_profiles (name, mapping) VALUES (?1, ?2)
+             ON CONFLICT (name) DO UPDATE
+             SET mapping = excluded.mapping, updated_at = datetime('now', 'localtime')",
+            params![name, json!(mapping).to_string()],
+        )?;
+
+        let after = find_import_profile_by_name(&tx, name)?;
+        audit::record(
+            &tx,
+            &operator,
+            "save_import_profile",
+            None,
+            before.as_ref().map(|p| json!(p)).as_ref(),
+            after.as_ref().map(|p| json!(p)).as_ref(),
+        )?;
+        tx.commit()?;
+
+        after.ok_or_else(|| AppError::InvalidValue {
+            field: "name",
+            value: name.to_string(),
+        })
+    }
+
+    pub fn delete_import_profile(&self, id: i32) -> AppResult<()> {
+        let operator = self.operator();
+        let conn = self.writer();
+        let tx = conn.unchecked_transaction()?;
+
+        let before = tx
+            .query_row(
+                &format!("SELECT {} FROM import_profiles WHERE id = ?1", IMPORT_PROFILE_COLUMNS),
+                [id],
+                import_profile_from_row,
+            )
+            .optional()?
+            .ok_or(AppError::ImportProfileNotFound { id })?;
+
+        tx.execute("DELETE FROM import_profiles WHERE id = ?1", [id])?;
+        audit::record(&tx, &operator, "delete_import_profile", None, Some(&json!(before)), None)?;
+        tx.commit()?;
+
+        println!("Deleted import profile {}", before.name);
+        Ok(())
+    }
+}
+
+impl Register {
+    fn load(conn: &Connection) -> rusqlite::Result<Self> {
+        let mediators = conn
+            .prepare("SELECT display_name, id FROM mediators")?
+            .query_map([], |row| Ok((mediator_key(&row.get::<_, String>(0)?), row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+
+        let reasons = conn
+            .prepare("SELECT label, id FROM disposal_reasons")?
+            .query_map([], |row| Ok((row.get::<_, String>(0)?.to_lowercase(), row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+
+        let cases = conn
+            .prepare("SELECT case_no, year, party1, party2 FROM case_table")?
+            .query_map([], |row| Ok(((row.get(0)?, row.get(1)?), (row.get(2)?, row.get(3)?))))?
+            .collect::<rusqlite::Result<_>>()?;
+
+        Ok(Register {
+            mediators,
+            reasons,
+            cases,
+        })
+    }
+}
+
+/// Mediators only found in old sheets have usually left the panel, so
+/// they come in retired and can be reinstated from the mediator screen.
+fn add_retired_mediator(conn: &Connection, operator: &str, name: &str) -> AppResult<i32> {
+    let (honorific, display_name) = parse_legacy_name(name);
+    conn.execute(
+        "INSERT INTO mediators (display_name, honorific, active) VALUES (?1, ?2, 0)",
+        params![display_name, honorific],
+    )
+    .map_err(|e| AppError::on_duplicate_mediator(e, &display_name))?;
+
+    let added = find_mediator(conn, conn.last_insert_rowid() as i32)?;
+    audit::record(conn, operator, "import_cases", None, None, Some(&json!(added)))?;
+    Ok(added.id)
+}
+
+fn add_retired_disposal_reason(conn: &Connection, operator: &str, label: &str) -> AppResult<i32> {
+    conn.execute("INSERT INTO disposal_reasons (label, active) VALUES (?1, 0)", [label])
+        .map_err(|e| AppError::on_duplicate_disposal_reason(e, label))?;
+
+    let id = conn.last_insert_rowid() as i32;
+    audit::record(
+        conn,
+        operator,
+        "import_cases",
+        None,
+        None,
+        Some(&json!({ "disposal_reason": { "id": id, "label": label, "active": false } })),
+    )?;
+    Ok(id)
+}
+
+fn insert_imported_case(conn: &Connection, operator: &str, register: &Register, case: &ImportedCase) -> AppResult<()> {
+    let mediator_id = register.mediators[&mediator_key(&case.mediator)];
+    let reason_id = case
+        .disposal_reason
+        .as_ref()
+        .map(|reason| register.reasons[&reason.to_lowercase()]);
+
+    conn.execute(
+        "INSERT INTO case_table (
+            case_no, year, nature_of_case, received_from, date, time_slot,
+            party1, party2, mediator_id, ndoh_date, ndoh_time, disposal_of_case,
+            connected, disposal_date, disposal_reason_id, disposal_remarks
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+        params![
+            case.case_no,
+            case.year,
+            case.nature_of_case,
+            case.received_from,
+            case.date.to_string(),
+            case.time_slot.to_string(),
+            case.party1,
+            case.party2,
+            mediator_id,
+            case.ndoh_date.map(|d| d.to_string()),
+            case.ndoh_time.map(|t| t.to_string()),
+            case.disposal_of_case.map(|d| d.code()),
+            case.connected,
+            case.disposal_date.map(|d| d.to_string()),
+            reason_id,
+            case.disposal_remarks
+        ],
+    )
+    .map_err(|e| AppError::on_duplicate_case(e, case.case_no, case.year))?;
+    let id = conn.last_insert_rowid() as i32;
+
+    // The sheet's next date is the one sitting we know of, so it becomes the
+    // case's first hearing, as it would have had it been entered by hand.
+    if let Some(hearing_date) = case.ndoh_date {
+        conn.execute(
+            "INSERT INTO hearings (case_id, hearing_date, hearing_time, mediator_id, outcome, remarks)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                id,
+                hearing_date.to_string(),
+                case.ndoh_time.map(|t| t.to_string()),
+                mediator_id,
+                case.disposal_of_case.map(|d| d.code()),
+                case.remarks
+            ],
+        )?;
+    }
+
+    let imported = case_by_id(conn, id)?;
+    audit::record(
+        conn,
+        operator,
+        "import_cases",
+        Some(imported.audit_case()),
+        None,
+        Some(&json!({ "case": imported, "row": case.row, "remarks": case.remarks })),
+    )?;
+    Ok(())
+}
+
+/// Parses and normalises one row, or lists everything wrong with it.
+fn read_row(
+    row: &SourceRow,
+    columns: &HashMap<ImportField, usize>,
+    mapping: &ImportMapping,
+    default_time_slot: Option<NaiveTime>,
+) -> Result<ImportedCase, Vec<ImportIssue>> {
+    let mut issues = Vec::new();
+    let cell = |field: ImportField| -> Option<&str> {
+        columns
+            .get(&field)
+            .and_then(|&i| row.cells.get(i))
+            .map(|c| c.trim())
+            .filter(|c| !c.is_empty())
+    };
+    let mut fail = |field: ImportField, value: Option<&str>, message: &str| {
+        issues.push(ImportIssue {
+            row: row.line,
+            field,
+            value: value.unwrap_or_default().to_string(),
+            message: message.to_string(),
+        });
+    };
+
+    let mut text = |field: ImportField| -> String {
+        match cell(field) {
+            Some(value) => value.to_string(),
+            None => {
+                fail(field, None, "required");
+                String::new()
+            }
+        }
+    };
+    let received_from = text(ImportField::ReceivedFrom);
+    let party1 = text(ImportField::Party1);
+    let party2 = text(ImportField::Party2);
+    let mediator = text(ImportField::Mediator);
+
+    let (case_no, embedded_year) = match cell(ImportField::CaseNo) {
+        None => {
+            fail(ImportField::CaseNo, None, "required");
+            (0, None)
+        }
+        Some(value) => match parse_case_no(value) {
+            Some(parsed) => parsed,
+            None => {
+                fail(ImportField::CaseNo, Some(value), "not a case number");
+                (0, None)
+            }
+        },
+    };
+
+    let year = match (cell(ImportField::Year), embedded_year) {
+        (Some(value), embedded) => match value.parse::<i32>() {
+            Ok(year) if !YEARS.contains(&year) => {
+                fail(ImportField::Year, Some(value), "not a plausible year");
+                0
+            }
+            Ok(year) if embedded.is_some_and(|e| e != year) => {
+                fail(ImportField::Year, Some(value), "differs from the year in the case number");
+                0
+            }
+            Ok(year) => year,
+            Err(_) => {
+                fail(ImportField::Year, Some(value), "not a year");
+                0
+            }
+        },
+        (None, Some(year)) => year,
+        (None, None) => {
+            fail(ImportField::Year, None, "required, either in its own column or as 123/2021 in the case number");
+            0
+        }
+    };
+
+    let nature_of_case = match cell(ImportField::NatureOfCase) {
+        None => {
+            fail(ImportField::NatureOfCase, None, "required");
+            None
+        }
+        Some(value) => {
+            let nature = NatureOfCase::from_legacy(value);
+            if nature.is_none() {
+                fail(ImportField::NatureOfCase, Some(value), "not a recognised nature of case");
+            }
+            nature
+        }
+    };
+
+    let date_format = mapping.date_format.as_deref().filter(|f| !f.is_empty());
+    let mut date = |field: ImportField, required: bool| -> Option<NaiveDate> {
+        match cell(field) {
+            None if required => {
+                fail(field, None, "required");
+                None
+            }
+            None => None,
+            Some(value) => {
+                let date = parse_date(value, date_format);
+                if date.is_none() {
+                    fail(field, Some(value), "not a date (expected day, month, year)");
+                }
+                date
+            }
+        }
+    };
+    let intake = date(ImportField::Date, true);
+    let ndoh_date = date(ImportField::NdohDate, false);
+    let disposal_date = date(ImportField::DisposalDate, false);
+
+    let mut time = |field: ImportField| -> Option<NaiveTime> {
+        let value = cell(field)?;
+        let time = parse_time(value);
+        if time.is_none() {
+            fail(field, Some(value), "not a time (expected HH:MM)");
+        }
+        time
+    };
+    let slot = time(ImportField::TimeSlot);
+    let ndoh_time = time(ImportField::NdohTime);
+    let time_slot = match slot.or(default_time_slot) {
+        Some(time) => Some(time),
+        None if cell(ImportField::TimeSlot).is_none() => {
+            fail(ImportField::TimeSlot, None, "required, or set a default time slot");
+            None
+        }
+        None => None,
+    };
+
+    let disposal_of_case = match cell(ImportField::DisposalOfCase) {
+        None => None,
+        Some(value) => {
+            let disposal = DisposalOfCase::from_legacy(value);
+            if disposal.is_none() {
+                fail(ImportField::DisposalOfCase, Some(value), "not a recognised disposal");
+            }
+            disposal
+        }
+    };
+
+    // Old registers often only kept the date a case was disposed of on as
+    // its last hearing date.
+    let is_final = disposal_of_case.is_some_and(|d| d.is_final());
+    let disposal_date = if is_final { disposal_date.or(ndoh_date) } else { None };
+    if is_final {
+        match (disposal_date, intake) {
+            (None, _) => fail(
+                ImportField::DisposalDate,
+                None,
+                "required for a final disposal, or give the last hearing date",
+            ),
+            (Some(on), Some(intake)) if on < intake || on > Local::now().date_naive() => {
+                fail(
+                    ImportField::DisposalDate,
+                    Some(&on.to_string()),
+                    "must fall between the intake date and today",
+                );
+            }
+            _ => {}
+        }
+    }
+
+    let connected = match cell(ImportField::Connected) {
+        None => 0,
+        Some(value) => match parse_connected(value) {
+            Some(connected) => connected,
+            None => {
+                fail(ImportField::Connected, Some(value), "expected a number, or yes or no");
+                0
+            }
+        },
+    };
+
+    let remarks = cell(ImportField::Remarks).map(str::to_string);
+    if remarks.is_some() && ndoh_date.is_none() {
+        fail(
+            ImportField::Remarks,
+            remarks.as_deref(),
+            "remarks are kept with a hearing, so the row needs a next hearing date",
+        );
+    }
+
+    let (Some(nature_of_case), Some(date), Some(time_slot)) = (nature_of_case, intake, time_slot) else {
+        return Err(issues);
+    };
+    if !issues.is_empty() {
+        return Err(issues);
+    }
+
+    Ok(ImportedCase {
+        row: row.line,
+        case_no,
+        year,
+        nature_of_case,
+        received_from,
+        date,
+        time_slot,
+        party1,
+        party2,
+        mediator,
+        ndoh_date,
+        ndoh_time,
+        disposal_of_case,
+        disposal_date,
+        disposal_reason: cell(ImportField::DisposalReason).filter(|_| is_final).map(str::to_string),
+        disposal_remarks: cell(ImportField::DisposalRemarks).filter(|_| is_final).map(str::to_string),
+        connected,
+        remarks,
+    })
+}
+
+/// "123", or "123/2021" and "123 of 2021" with the year attached.
+fn parse_case_no(text: &str) -> Option<(i32, Option<i32>)> {
+    let numbers: Vec<&str> = text
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|n| !n.is_empty())
+        .collect();
+
+    let case_no = numbers.first()?.parse::<i32>().ok().filter(|n| *n > 0)?;
+    match numbers[1..] {
+        [] => Some((case_no, None)),
+        [year] => year.parse().ok().filter(|y| YEARS.contains(y)).map(|y| (case_no, Some(y))),
+        _ => None,
+    }
+}
+
+/// Dates are read day first, as the centre writes them. `custom` is tried
+/// before the built-in formats. Whole numbers are taken as Excel serial
+/// dates, which is how a date column saved as CSV sometimes comes out.
+fn parse_date(text: &str, custom: Option<&str>) -> Option<NaiveDate> {
+    let plausible = |date: &NaiveDate| YEARS.contains(&date.year());
+
+    // Date cells that also carry a time, e.g. "2021-03-04 00:00".
+    let date_part = match text.split_once([' ', 'T']) {
+        Some((date, rest)) if rest.contains(':') => date,
+        _ => text,
+    };
+
+    if let Ok(serial) = date_part.parse::<i64>() {
+        let excel_epoch = NaiveDate::from_ymd_opt(1899, 12, 30)?;
+        return excel_epoch
+            .checked_add_signed(Duration::days(serial))
+            .filter(plausible);
+    }
+
+    custom
+        .into_iter()
+        .chain(DATE_FORMATS.iter().copied())
+        .filter_map(|format| NaiveDate::parse_from_str(date_part, format).ok())
+        .find(plausible)
+}
+
+fn parse_time(text: &str) -> Option<NaiveTime> {
+    let text = text.to_uppercase();
+    TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveTime::parse_from_str(text.trim(), format).ok())
+}
+
+fn parse_connected(text: &str) -> Option<i32> {
+    match text.to_lowercase().as_str() {
+        "yes" | "y" | "true" => Some(1),
+        "no" | "n" | "false" => Some(0),
+        number => number.parse().ok().filter(|n: &i32| *n >= 0),
+    }
+}
+
+/// Matches 'Sh_Surender_Singh', 'Sh. Surender Singh' and 'surender singh'
+/// to the same mediator.
+fn mediator_key(name: &str) -> String {
+    parse_legacy_name(name).1.to_lowercase()
+}
+
+fn find_import_profile_by_name(conn: &Connection, name: &str) -> rusqlite::Result<Option<ImportProfile>> {
+    conn.query_row(
+        &format!("SELECT {} FROM import_profiles WHERE name = ?1", IMPORT_PROFILE_COLUMNS),
+        [name],
+        import_profile_from_row,
+    )
+    .optional()
+}
+
+pub(crate) fn import_profile_from_row(row: &rusqlite::Row) -> rusqlite::Result<ImportProfile> {
+    let mapping: String = row.get(2)?;
+    Ok(ImportProfile {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        mapping: serde_json::from_str(&mapping).map_err(|_| bad_stored_value(row, 2, &mapping))?,
+        updated_at: row.get(3)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database_init::CaseKey;
+    use crate::test_support::{intake, register, ScratchDir};
+
+    fn ymd(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn dates_are_read_day_first() {
+        assert_eq!(parse_date("04.03.2021", None), Some(ymd(2021, 3, 4)));
+        assert_eq!(parse_date("04/03/2021", None), Some(ymd(2021, 3, 4)));
+        assert_eq!(parse_date("2021-03-04", None), Some(ymd(2021, 3, 4)));
+        assert_eq!(parse_date("4 Mar 2021", None), Some(ymd(2021, 3, 4)));
+        assert_eq!(parse_date("2021-03-04 00:00", None), Some(ymd(2021, 3, 4)));
+        assert_eq!(parse_date("31/02/2021", None), None);
+        assert_eq!(parse_date("next week", None), None);
+    }
+
+    #[test]
+    fn two_digit_years_are_not_taken_as_the_first_century() {
+        assert_eq!(parse_date("04.03.21", None), Some(ymd(2021, 3, 4)));
+        assert_eq!(parse_date("04/03/99", None), Some(ymd(1999, 3, 4)));
+    }
+
+    #[test]
+    fn whole_numbers_are_excel_serial_dates() {
+        assert_eq!(parse_date("44259", None), Some(ymd(2021, 3, 4)));
+        assert_eq!(parse_date("44259 00:00", None), Some(ymd(2021, 3, 4)));
+        // Day 5 is in January 1900, long before any case on the register.
+        assert_eq!(parse_date("5", None), None);
+    }
+
+    #[test]
+    fn custom_date_format_is_tried_first() {
+        assert_eq!(parse_date("03-04-2021", None), Some(ymd(2021, 4, 3)));
+        assert_eq!(parse_date("03-04-2021", Some("%m-%d-%Y")), Some(ymd(2021, 3, 4)));
+    }
+
+    #[test]
+    fn case_numbers_may_carry_their_year() {
+        assert_eq!(parse_case_no("123"), Some((123, None)));
+        assert_eq!(parse_case_no("123/2021"), Some((123, Some(2021))));
+        assert_eq!(parse_case_no("123 of 2021"), Some((123, Some(2021))));
+        assert_eq!(parse_case_no("MACT 123 / 2021"), Some((123, Some(2021))));
+    }
+
+    #[test]
+    fn malformed_case_numbers_are_rejected() {
+        assert_eq!(parse_case_no(""), None);
+        assert_eq!(parse_case_no("0"), None);
+        assert_eq!(parse_case_no("123/21"), None);
+        assert_eq!(parse_case_no("1/2/2021"), None);
+    }
+
+    fn source_row(cells: &[(ImportField, &str)]) -> (SourceRow, HashMap<ImportField, usize>) {
+        let row = SourceRow {
+            line: 2,
+            cells: cells.iter().map(|(_, value)| value.to_string()).collect(),
+        };
+        let columns = cells.iter().enumerate().map(|(i, (field, _))| (*field, i)).collect();
+        (row, columns)
+    }
+
+    fn complete_row() -> Vec<(ImportField, &'static str)> {
+        vec![
+            (ImportField::CaseNo, "123/2021"),
+            (ImportField::NatureOfCase, "MactCase"),
+            (ImportField::ReceivedFrom, "Court of Sh. A. Kumar"),
+            (ImportField::Date, "04.03.21"),
+            (ImportField::TimeSlot, "10:30 AM"),
+            (ImportField::Party1, "Ram"),
+            (ImportField::Party2, "Shyam"),
+            (ImportField::Mediator, "Sh_Tarun_Shokeen"),
+            (ImportField::NdohDate, "01/04/2021"),
+            (ImportField::Remarks, "parties to bring documents"),
+        ]
+    }
+
+    #[test]
+    fn complete_row_is_normalised() {
+        let (row, columns) = source_row(&complete_row());
+        let case = read_row(&row, &columns, &ImportMapping::default(), None).unwrap();
+
+        assert_eq!((case.case_no, case.year), (123, 2021));
+        assert_eq!(case.nature_of_case, NatureOfCase::MactCase);
+        assert_eq!(case.date, ymd(2021, 3, 4));
+        assert_eq!(case.time_slot, NaiveTime::from_hms_opt(10, 30, 0).unwrap());
+        assert_eq!(case.ndoh_date, Some(ymd(2021, 4, 1)));
+        assert_eq!(case.disposal_of_case, None);
+        assert_eq!(case.remarks.as_deref(), Some("parties to bring documents"));
+    }
+
+    #[test]
+    fn final_disposal_falls_back_to_the_last_hearing_date() {
+        let mut cells = complete_row();
+        cells.push((ImportField::DisposalOfCase, "Settled"));
+        let (row, columns) = source_row(&cells);
+        let case = read_row(&row, &columns, &ImportMapping::default(), None).unwrap();
+
+        assert_eq!(case.disposal_of_case, Some(DisposalOfCase::Settled));
+        assert_eq!(case.disposal_date, Some(ymd(2021, 4, 1)));
+    }
+
+    #[test]
+    fn every_problem_in_a_row_is_reported() {
+        let mut cells = complete_row();
+        cells.retain(|(field, _)| *field != ImportField::TimeSlot && *field != ImportField::Party2);
+        cells.push((ImportField::Year, "2020"));
+        cells.push((ImportField::Connected, "maybe"));
+        let (row, columns) = source_row(&cells);
+        let issues = read_row(&row, &columns, &ImportMapping::default(), None).unwrap_err();
+
+        let fields: Vec<ImportField> = issues.iter().map(|i| i.field).collect();
+        assert_eq!(
+            fields,
+            [ImportField::Party2, ImportField::Year, ImportField::TimeSlot, ImportField::Connected]
+        );
+        assert!(issues.iter().all(|i| i.row == 2));
+    }
+
+    #[test]
+    fn default_time_slot_fills_a_missing_one() {
+        let mut cells = complete_row();
+        cells.retain(|(field, _)| *field != ImportField::TimeSlot);
+        let (row, columns) = source_row(&cells);
+        let ten = NaiveTime::from_hms_opt(10, 0, 0).unwrap();
+        let case = read_row(&row, &columns, &ImportMapping::default(), Some(ten)).unwrap();
+
+        assert_eq!(case.time_slot, ten);
+    }
+
+    const HEADERS: [ImportField; 8] = [
+        ImportField::CaseNo,
+        ImportField::NatureOfCase,
+        ImportField::ReceivedFrom,
+        ImportField::Date,
+        ImportField::TimeSlot,
+        ImportField::Party1,
+        ImportField::Party2,
+        ImportField::Mediator,
+    ];
+
+    // A sheet headed like an export, one row per `(case no, intake date,
+    // mediator)`, starting on line 2.
+    fn sheet(rows: &[(&str, &str, &str)]) -> ImportSource {
+        ImportSource {
+            sheets: Vec::new(),
+            sheet: None,
+            headers: HEADERS.iter().map(|f| f.label().to_string()).collect(),
+            rows: rows
+                .iter()
+                .enumerate()
+                .map(|(i, (case_no, date, mediator))| SourceRow {
+                    line: i + 2,
+                    cells: [*case_no, "MactCase", "MACT Court", *date, "10:00", "Ram", "Shyam", *mediator]
+                        .map(str::to_string)
+                        .to_vec(),
+                })
+                .collect(),
+        }
+    }
+
+    fn mapping(create_missing: bool) -> ImportMapping {
+        ImportMapping {
+            columns: HEADERS.iter().map(|f| (*f, f.label().to_string())).collect(),
+            create_missing,
+            ..Default::default()
+        }
+    }
+
+    fn import(db: &Db, source: &ImportSource, create_missing: bool, dry_run: bool) -> ImportReport {
+        db.import_cases(Path::new("legacy.csv"), source, &mapping(create_missing), dry_run)
+            .unwrap()
+    }
+
+    fn case_count(db: &Db) -> usize {
+        db.query_cases_filtered(&Default::default(), &Default::default()).unwrap().total
+    }
+
+    #[test]
+    fn dry_run_writes_nothing() {
+        let dir = ScratchDir::new("import-dry-run");
+        let db = register(&dir);
+        let source = sheet(&[
+            ("1/2021", "04.03.2021", "Sh Tarun Shokeen"),
+            ("2/2021", "05.03.2021", "Dr New Mediator"),
+        ]);
+
+        let report = import(&db, &source, true, true);
+
+        assert_eq!((report.rows, report.valid, report.imported), (2, 2, 0));
+        assert_eq!(report.new_mediators, ["Dr New Mediator"]);
+        assert!(!report.committed);
+        assert_eq!(case_count(&db), 0);
+        assert_eq!(db.get_mediators(true).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn cases_already_on_the_register_are_reported_and_skipped() {
+        let dir = ScratchDir::new("import-conflict");
+        let db = register(&dir);
+        db.insert_case(intake(1, 2021)).unwrap();
+        let source = sheet(&[
+            ("1/2021", "04.03.2021", "Sh Tarun Shokeen"),
+            ("2/2021", "05.03.2021", "Sh Tarun Shokeen"),
+        ]);
+
+        let report = import(&db, &source, false, false);
+
+        assert!(report.committed);
+        assert_eq!(report.imported, 1);
+        let conflicts: Vec<(usize, i32, i32)> = report.conflicts.iter().map(|c| (c.row, c.case_no, c.year)).collect();
+        assert_eq!(conflicts, [(2, 1, 2021)]);
+        assert_eq!(case_count(&db), 2);
+        // The case already there keeps its own parties.
+        assert_eq!(db.get_case(CaseKey { case_no: 1, year: 2021 }).unwrap().party1, "A");
+    }
+
+    #[test]
+    fn a_case_repeated_within_the_file_is_an_error() {
+        let dir = ScratchDir::new("import-repeated");
+        let db = register(&dir);
+        let source = sheet(&[
+            ("1/2021", "04.03.2021", "Sh Tarun Shokeen"),
+            ("1 of 2021", "05.03.2021", "Sh Tarun Shokeen"),
+        ]);
+
+        let report = import(&db, &source, false, false);
+
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!((report.errors[0].row, report.errors[0].field), (3, ImportField::CaseNo));
+        assert!(report.errors[0].message.contains("row 2"));
+        assert!(!report.committed);
+        assert_eq!(case_count(&db), 0);
+    }
+
+    #[test]
+    fn one_bad_row_keeps_the_whole_file_out() {
+        let dir = ScratchDir::new("import-bad-row");
+        let db = register(&dir);
+        let source = sheet(&[
+            ("1/2021", "04.03.2021", "Sh Tarun Shokeen"),
+            ("2/2021", "someday", "Sh Tarun Shokeen"),
+            ("3/2021", "06.03.2021", "Dr New Mediator"),
+        ]);
+
+        let report = import(&db, &source, true, false);
+
+        assert_eq!(report.valid, 2);
+        let errors: Vec<(usize, ImportField)> = report.errors.iter().map(|e| (e.row, e.field)).collect();
+        assert_eq!(errors, [(3, ImportField::Date)]);
+        assert!(!report.committed);
+        assert_eq!(report.imported, 0);
+        assert_eq!(case_count(&db), 0);
+        assert_eq!(db.get_mediators(true).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn create_missing_adds_unknown_mediators_as_retired() {
+        let dir = ScratchDir::new("import-create-missing");
+        let db = register(&dir);
+        let source = sheet(&[
+            ("1/2021", "04.03.2021", "Dr. Anita Rao"),
+            ("2/2021", "05.03.2021", "dr anita rao"),
+            ("3/2021", "06.03.2021", "Shri Tarun Shokeen"),
+        ]);
+
+        let refused = import(&db, &source, false, false);
+        assert_eq!(refused.errors.len(), 2);
+        assert!(refused.errors.iter().all(|e| e.field == ImportField::Mediator));
+
+        let report = import(&db, &source, true, false);
+        assert!(report.committed);
+        assert_eq!(report.imported, 3);
+        assert_eq!(report.new_mediators, ["Dr. Anita Rao"]);
+
+        let added = db.get_mediators(true).unwrap().into_iter().find(|m| m.id != 1).unwrap();
+        assert_eq!((added.honorific.as_str(), added.display_name.as_str()), ("Dr", "Anita Rao"));
+        assert!(!added.active);
+        assert!(db.get_mediators(false).unwrap().iter().all(|m| m.id == 1));
+        let held_by = |case_no| db.get_case(CaseKey { case_no, year: 2021 }).unwrap().mediator_id;
+        assert_eq!((held_by(1), held_by(2), held_by(3)), (added.id, added.id, 1));
+    }
+}
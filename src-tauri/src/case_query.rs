@@ -0,0 +1,652 @@
+use chrono::NaiveDate;
+use rusqlite::{Connection, ToSql};
+use serde::Serialize;
+
+use crate::database_init::{case_from_row, Case, Db, DisposalOfCase, NatureOfCase, CASE_COLUMNS, MEDIATOR_AS_OF};
+use crate::error::AppResult;
+
+/// Aggregates read by `summary_from_row`, for whatever rows are grouped:
+/// the row count, the undisposed count, then one count per disposal in
+/// `DisposalOfCase::ALL` order.
+fn summary_columns() -> String {
+    let mut columns = vec![
+        "COUNT(*)".to_string(),
+        "COALESCE(SUM(disposal_of_case IS NULL), 0)".to_string(),
+    ];
+    columns.extend(
+        DisposalOfCase::ALL
+            .iter()
+            .map(|d| format!("COALESCE(SUM(disposal_of_case = '{}'), 0)", d.code())),
+    );
+    columns.join(", ")
+}
+
+/// Which of a case's dates a `DateRange` is checked against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateField {
+    /// The intake date or any hearing date; what the filtered report has
+    /// always used.
+    Any,
+    Intake,
+    Hearing,
+    Disposal,
+    /// The intake date or the next date of hearing: the cases on the board
+    /// for a day, as today's report lists them.
+    Listed,
+}
+
+impl DateField {
+    pub const ALL: [DateField; 5] = [
+        DateField::Any,
+        DateField::Intake,
+        DateField::Hearing,
+        DateField::Disposal,
+        DateField::Listed,
+    ];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            DateField::Any => "any",
+            DateField::Intake => "intake",
+            DateField::Hearing => "hearing",
+            DateField::Disposal => "disposal",
+            DateField::Listed => "listed",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DateField::Any => "Intake or hearing date",
+            DateField::Intake => "Intake date",
+            DateField::Hearing => "Hearing date",
+            DateField::Disposal => "Disposal date",
+            DateField::Listed => "Intake or next hearing date",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|f| f.code() == code)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DateRange {
+    pub field: DateField,
+    /// `None` leaves that end of the range open.
+    pub start: Option<NaiveDate>,
+    pub end: Option<NaiveDate>,
+}
+
+/// One accepted disposal status. A filter with several matches any of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisposalFilter {
+    /// Nothing recorded yet.
+    Undisposed,
+    Is(DisposalOfCase),
+}
+
+impl DisposalFilter {
+    /// Code for `Undisposed`; the others use the `DisposalOfCase` codes.
+    pub const UNDISPOSED: &'static str = "None";
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        if code == Self::UNDISPOSED {
+            return Some(DisposalFilter::Undisposed);
+        }
+        DisposalOfCase::from_code(code).map(DisposalFilter::Is)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortField {
+    CaseNo,
+    Year,
+    IntakeDate,
+    NextHearing,
+    Party1,
+    Party2,
+    ReceivedFrom,
+    Mediator,
+    Disposal,
+    DisposalDate,
+}
+
+impl SortField {
+    pub const ALL: [SortField; 10] = [
+        SortField::CaseNo,
+        SortField::Year,
+        SortField::IntakeDate,
+        SortField::NextHearing,
+        SortField::Party1,
+        SortField::Party2,
+        SortField::ReceivedFrom,
+        SortField::Mediator,
+        SortField::Disposal,
+        SortField::DisposalDate,
+    ];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            SortField::CaseNo => "case_no",
+            SortField::Year => "year",
+            SortField::IntakeDate => "date",
+            SortField::NextHearing => "ndoh_date",
+            SortField::Party1 => "party1",
+            SortField::Party2 => "party2",
+            SortField::ReceivedFrom => "received_from",
+            SortField::Mediator => "assigned_to",
+            SortField::Disposal => "disposal_of_case",
+            SortField::DisposalDate => "disposal_date",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|f| f.code() == code)
+    }
+
+    fn sql(self) -> &'static str {
+        match self {
+            SortField::CaseNo => "case_no",
+            SortField::Year => "year",
+            SortField::IntakeDate => "date",
+            SortField::NextHearing => "ndoh_date",
+            SortField::Party1 => "party1 COLLATE NOCASE",
+            SortField::Party2 => "party2 COLLATE NOCASE",
+            SortField::ReceivedFrom => "received_from COLLATE NOCASE",
+            SortField::Mediator => "assigned_to COLLATE NOCASE",
+            SortField::Disposal => "disposal_of_case",
+            SortField::DisposalDate => "disposal_date",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SortKey {
+    pub field: SortField,
+    pub descending: bool,
+}
+
+/// Everything `query_cases_filtered` can narrow by. Unset fields don't
+/// filter; an empty `disposal` accepts every status.
+#[derive(Debug, Clone, Default)]
+pub struct CaseFilter {
+    pub nature_of_case: Option<NatureOfCase>,
+    pub mediator_id: Option<i32>,
+    /// With `mediator_id`, match whoever held the case on this date rather
+    /// than its mediator today.
+    pub assigned_as_of: Option<NaiveDate>,
+    /// Part of the referring court's name, any case.
+    pub received_from: Option<String>,
+    pub disposal: Vec<DisposalFilter>,
+    pub connected: Option<bool>,
+    pub case_no_from: Option<i32>,
+    pub case_no_to: Option<i32>,
+    pub year: Option<i32>,
+    /// Part of either party's name, any case.
+    pub party: Option<String>,
+    pub date_range: Option<DateRange>,
+}
+
+/// Which slice of the matches to return. Without a limit every match is.
+#[derive(Debug, Clone, Default)]
+pub struct CasePageRequest {
+    pub sort: Vec<SortKey>,
+    pub limit: Option<u32>,
+    pub offset: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CasePage {
+    pub cases: Vec<Case>,
+    /// Matches across all pages.
+    pub total: usize,
+    /// Disposal counts across all pages, then the same split by nature of
+    /// case and by mediator.
+    pub summary: CaseSummary,
+    pub by_nature: Vec<NatureBreakdown>,
+    pub by_mediator: Vec<MediatorBreakdown>,
+}
+
+/// How many cases fall in each disposal bucket. `undisposed`, `interim`
+/// and `disposed` add up to `total`; `by_disposal` splits the last two by
+/// outcome, one entry per `DisposalOfCase` whether or not any case has it.
+#[derive(Debug, Serialize)]
+pub struct CaseSummary {
+    pub total: usize,
+    /// Nothing recorded yet.
+    pub undisposed: usize,
+    /// Last sitting adjourned, e.g. `Pending`.
+    pub interim: usize,
+    /// A final outcome is recorded.
+    pub disposed: usize,
+    pub by_disposal: Vec<DisposalCount>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DisposalCount {
+    pub disposal: DisposalOfCase,
+    pub label: &'static str,
+    pub is_final: bool,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NatureBreakdown {
+    pub nature_of_case: NatureOfCase,
+    pub counts: CaseSummary,
+}
+
+/// Counts for the cases a mediator holds now.
+#[derive(Debug, Serialize)]
+pub struct MediatorBreakdown {
+    pub mediator_id: i32,
+    pub mediator: String,
+    pub counts: CaseSummary,
+}
+
+/// The disposal counts `CasePage` carries, for callers that walk the cases
+/// themselves with `for_each_case`.
+#[derive(Debug, Serialize)]
+pub struct CaseCounts {
+    pub summary: CaseSummary,
+    pub by_nature: Vec<NatureBreakdown>,
+    pub by_mediator: Vec<MediatorBreakdown>,
+}
+
+impl CaseSummary {
+    /// Column titles for `counts`.
+    pub fn count_titles(&self) -> Vec<&'static str> {
+        let mut titles = vec!["Total", "Undisposed"];
+        titles.extend(self.by_disposal.iter().map(|d| d.label));
+        titles
+    }
+
+    /// The total, the undisposed count, then one count per disposal.
+    pub fn counts(&self) -> Vec<usize> {
+        let mut counts = vec![self.total, self.undisposed];
+        counts.extend(self.by_disposal.iter().map(|d| d.count));
+        counts
+    }
+}
+
+impl Db {
+    pub fn query_cases_filtered(&self, filter: &CaseFilter, page: &CasePageRequest) -> AppResult<CasePage> {
+        let conn = self.reader()?;
+        let counts = counts(&conn, filter)?;
+        let (where_sql, mut params) = where_clause(filter);
+
+        let mut query = format!(
+            "SELECT {} FROM case_table WHERE {} ORDER BY {}",
+            CASE_COLUMNS,
+            where_sql,
+            order_by(&page.sort)
+        );
+        if let Some(limit) = page.limit {
+            query += " LIMIT ? OFFSET ?";
+            params.push(Box::new(limit));
+            params.push(Box::new(page.offset));
+        }
+
+        let mut stmt = conn.prepare(&query)?;
+        let cases = stmt
+            .query_map(&param_refs(&params)[..], case_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(CasePage {
+            cases,
+            total: counts.summary.total,
+            summary: counts.summary,
+            by_nature: counts.by_nature,
+            by_mediator: counts.by_mediator,
+        })
+    }
+
+    pub fn case_counts(&self, filter: &CaseFilter) -> AppResult<CaseCounts> {
+        counts(&*self.reader()?, filter)
+    }
+
+    /// Hands every case matching `filter` to `each` in `sort` order, one row
+    /// at a time, so exports never hold the whole register in memory.
+    /// Returns how many cases were visited.
+    pub fn for_each_case(
+        &self,
+        filter: &CaseFilter,
+        sort: &[SortKey],
+        mut each: impl FnMut(&Case) -> AppResult<()>,
+    ) -> AppResult<usize> {
+        let conn = self.reader()?;
+        let (where_sql, params) = where_clause(filter);
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM case_table WHERE {} ORDER BY {}",
+            CASE_COLUMNS,
+            where_sql,
+            order_by(sort)
+        ))?;
+        let mut rows = stmt.query(&param_refs(&params)[..])?;
+
+        let mut visited = 0;
+        while let Some(row) = rows.next()? {
+            each(&case_from_row(row)?)?;
+            visited += 1;
+        }
+        Ok(visited)
+    }
+}
+
+fn counts(conn: &Connection, filter: &CaseFilter) -> AppResult<CaseCounts> {
+    let (where_sql, params) = where_clause(filter);
+
+    let summary = conn.query_row(
+        &format!("SELECT {} FROM case_table WHERE {}", summary_columns(), where_sql),
+        &param_refs(&params)[..],
+        |row| summary_from_row(row, 0),
+    )?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT nature_of_case, {} FROM case_table WHERE {}
+         GROUP BY nature_of_case ORDER BY nature_of_case",
+        summary_columns(), where_sql
+    ))?;
+    let by_nature = stmt
+        .query_map(&param_refs(&params)[..], |row| {
+            Ok(NatureBreakdown {
+                nature_of_case: row.get(0)?,
+                counts: summary_from_row(row, 1)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    // Cases picked by who held them on a date are counted under that
+    // mediator too, not under whoever holds them now.
+    let (held_by, mut mediator_params) = match filter.assigned_as_of {
+        Some(as_of) => (MEDIATOR_AS_OF, vec![Box::new(as_of.to_string()) as Box<dyn ToSql>]),
+        None => ("mediator_id", Vec::new()),
+    };
+    mediator_params.extend(params);
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT held_by,
+                (SELECT TRIM(m.honorific || ' ' || m.display_name) FROM mediators m
+                 WHERE m.id = held_by),
+                {}
+         FROM (SELECT {} AS held_by, * FROM case_table WHERE {})
+         GROUP BY held_by ORDER BY 2 COLLATE NOCASE",
+        summary_columns(), held_by, where_sql
+    ))?;
+    let by_mediator = stmt
+        .query_map(&param_refs(&mediator_params)[..], |row| {
+            Ok(MediatorBreakdown {
+                mediator_id: row.get(0)?,
+                mediator: row.get(1)?,
+                counts: summary_from_row(row, 2)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    Ok(CaseCounts { summary, by_nature, by_mediator })
+}
+
+/// The `WHERE` condition for `filter` with its parameters, bound in order to
+/// plain `?` placeholders.
+fn where_clause(filter: &CaseFilter) -> (String, Vec<Box<dyn ToSql>>) {
+    let mut clauses: Vec<String> = Vec::new();
+    let mut params: Vec<Box<dyn ToSql>> = Vec::new();
+
+    if let Some(nature) = filter.nature_of_case {
+        clauses.push("nature_of_case = ?".into());
+        params.push(Box::new(nature));
+    }
+
+    if let Some(id) = filter.mediator_id {
+        match filter.assigned_as_of {
+            Some(as_of) => {
+                clauses.push(format!("{} = ?", MEDIATOR_AS_OF));
+                params.push(Box::new(as_of.to_string()));
+            }
+            None => clauses.push("mediator_id = ?".into()),
+        }
+        params.push(Box::new(id));
+    }
+
+    if let Some(court) = filter.received_from.as_deref().filter(|s| !s.trim().is_empty()) {
+        clauses.push("received_from LIKE ? ESCAPE '\\'".into());
+        params.push(Box::new(like_pattern(court)));
+    }
+
+    if let Some(party) = filter.party.as_deref().filter(|s| !s.trim().is_empty()) {
+        clauses.push("(party1 LIKE ? ESCAPE '\\' OR party2 LIKE ? ESCAPE '\\')".into());
+        let pattern = like_pattern(party);
+        params.push(Box::new(pattern.clone()));
+        params.push(Box::new(pattern));
+    }
+
+    if !filter.disposal.is_empty() {
+        let mut accepted = Vec::new();
+        for status in &filter.disposal {
+            match status {
+                DisposalFilter::Undisposed => accepted.push("disposal_of_case IS NULL".to_string()),
+                DisposalFilter::Is(disposal) => {
+                    accepted.push("disposal_of_case = ?".to_string());
+                    params.push(Box::new(disposal.code()));
+                }
+            }
+        }
+        clauses.push(format!("({})", accepted.join(" OR ")));
+    }
+
+    if let Some(connected) = filter.connected {
+        clauses.push(if connected {
+            "COALESCE(connected, 0) <> 0".into()
+        } else {
+            "COALESCE(connected, 0) = 0".into()
+        });
+    }
+
+    if let Some(from) = filter.case_no_from {
+        clauses.push("case_no >= ?".into());
+        params.push(Box::new(from));
+    }
+    if let Some(to) = filter.case_no_to {
+        clauses.push("case_no <= ?".into());
+        params.push(Box::new(to));
+    }
+    if let Some(year) = filter.year {
+        clauses.push("year = ?".into());
+        params.push(Box::new(year));
+    }
+
+    if let Some(range) = filter.date_range {
+        let clause = match range.field {
+            DateField::Any => {
+                let intake = date_within("date", &range, &mut params);
+                format!("({} OR {})", intake, heard_within(&range, &mut params))
+            }
+            DateField::Intake => date_within("date", &range, &mut params),
+            DateField::Hearing => heard_within(&range, &mut params),
+            DateField::Disposal => date_within("disposal_date", &range, &mut params),
+            DateField::Listed => {
+                let intake = date_within("date", &range, &mut params);
+                format!("({} OR {})", intake, date_within("ndoh_date", &range, &mut params))
+            }
+        };
+        clauses.push(clause);
+    }
+
+    if clauses.is_empty() {
+        ("1".to_string(), params)
+    } else {
+        (clauses.join(" AND "), params)
+    }
+}
+
+// Dates are stored as ISO text and compared as text, so an open end of the
+// range is left out rather than stood in for by an extreme date, which
+// would not print as a four-digit year.
+fn date_within(column: &str, range: &DateRange, params: &mut Vec<Box<dyn ToSql>>) -> String {
+    let mut bounds = Vec::new();
+    if let Some(start) = range.start {
+        bounds.push(format!("{} >= ?", column));
+        params.push(Box::new(start.to_string()));
+    }
+    if let Some(end) = range.end {
+        bounds.push(format!("{} <= ?", column));
+        params.push(Box::new(end.to_string()));
+    }
+
+    if bounds.is_empty() {
+        format!("{} IS NOT NULL", column)
+    } else {
+        bounds.join(" AND ")
+    }
+}
+
+fn heard_within(range: &DateRange, params: &mut Vec<Box<dyn ToSql>>) -> String {
+    format!(
+        "EXISTS (SELECT 1 FROM hearings h WHERE h.case_id = case_table.id AND {})",
+        date_within("h.hearing_date", range, params)
+    )
+}
+
+fn order_by(sort: &[SortKey]) -> String {
+    let mut terms: Vec<String> = sort
+        .iter()
+        .map(|key| format!("{} {}", key.field.sql(), if key.descending { "DESC" } else { "ASC" }))
+        .collect();
+    if terms.is_empty() {
+        terms.push("year ASC, case_no ASC".into());
+    }
+    // Ties always fall back to insertion order, so pages never overlap.
+    terms.push("id ASC".into());
+    terms.join(", ")
+}
+
+/// `%text%` with LIKE's own wildcards in `text` taken literally.
+fn like_pattern(text: &str) -> String {
+    let escaped = text
+        .trim()
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+fn summary_from_row(row: &rusqlite::Row, first: usize) -> rusqlite::Result<CaseSummary> {
+    let count = |i: usize| row.get::<_, i64>(first + i).map(|n| n as usize);
+
+    let by_disposal = DisposalOfCase::ALL
+        .into_iter()
+        .enumerate()
+        .map(|(i, disposal)| {
+            Ok(DisposalCount {
+                disposal,
+                label: disposal.label(),
+                is_final: disposal.is_final(),
+                count: count(2 + i)?,
+            })
+        })
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let counted = |is_final: bool| {
+        by_disposal
+            .iter()
+            .filter(|c| c.is_final == is_final)
+            .map(|c| c.count)
+            .sum()
+    };
+
+    Ok(CaseSummary {
+        total: count(0)?,
+        undisposed: count(1)?,
+        interim: counted(false),
+        disposed: counted(true),
+        by_disposal,
+    })
+}
+
+fn param_refs(params: &[Box<dyn ToSql>]) -> Vec<&dyn ToSql> {
+    params.iter().map(|b| b.as_ref()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Local};
+
+    use super::*;
+    use crate::database_init::CaseKey;
+    use crate::mediators::MediatorDetails;
+    use crate::test_support::{intake, register, ScratchDir};
+
+    // A register holding one case taken in today.
+    fn register_with_one_case(name: &str) -> (ScratchDir, Db) {
+        let dir = ScratchDir::new(name);
+        let db = register(&dir);
+        db.insert_case(intake(1, 2024)).unwrap();
+        (dir, db)
+    }
+
+    fn matches(db: &Db, field: DateField, start: Option<NaiveDate>, end: Option<NaiveDate>) -> usize {
+        let filter = CaseFilter {
+            date_range: Some(DateRange { field, start, end }),
+            ..Default::default()
+        };
+        db.query_cases_filtered(&filter, &Default::default()).unwrap().total
+    }
+
+    #[test]
+    fn start_only_range_runs_to_the_end() {
+        let (_dir, db) = register_with_one_case("range-start");
+        let today = Local::now().date_naive();
+
+        for field in [DateField::Intake, DateField::Listed, DateField::Any] {
+            assert_eq!(matches(&db, field, Some(today - Duration::days(30)), None), 1);
+            assert_eq!(matches(&db, field, Some(today + Duration::days(1)), None), 0);
+        }
+    }
+
+    #[test]
+    fn end_only_range_runs_from_the_beginning() {
+        let (_dir, db) = register_with_one_case("range-end");
+        let today = Local::now().date_naive();
+
+        for field in [DateField::Intake, DateField::Listed, DateField::Any] {
+            assert_eq!(matches(&db, field, None, Some(today)), 1);
+            assert_eq!(matches(&db, field, None, Some(today - Duration::days(1))), 0);
+        }
+    }
+
+    #[test]
+    fn cases_held_on_a_date_are_counted_under_who_held_them() {
+        let dir = ScratchDir::new("held-by");
+        let db = register(&dir);
+        let second = db
+            .add_mediator(MediatorDetails {
+                display_name: "Surender Singh".to_string(),
+                honorific: "Sh".to_string(),
+                phone: None,
+                email: None,
+                joined_on: None,
+            })
+            .unwrap();
+        db.insert_case(intake(1, 2024)).unwrap();
+        db.insert_case(intake(2, 2024)).unwrap();
+        let today = Local::now().date_naive();
+        db.reassign_case(CaseKey { case_no: 1, year: 2024 }, second.id, today, "leave")
+            .unwrap();
+
+        let filter = CaseFilter {
+            mediator_id: Some(1),
+            assigned_as_of: Some(today - Duration::days(1)),
+            ..Default::default()
+        };
+        let page = db.query_cases_filtered(&filter, &Default::default()).unwrap();
+
+        assert_eq!(page.total, 2);
+        let by_mediator: Vec<(i32, usize)> = page
+            .by_mediator
+            .iter()
+            .map(|m| (m.mediator_id, m.counts.total))
+            .collect();
+        assert_eq!(by_mediator, [(1, 2)]);
+    }
+}
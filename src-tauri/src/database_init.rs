@@ -1,426 +1,1050 @@
-use chrono::{Datelike, Local, NaiveDate, NaiveTime};
-use convert_case::{ Casing};
-use rusqlite::{params, Connection, Result};
+use chrono::{Local, NaiveDate, NaiveTime};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef};
+use rusqlite::{params, Connection, OptionalExtension, ToSql};
 use serde::{Deserialize, Serialize};
-use serde_json::to_string;
-use serde_plain;
-use std::sync::{Arc, Mutex};
+use serde_json::json;
+use std::sync::{Arc, Mutex, MutexGuard};
 
+use crate::audit::{self, AuditCase};
+use crate::disposal::{require_active_reason, DisposalDetails};
+use crate::error::{AppError, AppResult, BadStoredValue};
+use crate::mediators::require_active_mediator;
+use crate::migrations;
+use crate::pool::{self, PooledConnection, ReaderPool};
+
+/// Handle to an open register. Cloning it is cheap and every clone shares
+/// the same connections.
+///
+/// The register runs in WAL mode with one writer connection and a small
+/// pool of readers, so a long report query never holds up a case being
+/// entered at the counter, and vice versa.
 #[derive(Clone)]
 pub struct Db {
-    conn: Arc<Mutex<Connection>>,
+    pub(crate) writer: Arc<Mutex<Connection>>,
+    pub(crate) readers: Arc<ReaderPool>,
+    pub(crate) operator: Arc<Mutex<String>>,
+    pub(crate) path: std::path::PathBuf,
 }
 
 impl Db {
-    pub fn init(path: std::path::PathBuf) -> Result<Self> {
-        let conn = Connection::open(path)?;
-        let db = Self {
-            conn: Arc::new(Mutex::new(conn)),
-        };
-        db.create_table()?;
-        Ok(db)
-    }
+    pub fn init(path: std::path::PathBuf) -> AppResult<Self> {
+        let conn = pool::open_connection(&path, false)?;
+        migrations::migrate(&conn, &path)?;
+        conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))?;
 
-    fn create_table(&self) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS case_table (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                case_no INTEGER NOT NULL,
-                year INTEGER NOT NULL,
-                nature_of_case TEXT NOT NULL,
-                received_from TEXT NOT NULL,
-                date TEXT NOT NULL,
-                time_slot TEXT NOT NULL,
-                party1 TEXT NOT NULL,
-                party2 TEXT NOT NULL,
-                assigned_to TEXT NOT NULL,
-                ndoh_date TEXT,
-                ndoh_time TEXT,
-                disposal_of_case TEXT,
-                connected INTEGER DEFAULT 0,
-                UNIQUE (case_no, year)
-            );",
-            [],
-        )?;
+        // Until the UI says who is at the desk, writes are put down to the
+        // account the app is running under.
+        let operator = std::env::var("USERNAME")
+            .or_else(|_| std::env::var("USER"))
+            .unwrap_or_else(|_| "unknown".to_string());
 
+        Ok(Self {
+            writer: Arc::new(Mutex::new(conn)),
+            readers: Arc::new(ReaderPool::new(path.clone())),
+            operator: Arc::new(Mutex::new(operator)),
+            path,
+        })
+    }
 
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS assigned_to (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                name TEXT UNIQUE NOT NULL
-            );",
-            [],
-        )?;
-
-        conn.execute(
-            "INSERT OR IGNORE INTO assigned_to (name) VALUES
-                ('Judge_In_Charge'),
-                ('Sh_Surender_Singh'),
-                ('Sh_Nishant_S_Dewan'),
-                ('Ms_Shashi_Jaiswal'),
-                ('Sh_Tarun_Shokeen'),
-                ('Sh_Manmohan_Goel'),
-                ('Sh_Anuj_Rajput'),
-                ('Sh_S_K_Malik'),
-                ('Ms_Indu_Shekhar'),
-                ('Ms_Surinder_Kaur'),
-                ('Sh_Muskesh_Goel'),
-                ('Sh_Anil_Kumar_Chhabra'),
-                ('Ms_Neeru_Nagpal'),
-                ('Ms_Savita_Kasana'),
-                ('Sh_O_P_Gupta'),
-                ('Sh_Jagdish_Sethi'),
-                ('Ms_Meenakshi_Juneja'),
-                ('Sh_R_B_Singh'),
-                ('Sh_Rajbir_Malik'),
-                ('Ms_Nidhi_T_Raj'),
-                ('Ms_Kavita_Kapil'),
-                ('Ms_Jyotsna_Jena'),
-                ('Sh_Chandra_Bose'),
-                ('Sh_Naveen_Tayal'),
-                ('Ms_Urmila_Yadav');",
-            [],
-        )?;
+    /// The writer connection. Writes queue up here one at a time, which is
+    /// all SQLite allows anyway; take it only for the write itself.
+    pub(crate) fn writer(&self) -> MutexGuard<'_, Connection> {
+        pool::lock(&self.writer)
+    }
 
-        Ok(())
+    /// A read-only connection from the pool, returned to it on drop.
+    pub(crate) fn reader(&self) -> AppResult<PooledConnection<'_>> {
+        self.readers.get()
     }
 
-    pub fn insert_case(
-        &self,
-        case_no: i32,
-        year: i32,
-        nature_of_case: String,
-        received_from: String,
-        time_slot: NaiveTime,
-        party1: String,
-        party2: String,
-        assigned_to: String,
-    ) -> Result<usize> {
-        let conn = self.conn.lock().unwrap();
-
-        let date = NaiveDate::from_ymd_opt(
-            Local::now().year(),
-            Local::now().month(),
-            Local::now().day(),
-        )
-        .expect("Failed to get current date");
+    pub fn insert_case(&self, intake: CaseIntake) -> AppResult<usize> {
+        let operator = self.operator();
+        let conn = self.writer();
+        let tx = conn.unchecked_transaction()?;
+        require_active_mediator(&tx, intake.mediator_id)?;
+
+        let date = Local::now().date_naive();
 
         println!(
-            "Inserting case: {} , year {} , Nature: {:?}, Received From: {}, Date: {}, Time Slot: {}, Party1: {}, Party2: {}, Assigned To: {}",
-            case_no, year, nature_of_case, received_from, date, time_slot, party1, party2, assigned_to
+            "Inserting case: {} , year {} , Nature: {:?}, Received From: {}, Date: {}, Time Slot: {}, Party1: {}, Party2: {}, Mediator: {}",
+            intake.case_no,
+            intake.year,
+            intake.nature_of_case,
+            intake.received_from,
+            date,
+            intake.time_slot,
+            intake.party1,
+            intake.party2,
+            intake.mediator_id
         );
 
-        let nature_of_case_str = to_string(&nature_of_case)
-            .unwrap()
-            .trim_matches('"')
-            .to_string();
-
-        conn.execute(
+        tx.execute(
             "INSERT INTO case_table (
                 case_no, year, nature_of_case, received_from, date, time_slot,
-                party1, party2, assigned_to
+                party1, party2, mediator_id
             ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
             params![
-                case_no,
-                year,
-                nature_of_case_str,
-                received_from,
+                intake.case_no,
+                intake.year,
+                intake.nature_of_case,
+                intake.received_from,
                 date.to_string(),
-                time_slot.to_string(),
-                party1,
-                party2,
-                assigned_to
+                intake.time_slot.to_string(),
+                intake.party1,
+                intake.party2,
+                intake.mediator_id
             ],
-        )?;
+        )
+        .map_err(|e| AppError::on_duplicate_case(e, intake.case_no, intake.year))?;
+
+        let id = tx.last_insert_rowid() as i32;
+        let inserted = case_by_id(&tx, id)?;
+        audit::record(&tx, &operator, "insert_case", Some(inserted.audit_case()), None, Some(&json!(inserted)))?;
 
-        Ok(conn.last_insert_rowid() as usize)
+        tx.commit()?;
+        Ok(id as usize)
     }
 
-    pub fn update_case_details(
+    /// Replaces the intake details of the case at `key` with `corrected`,
+    /// which may also carry a corrected case number or year. Only fields
+    /// that actually change are written to `case_edits`.
+    pub fn edit_case(
         &self,
-        case_no: i32,
-        ndoh_date: NaiveDate,
-        ndoh_time: NaiveTime,
-        disposal_of_case: DisposalOfCase,
-        connected: Option<i32>,
-    ) -> Result<usize> {
-        let conn = self.conn.lock().unwrap();
-
-        let mut stmt = conn.prepare("SELECT COUNT(*) FROM case_table WHERE case_no = ?1")?;
-        let count: i64 = stmt.query_row([case_no], |row| row.get(0))?;
-
-        if count == 0 {
-            println!("No case found with case_no: {}", case_no);
-            return Ok(0);
+        key: CaseKey,
+        corrected: CaseIntake,
+        edited_by: &str,
+        reason: &str,
+    ) -> AppResult<Case> {
+        let operator = self.operator();
+        let conn = self.writer();
+        let tx = conn.unchecked_transaction()?;
+
+        let current = case_by_key(&tx, key)?;
+
+        let changes = current.intake_changes(&corrected);
+        if changes.is_empty() {
+            return Ok(current);
+        }
+
+        // A retired mediator's existing cases stay editable; they just can't
+        // be handed new ones.
+        if corrected.mediator_id != current.mediator_id {
+            require_active_mediator(&tx, corrected.mediator_id)?;
         }
 
-        let disposal_str = to_string(&disposal_of_case)
-            .unwrap()
-            .trim_matches('"')
-            .to_string();
+        println!(
+            "Editing case_no {} of {} by {}: {}",
+            key.case_no, key.year, edited_by, reason
+        );
+
+        tx.execute(
+            "UPDATE case_table
+             SET case_no = ?1,
+                 year = ?2,
+                 nature_of_case = ?3,
+                 received_from = ?4,
+                 time_slot = ?5,
+                 party1 = ?6,
+                 party2 = ?7,
+                 mediator_id = ?8
+             WHERE id = ?9",
+            params![
+                corrected.case_no,
+                corrected.year,
+                corrected.nature_of_case,
+                corrected.received_from,
+                corrected.time_slot.to_string(),
+                corrected.party1,
+                corrected.party2,
+                corrected.mediator_id,
+                current.id
+            ],
+        )
+        .map_err(|e| AppError::on_duplicate_case(e, corrected.case_no, corrected.year))?;
+
+        tx.execute(
+            "INSERT INTO case_edits (case_id, edited_by, reason, changes)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                current.id,
+                edited_by,
+                reason,
+                serde_json::Value::Object(changes).to_string()
+            ],
+        )?;
+
+        let edited = case_by_id(&tx, current.id)?;
+        audit::record(
+            &tx,
+            &operator,
+            "edit_case",
+            Some(current.audit_case()),
+            Some(&json!(current)),
+            Some(&json!(edited)),
+        )?;
+
+        tx.commit()?;
+        Ok(edited)
+    }
+
+    pub fn get_case_edits(&self, key: CaseKey) -> AppResult<Vec<CaseEdit>> {
+        let conn = self.reader()?;
+
+        let case_id: i32 = conn
+            .query_row(
+                "SELECT id FROM case_table WHERE case_no = ?1 AND year = ?2",
+                params![key.case_no, key.year],
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or(key.not_found())?;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM case_edits WHERE case_id = ?1 ORDER BY id",
+            CASE_EDIT_COLUMNS
+        ))?;
+
+        let edits = stmt
+            .query_map([case_id], case_edit_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(edits)
+    }
+
+    pub fn update_case_details(&self, key: CaseKey, update: CaseUpdate) -> AppResult<usize> {
+        let operator = self.operator();
+        let conn = self.writer();
+        let CaseKey { case_no, year } = key;
+        let CaseUpdate {
+            ndoh_date,
+            ndoh_time,
+            disposal_of_case,
+            connected,
+            remarks,
+            disposal,
+        } = update;
+
+        let disposal_str = disposal_of_case.code();
 
         println!(
-            "Updating case_no {} with NDOH date: {}, NDOH time: {}, Disposal: {}, Connected: {:?}",
-            case_no, ndoh_date, ndoh_time, disposal_str, connected
+            "Updating case_no {} of {} with NDOH date: {}, NDOH time: {}, Disposal: {}, Connected: {:?}, Details: {:?}",
+            case_no, year, ndoh_date, ndoh_time, disposal_str, connected, disposal
         );
-        //getting the current year
-        let current_year = Local::now().year();
 
-        let affected = conn.execute(
+        let tx = conn.unchecked_transaction()?;
+        let before = case_by_key(&tx, key)?;
+
+        // Only a final outcome carries a disposal date and reason; an interim
+        // one clears them.
+        let disposal = if disposal_of_case.is_final() {
+            let details = disposal.ok_or(AppError::InvalidValue {
+                field: "disposal_reason_id",
+                value: String::new(),
+            })?;
+            if details.date < before.date || details.date > Local::now().date_naive() {
+                return Err(AppError::InvalidValue {
+                    field: "disposal_date",
+                    value: details.date.to_string(),
+                });
+            }
+            require_active_reason(&tx, details.reason_id)?;
+            Some(details)
+        } else {
+            None
+        };
+
+        let affected = tx.execute(
             "UPDATE case_table
              SET ndoh_date = ?1,
                  ndoh_time = ?2,
                  disposal_of_case = ?3,
-                 connected = COALESCE(?5, connected)
-             WHERE case_no = ?4 AND year = ?6",
+                 connected = COALESCE(?4, connected),
+                 disposal_date = ?5,
+                 disposal_reason_id = ?6,
+                 disposal_remarks = ?7
+             WHERE id = ?8",
             params![
                 ndoh_date.to_string(),
                 ndoh_time.to_string(),
                 disposal_str,
-                case_no,
                 connected.unwrap_or(0),
-                current_year
+                disposal.as_ref().map(|d| d.date.to_string()),
+                disposal.as_ref().map(|d| d.reason_id),
+                disposal.as_ref().and_then(|d| d.remarks.clone()),
+                before.id
             ],
         )?;
 
-        println!("Updated {} row(s) for case_no: {}", affected, case_no);
+        // Each update is one sitting; keep it so earlier dates aren't lost.
+        tx.execute(
+            "INSERT INTO hearings (case_id, hearing_date, hearing_time, mediator_id, outcome, remarks)
+             SELECT id, ?3, ?4, mediator_id, ?5, ?6
+             FROM case_table
+             WHERE case_no = ?1 AND year = ?2",
+            params![
+                case_no,
+                year,
+                ndoh_date.to_string(),
+                ndoh_time.to_string(),
+                disposal_str,
+                remarks
+            ],
+        )?;
+
+        let after = case_by_id(&tx, before.id)?;
+        audit::record(
+            &tx,
+            &operator,
+            "update_case",
+            Some(before.audit_case()),
+            Some(&json!(before)),
+            Some(&json!({ "case": after, "remarks": remarks })),
+        )?;
+
+        tx.commit()?;
+
+        println!("Updated {} row(s) for case_no: {} of {}", affected, case_no, year);
         Ok(affected)
     }
 
-    pub fn get_cases_for_today(&self) -> Result<Vec<Case>> {
-        let conn = self.conn.lock().unwrap();
+    pub fn get_case(&self, key: CaseKey) -> AppResult<Case> {
+        let conn = self.reader()?;
+        case_by_key(&conn, key)
+    }
 
-        let today = NaiveDate::from_ymd_opt(
-            Local::now().year(),
-            Local::now().month(),
-            Local::now().day(),
-        )
-        .expect("Invalid local date")
-        .to_string();
+    pub fn get_case_timeline(&self, key: CaseKey) -> AppResult<CaseTimeline> {
+        let conn = self.reader()?;
+        let CaseKey { case_no, year } = key;
 
-        let mut stmt = conn.prepare(
-            "SELECT id, case_no, nature_of_case,received_from, date, time_slot,
-                    party1, party2, assigned_to, ndoh_date, ndoh_time, disposal_of_case, connected
-             FROM case_table
-             WHERE date = ?1 OR ndoh_date = ?1",
-        )?;
+        let (case_id, registered_on): (i32, NaiveDate) = conn
+            .query_row(
+                "SELECT id, date FROM case_table WHERE case_no = ?1 AND year = ?2",
+                params![case_no, year],
+                |row| Ok((row.get(0)?, parse_date_column(row, 1)?)),
+            )
+            .optional()?
+            .ok_or(key.not_found())?;
 
-        let case_iter = stmt.query_map(params![today], |row| {
-            let nature_str: String = row.get(2)?;
-            let disposal: Option<String> = row.get(11)?;
-            let ndoh_date: Option<String> = row.get(9)?;
-            let ndoh_time: Option<String> = row.get(10)?;
-            let connected: i32 = row.get(12)?;
-
-            Ok(Case {
-                id: row.get(0)?,
-                case_no: row.get(1)?,
-                nature_of_case: nature_str,
-                received_from: row.get(3)?,
-                date: NaiveDate::parse_from_str(&row.get::<_, String>(4)?, "%Y-%m-%d")
-                    .expect("Invalid date"),
-                time_slot: NaiveTime::parse_from_str(&row.get::<_, String>(5)?, "%H:%M:%S")
-                    .expect("Invalid time"),
-                party1: row.get(6)?,
-                party2: row.get(7)?,
-                assigned_to: row.get(8)?,
-                ndoh_date: ndoh_date
-                    .and_then(|s| NaiveDate::parse_from_str(&s, "%Y-%m-%d").ok()),
-                ndoh_time: ndoh_time
-                    .and_then(|s| NaiveTime::parse_from_str(&s, "%H:%M:%S").ok()),
-                disposal_of_case: disposal
-                    .and_then(|s| serde_plain::from_str(&s.to_case(convert_case::Case::Pascal)).ok()),
-                connected,
-            })
-        })?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM hearings h
+             WHERE h.case_id = ?1
+             ORDER BY h.hearing_date, h.hearing_time, h.id",
+            HEARING_COLUMNS
+        ))?;
+
+        let hearings = stmt
+            .query_map([case_id], hearing_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
 
-        Ok(case_iter.collect::<Result<Vec<_>, _>>()?)
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM case_transfers t
+             WHERE t.case_id = ?1
+             ORDER BY t.transferred_on, t.id",
+            CASE_TRANSFER_COLUMNS
+        ))?;
+
+        let transfers = stmt
+            .query_map([case_id], case_transfer_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(CaseTimeline {
+            case_no,
+            year,
+            registered_on,
+            hearings,
+            transfers,
+        })
     }
 
-    pub fn query_cases_filtered(
+    /// Moves a case to another mediator from `transferred_on`, keeping the
+    /// move in `case_transfers` so earlier allocations can still be reported.
+    pub fn reassign_case(
         &self,
-        nature_of_case: Option<String>,
-        assigned_to: Option<String>,
-        start_date: NaiveDate,
-        end_date: NaiveDate,
-    ) -> Result<(Vec<Case>, CaseSummary)> {
-        let conn = self.conn.lock().unwrap();
+        key: CaseKey,
+        to_mediator_id: i32,
+        transferred_on: NaiveDate,
+        reason: &str,
+    ) -> AppResult<CaseTransfer> {
+        let operator = self.operator();
+        let conn = self.writer();
+        let tx = conn.unchecked_transaction()?;
+
+        let before = case_by_key(&tx, key)?;
+        let (case_id, registered_on, from_mediator_id) = (before.id, before.date, before.mediator_id);
+
+        if from_mediator_id == to_mediator_id {
+            return Err(AppError::InvalidValue {
+                field: "to_mediator_id",
+                value: to_mediator_id.to_string(),
+            });
+        }
+
+        require_active_mediator(&tx, to_mediator_id)?;
+
+        if transferred_on < registered_on {
+            return Err(AppError::InvalidDate {
+                field: "transferred_on",
+                value: transferred_on.to_string(),
+            });
+        }
 
         println!(
-            "Querying cases with filters: nature_of_case: {:?}, assigned_to: {:?}, start_date: {}, end_date: {}",
-            nature_of_case, assigned_to, start_date, end_date
+            "Reassigning case_no {} of {} from {} to {} on {}",
+            key.case_no, key.year, from_mediator_id, to_mediator_id, transferred_on
         );
 
-        let mut query = String::from(
-            "SELECT id, case_no, nature_of_case, received_from, date, time_slot, party1, party2, assigned_to, ndoh_date, ndoh_time, disposal_of_case, connected
-            FROM case_table
-            WHERE (date BETWEEN ?1 AND ?2 OR ndoh_date BETWEEN ?1 AND ?2)"
-        );
+        tx.execute(
+            "UPDATE case_table SET mediator_id = ?1 WHERE id = ?2",
+            params![to_mediator_id, case_id],
+        )?;
 
-        let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![
-            Box::new(start_date.to_string()),
-            Box::new(end_date.to_string()),
-        ];
+        tx.execute(
+            "INSERT INTO case_transfers (case_id, from_mediator_id, to_mediator_id, transferred_on, reason)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                case_id,
+                from_mediator_id,
+                to_mediator_id,
+                transferred_on.to_string(),
+                reason
+            ],
+        )?;
 
-        if let Some(ref nature) = nature_of_case {
-            if !nature.is_empty() {
-                query += " AND nature_of_case = ?";
-                params.push(Box::new(nature.clone()));
-            }
-        }
+        let transfer = tx.query_row(
+            &format!("SELECT {} FROM case_transfers t WHERE t.id = ?1", CASE_TRANSFER_COLUMNS),
+            [tx.last_insert_rowid()],
+            case_transfer_from_row,
+        )?;
 
-        if let Some(name) = &assigned_to {
-            query += " AND assigned_to = ?";
-            params.push(Box::new(name.clone()));
-        }
+        let after = case_by_id(&tx, case_id)?;
+        audit::record(
+            &tx,
+            &operator,
+            "reassign_case",
+            Some(before.audit_case()),
+            Some(&json!(before)),
+            Some(&json!({ "case": after, "transfer": transfer })),
+        )?;
 
-        let mut stmt = conn.prepare(&query)?;
-        let params_ref: Vec<&dyn rusqlite::ToSql> = params.iter().map(|b| b.as_ref()).collect();
-
-        let case_iter = stmt.query_map(&params_ref[..], |row| {
-            Ok(Case {
-                id: row.get(0)?,
-                case_no: row.get(1)?,
-                nature_of_case: {
-                    let val: String = row.get(2)?;
-                    serde_plain::from_str(&val).unwrap_or_else(|_| {
-                        println!("Failed to parse nature_of_case: {:?}", val);
-                        Default::default() // or whatever default you prefer
-                    })
-                },
-                received_from: row.get(3)?,
-                date: {
-                    let val: String = row.get(4)?;
-                    NaiveDate::parse_from_str(&val, "%Y-%m-%d").unwrap_or_else(|_| {
-                        println!("Failed to parse date: {:?}", val);
-                        NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()
-                    })
-                },
-                time_slot: {
-                    let val: String = row.get(5)?;
-                    NaiveTime::parse_from_str(&val, "%H:%M").unwrap_or_else(|_| {
-                        println!("Failed to parse time_slot: {:?}", val);
-                        NaiveTime::from_hms_opt(0, 0, 0).unwrap()
-                    })
-                },
-                party1: row.get(6)?,
-                party2: row.get(7)?,
-                assigned_to: row.get(8)?,
-                ndoh_date: {
-                    let val: Option<String> = row.get(9)?;
-                    val.and_then(|s| NaiveDate::parse_from_str(&s, "%Y-%m-%d").ok())
-                },
-                ndoh_time: {
-                    let val: Option<String> = row.get(10)?;
-                    val.and_then(|s| NaiveTime::parse_from_str(&s, "%H:%M").ok())
-                },
-                disposal_of_case: {
-                    let val: Option<String> = row.get(11)?;
-                    val.and_then(|s| serde_plain::from_str(&s).ok())
-                },
-                connected: {
-                    let val: i32 = row.get(12)?;
-                    println!("Row ID: {}, Connected: {:?}", row.get::<_, i32>(0).unwrap_or(-1), val);
-                    val
-                },
+        tx.commit()?;
+        Ok(transfer)
+    }
 
-            })
-        })?;
+    pub fn get_cases_for_today(&self) -> AppResult<Vec<Case>> {
+        let conn = self.reader()?;
 
-        let cases: Vec<Case> = case_iter.collect::<Result<_, _>>()?;
+        let today = Local::now().date_naive().to_string();
 
-        let mut summary = CaseSummary {
-            settled: 0,
-            not_settled: 0,
-            not_fit: 0,
-            pending: 0,
-        };
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM case_table WHERE date = ?1 OR ndoh_date = ?1",
+            CASE_COLUMNS
+        ))?;
 
-        for case in &cases {
-            match case.disposal_of_case {
-                Some(DisposalOfCase::Settled) => summary.settled += 1,
-                Some(DisposalOfCase::NotSettled) => summary.not_settled += 1,
-                Some(DisposalOfCase::NotFitForMediation) => summary.not_fit += 1,
-                Some(DisposalOfCase::Pending) => summary.pending += 1,
-                _ => {}
-            }
-        }
+        let cases = stmt
+            .query_map(params![today], case_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
 
-        Ok((cases, summary))
+        Ok(cases)
     }
+}
 
 
-    pub fn get_assigned_to_list(&self) -> Result<Vec<String>, String> {
-        let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare("SELECT name FROM assigned_to")
-            .map_err(|e| e.to_string())?;
+/// The mediator holding a `case_table` row on the date bound to the single
+/// `?` placeholder: the latest transfer on or before that date, else whoever
+/// the case was first allocated to.
+pub(crate) const MEDIATOR_AS_OF: &str = "COALESCE(
+        (SELECT t.to_mediator_id FROM case_transfers t
+         WHERE t.case_id = case_table.id AND t.transferred_on <= ?
+         ORDER BY t.transferred_on DESC, t.id DESC LIMIT 1),
+        (SELECT t.from_mediator_id FROM case_transfers t
+         WHERE t.case_id = case_table.id
+         ORDER BY t.transferred_on, t.id LIMIT 1),
+        case_table.mediator_id)";
 
-        let rows = stmt.query_map([], |row| row.get::<_, String>(0))
-            .map_err(|e| e.to_string())?;
+/// Column list every `Case` query selects, in the order `case_from_row` reads.
+/// `assigned_to` is the mediator's name as shown on screen and in reports.
+pub(crate) const CASE_COLUMNS: &str = "id, case_no, year, nature_of_case, received_from, date, time_slot, \
+     party1, party2, \
+     (SELECT TRIM(m.honorific || ' ' || m.display_name) FROM mediators m \
+      WHERE m.id = case_table.mediator_id) AS assigned_to, \
+     ndoh_date, ndoh_time, disposal_of_case, connected, mediator_id, \
+     disposal_date, disposal_reason_id, \
+     (SELECT r.label FROM disposal_reasons r WHERE r.id = case_table.disposal_reason_id), \
+     disposal_remarks";
 
-        let names = rows.collect::<Result<Vec<_>, _>>()
-            .map_err(|e| e.to_string())?;
+/// How many columns `CASE_COLUMNS` selects, for queries that read more after them.
+pub(crate) const CASE_COLUMN_COUNT: usize = 19;
 
-        Ok(names)
-    }
+/// Column list for `hearing_from_row`, selected from `hearings h`.
+pub(crate) const HEARING_COLUMNS: &str = "h.id, h.hearing_date, h.hearing_time, h.mediator_id, \
+     (SELECT TRIM(m.honorific || ' ' || m.display_name) FROM mediators m WHERE m.id = h.mediator_id), \
+     h.outcome, h.remarks, h.recorded_at";
 
-    pub fn add_assigned_to(&self, name: String) -> Result<(), String> {
-        let conn = self.conn.lock().unwrap();
-        conn.execute(
-            "INSERT OR IGNORE INTO assigned_to (name) VALUES (?1)",
-            [name],
-        ).map_err(|e| e.to_string())?;
-        Ok(())
-    }
+/// Column list for `case_edit_from_row`.
+pub(crate) const CASE_EDIT_COLUMNS: &str = "id, edited_at, edited_by, reason, changes";
+
+/// Column list for `case_transfer_from_row`, selected from `case_transfers t`.
+pub(crate) const CASE_TRANSFER_COLUMNS: &str = "t.id, t.from_mediator_id, \
+     (SELECT TRIM(m.honorific || ' ' || m.display_name) FROM mediators m WHERE m.id = t.from_mediator_id), \
+     t.to_mediator_id, \
+     (SELECT TRIM(m.honorific || ' ' || m.display_name) FROM mediators m WHERE m.id = t.to_mediator_id), \
+     t.transferred_on, t.reason, t.recorded_at";
+
+pub(crate) fn case_by_id(conn: &Connection, id: i32) -> rusqlite::Result<Case> {
+    conn.query_row(
+        &format!("SELECT {} FROM case_table WHERE id = ?1", CASE_COLUMNS),
+        [id],
+        case_from_row,
+    )
+}
 
-    pub fn delete_assigned_to(&self, name: String) -> Result<(), String> {
-        let conn = self.conn.lock().unwrap();
-        conn.execute(
-            "DELETE FROM assigned_to WHERE name = ?1",
-            [name],
-        ).map_err(|e| e.to_string())?;
-        Ok(())
+fn case_by_key(conn: &Connection, key: CaseKey) -> AppResult<Case> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM case_table WHERE case_no = ?1 AND year = ?2",
+            CASE_COLUMNS
+        ),
+        params![key.case_no, key.year],
+        case_from_row,
+    )
+    .optional()?
+    .ok_or(key.not_found())
+}
+
+pub(crate) fn case_from_row(row: &rusqlite::Row) -> rusqlite::Result<Case> {
+    Ok(Case {
+        id: row.get(0)?,
+        case_no: row.get(1)?,
+        year: row.get(2)?,
+        nature_of_case: row.get(3)?,
+        received_from: row.get(4)?,
+        date: parse_date_column(row, 5)?,
+        time_slot: parse_time_column(row, 6)?,
+        party1: row.get(7)?,
+        party2: row.get(8)?,
+        assigned_to: row.get(9)?,
+        ndoh_date: parse_optional_date_column(row, 10)?,
+        ndoh_time: parse_optional_time_column(row, 11)?,
+        disposal_of_case: row.get(12)?,
+        connected: row.get::<_, Option<i32>>(13)?.unwrap_or(0),
+        mediator_id: row.get(14)?,
+        disposal_date: parse_optional_date_column(row, 15)?,
+        disposal_reason_id: row.get(16)?,
+        disposal_reason: row.get(17)?,
+        disposal_remarks: row.get(18)?,
+    })
+}
+
+pub(crate) fn hearing_from_row(row: &rusqlite::Row) -> rusqlite::Result<Hearing> {
+    Ok(Hearing {
+        id: row.get(0)?,
+        hearing_date: parse_date_column(row, 1)?,
+        hearing_time: parse_optional_time_column(row, 2)?,
+        mediator_id: row.get(3)?,
+        mediator: row.get(4)?,
+        outcome: row.get(5)?,
+        remarks: row.get(6)?,
+        recorded_at: row.get(7)?,
+    })
+}
+
+pub(crate) fn case_edit_from_row(row: &rusqlite::Row) -> rusqlite::Result<CaseEdit> {
+    let changes: String = row.get(4)?;
+    Ok(CaseEdit {
+        id: row.get(0)?,
+        edited_at: row.get(1)?,
+        edited_by: row.get(2)?,
+        reason: row.get(3)?,
+        changes: serde_json::from_str(&changes).map_err(|_| bad_stored_value(row, 4, &changes))?,
+    })
+}
+
+pub(crate) fn case_transfer_from_row(row: &rusqlite::Row) -> rusqlite::Result<CaseTransfer> {
+    Ok(CaseTransfer {
+        id: row.get(0)?,
+        from_mediator_id: row.get(1)?,
+        from_mediator: row.get(2)?,
+        to_mediator_id: row.get(3)?,
+        to_mediator: row.get(4)?,
+        transferred_on: parse_date_column(row, 5)?,
+        reason: row.get(6)?,
+        recorded_at: row.get(7)?,
+    })
+}
+
+pub(crate) fn bad_stored_value(row: &rusqlite::Row, idx: usize, value: &str) -> rusqlite::Error {
+    let column = row.as_ref().column_name(idx).unwrap_or("?").to_string();
+    rusqlite::Error::FromSqlConversionFailure(
+        idx,
+        rusqlite::types::Type::Text,
+        Box::new(BadStoredValue {
+            column,
+            value: value.to_string(),
+        }),
+    )
+}
+
+pub(crate) fn parse_date_column(row: &rusqlite::Row, idx: usize) -> rusqlite::Result<NaiveDate> {
+    let val: String = row.get(idx)?;
+    NaiveDate::parse_from_str(&val, "%Y-%m-%d").map_err(|_| bad_stored_value(row, idx, &val))
+}
+
+pub(crate) fn parse_optional_date_column(row: &rusqlite::Row, idx: usize) -> rusqlite::Result<Option<NaiveDate>> {
+    match row.get::<_, Option<String>>(idx)? {
+        Some(_) => parse_date_column(row, idx).map(Some),
+        None => Ok(None),
     }
 }
 
+// Times have been written both as "HH:MM:SS" (NaiveTime::to_string) and "HH:MM".
+fn parse_time_column(row: &rusqlite::Row, idx: usize) -> rusqlite::Result<NaiveTime> {
+    let val: String = row.get(idx)?;
+    NaiveTime::parse_from_str(&val, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(&val, "%H:%M"))
+        .map_err(|_| bad_stored_value(row, idx, &val))
+}
 
-#[derive(Debug, Serialize, Deserialize)]
+pub(crate) fn parse_optional_time_column(row: &rusqlite::Row, idx: usize) -> rusqlite::Result<Option<NaiveTime>> {
+    match row.get::<_, Option<String>>(idx)? {
+        Some(_) => parse_time_column(row, idx).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Stored in `case_table.disposal_of_case` and `hearings.outcome` by its
+/// code. Final outcomes end the case's time in mediation; interim ones are
+/// recorded at a sitting that is adjourned to the next date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub enum DisposalOfCase {
     Settled,
+    PartiallySettled,
     NotSettled,
     NotFitForMediation,
+    Withdrawn,
+    ReturnedUnserved,
+    ReferredToLokAdalat,
+    NonAppearance,
     Pending
 }
 
-#[derive(Debug, Serialize)]
-pub struct Case {
-    id: i32,
-    case_no: i32,
-    nature_of_case: String,
-    received_from: String,
-    date: NaiveDate,
-    time_slot: NaiveTime,
-    party1: String,
-    party2: String,
-    assigned_to: String,
-    ndoh_date: Option<NaiveDate>,
-    ndoh_time: Option<NaiveTime>,
-    disposal_of_case: Option<DisposalOfCase>,
-    connected: i32,
+impl DisposalOfCase {
+    pub const ALL: [DisposalOfCase; 9] = [
+        DisposalOfCase::Settled,
+        DisposalOfCase::PartiallySettled,
+        DisposalOfCase::NotSettled,
+        DisposalOfCase::NotFitForMediation,
+        DisposalOfCase::Withdrawn,
+        DisposalOfCase::ReturnedUnserved,
+        DisposalOfCase::ReferredToLokAdalat,
+        DisposalOfCase::NonAppearance,
+        DisposalOfCase::Pending,
+    ];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            DisposalOfCase::Settled => "Settled",
+            DisposalOfCase::PartiallySettled => "PartiallySettled",
+            DisposalOfCase::NotSettled => "NotSettled",
+            DisposalOfCase::NotFitForMediation => "NotFitForMediation",
+            DisposalOfCase::Withdrawn => "Withdrawn",
+            DisposalOfCase::ReturnedUnserved => "ReturnedUnserved",
+            DisposalOfCase::ReferredToLokAdalat => "ReferredToLokAdalat",
+            DisposalOfCase::NonAppearance => "NonAppearance",
+            DisposalOfCase::Pending => "Pending",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DisposalOfCase::Settled => "Settled",
+            DisposalOfCase::PartiallySettled => "Partially Settled",
+            DisposalOfCase::NotSettled => "Not Settled",
+            DisposalOfCase::NotFitForMediation => "Not Fit for Mediation",
+            DisposalOfCase::Withdrawn => "Withdrawn",
+            DisposalOfCase::ReturnedUnserved => "Returned Unserved",
+            DisposalOfCase::ReferredToLokAdalat => "Referred to Lok Adalat",
+            DisposalOfCase::NonAppearance => "Non-appearance of Parties",
+            DisposalOfCase::Pending => "Pending",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|d| d.code() == code)
+    }
+
+    /// Whether the case leaves mediation with this outcome. `Pending` and
+    /// `NonAppearance` are recorded at a sitting that is adjourned.
+    pub fn is_final(self) -> bool {
+        !matches!(self, DisposalOfCase::Pending | DisposalOfCase::NonAppearance)
+    }
+
+    /// Tolerant lookup for outcomes written by older versions or found in
+    /// imported registers: ignores case, spacing and punctuation, and accepts
+    /// the code, the label or an old clerk's shorthand such as "Not Fit" or
+    /// "Adjourned".
+    pub fn from_legacy(text: &str) -> Option<Self> {
+        const ALIASES: &[(&str, DisposalOfCase)] = &[
+            ("Not Fit", DisposalOfCase::NotFitForMediation),
+            ("Part Settled", DisposalOfCase::PartiallySettled),
+            ("Unserved", DisposalOfCase::ReturnedUnserved),
+            ("Lok Adalat", DisposalOfCase::ReferredToLokAdalat),
+            ("Non Appearance", DisposalOfCase::NonAppearance),
+            ("Adjourned", DisposalOfCase::Pending),
+        ];
+        let key = normalise_legacy_key(text);
+        Self::ALL
+            .into_iter()
+            .find(|d| normalise_legacy_key(d.code()) == key || normalise_legacy_key(d.label()) == key)
+            .or_else(|| {
+                ALIASES
+                    .iter()
+                    .find(|(alias, _)| normalise_legacy_key(alias) == key)
+                    .map(|(_, d)| *d)
+            })
+    }
+
+    /// The codes with the given `is_final`, quoted for an SQL `IN (...)`.
+    /// Codes are fixed identifiers, so nothing here needs escaping.
+    pub(crate) fn sql_codes(is_final: bool) -> String {
+        Self::ALL
+            .into_iter()
+            .filter(|d| d.is_final() == is_final)
+            .map(|d| format!("'{}'", d.code()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+impl FromSql for DisposalOfCase {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let code = value.as_str()?;
+        DisposalOfCase::from_code(code).ok_or_else(|| {
+            FromSqlError::Other(Box::new(BadStoredValue {
+                column: "disposal_of_case".to_string(),
+                value: code.to_string(),
+            }))
+        })
+    }
+}
+
+/// Stored in `case_table.nature_of_case` by its code, which is also the
+/// serde name. Codes must never change once shipped; labels are free to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NatureOfCase {
+    CivilRecovery,
+    CivilPartition,
+    CivilInjunction,
+    CivilPossession,
+    CivilProbate,
+    Arbitration,
+    CivilAppeal,
+    CivilExecution,
+    OtherCivilSuit,
+    CriminalMatter,
+    CriminalRevision,
+    CriminalAppeal,
+    PetitionForDivorce,
+    PetitionForMaintenance,
+    PetitionForCustody,
+    PetitionForDomesticViolenceAct,
+    CawCellN,
+    CawCellOD,
+    PetitionForRecoveryOfRent,
+    PetitionUs138OfNiActAndPasaAct,
+    PetitionUnderElectricityAct,
+    MactCase,
+}
+
+impl NatureOfCase {
+    pub const ALL: [NatureOfCase; 22] = [
+        NatureOfCase::CivilRecovery,
+        NatureOfCase::CivilPartition,
+        NatureOfCase::CivilInjunction,
+        NatureOfCase::CivilPossession,
+        NatureOfCase::CivilProbate,
+        NatureOfCase::Arbitration,
+        NatureOfCase::CivilAppeal,
+        NatureOfCase::CivilExecution,
+        NatureOfCase::OtherCivilSuit,
+        NatureOfCase::CriminalMatter,
+        NatureOfCase::CriminalRevision,
+        NatureOfCase::CriminalAppeal,
+        NatureOfCase::PetitionForDivorce,
+        NatureOfCase::PetitionForMaintenance,
+        NatureOfCase::PetitionForCustody,
+        NatureOfCase::PetitionForDomesticViolenceAct,
+        NatureOfCase::CawCellN,
+        NatureOfCase::CawCellOD,
+        NatureOfCase::PetitionForRecoveryOfRent,
+        NatureOfCase::PetitionUs138OfNiActAndPasaAct,
+        NatureOfCase::PetitionUnderElectricityAct,
+        NatureOfCase::MactCase,
+    ];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            NatureOfCase::CivilRecovery => "CivilRecovery",
+            NatureOfCase::CivilPartition => "CivilPartition",
+            NatureOfCase::CivilInjunction => "CivilInjunction",
+            NatureOfCase::CivilPossession => "CivilPossession",
+            NatureOfCase::CivilProbate => "CivilProbate",
+            NatureOfCase::Arbitration => "Arbitration",
+            NatureOfCase::CivilAppeal => "CivilAppeal",
+            NatureOfCase::CivilExecution => "CivilExecution",
+            NatureOfCase::OtherCivilSuit => "OtherCivilSuit",
+            NatureOfCase::CriminalMatter => "CriminalMatter",
+            NatureOfCase::CriminalRevision => "CriminalRevision",
+            NatureOfCase::CriminalAppeal => "CriminalAppeal",
+            NatureOfCase::PetitionForDivorce => "PetitionForDivorce",
+            NatureOfCase::PetitionForMaintenance => "PetitionForMaintenance",
+            NatureOfCase::PetitionForCustody => "PetitionForCustody",
+            NatureOfCase::PetitionForDomesticViolenceAct => "PetitionForDomesticViolenceAct",
+            NatureOfCase::CawCellN => "CawCellN",
+            NatureOfCase::CawCellOD => "CawCellOD",
+            NatureOfCase::PetitionForRecoveryOfRent => "PetitionForRecoveryOfRent",
+            NatureOfCase::PetitionUs138OfNiActAndPasaAct => "PetitionUs138OfNiActAndPasaAct",
+            NatureOfCase::PetitionUnderElectricityAct => "PetitionUnderElectricityAct",
+            NatureOfCase::MactCase => "MactCase",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            NatureOfCase::CivilRecovery => "Civil Recovery",
+            NatureOfCase::CivilPartition => "Civil Partition",
+            NatureOfCase::CivilInjunction => "Civil Injunction",
+            NatureOfCase::CivilPossession => "Civil Possession",
+            NatureOfCase::CivilProbate => "Civil Probate",
+            NatureOfCase::Arbitration => "Arbitration",
+            NatureOfCase::CivilAppeal => "Civil Appeal",
+            NatureOfCase::CivilExecution => "Civil Execution",
+            NatureOfCase::OtherCivilSuit => "Other Civil Suit",
+            NatureOfCase::CriminalMatter => "Criminal Matter",
+            NatureOfCase::CriminalRevision => "Criminal Revision",
+            NatureOfCase::CriminalAppeal => "Criminal Appeal",
+            NatureOfCase::PetitionForDivorce => "Petition For Divorce",
+            NatureOfCase::PetitionForMaintenance => "Petition For Maintenance",
+            NatureOfCase::PetitionForCustody => "Petition For Custody",
+            NatureOfCase::PetitionForDomesticViolenceAct => "Petition For Domestic Violence Act",
+            NatureOfCase::CawCellN => "CAW Cell (N)",
+            NatureOfCase::CawCellOD => "CAW Cell (OD)",
+            NatureOfCase::PetitionForRecoveryOfRent => "Petition For Recovery Of Rent",
+            NatureOfCase::PetitionUs138OfNiActAndPasaAct => "Petition U/s 138 of NI Act & PASA Act",
+            NatureOfCase::PetitionUnderElectricityAct => "Petition Under Electricity Act",
+            NatureOfCase::MactCase => "MACT Case",
+        }
+    }
+
+    /// Spellings that were stored before the enum existed and that neither
+    /// the code nor the label normalises to.
+    fn legacy_aliases(self) -> &'static [&'static str] {
+        match self {
+            NatureOfCase::PetitionForDomesticViolenceAct => &["Petition For Domestic Voilence Act"],
+            NatureOfCase::PetitionUs138OfNiActAndPasaAct => &["PetitionUs138OfNiActNPasaAct"],
+            _ => &[],
+        }
+    }
+
+    /// Strict lookup by storage code, used for anything the UI sends.
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|n| n.code() == code)
+    }
+
+    /// Tolerant lookup for free text written by older versions: ignores
+    /// case, spacing and punctuation, and accepts the code, the label or a
+    /// known legacy spelling.
+    pub fn from_legacy(text: &str) -> Option<Self> {
+        let key = normalise_legacy_key(text);
+        Self::ALL.into_iter().find(|n| {
+            normalise_legacy_key(n.code()) == key
+                || normalise_legacy_key(n.label()) == key
+                || n.legacy_aliases().iter().any(|a| normalise_legacy_key(a) == key)
+        })
+    }
+}
+
+fn normalise_legacy_key(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+impl ToSql for NatureOfCase {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.code()))
+    }
+}
+
+impl FromSql for NatureOfCase {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let code = value.as_str()?;
+        NatureOfCase::from_code(code).ok_or_else(|| {
+            FromSqlError::Other(Box::new(BadStoredValue {
+                column: "nature_of_case".to_string(),
+                value: code.to_string(),
+            }))
+        })
+    }
 }
 
 #[derive(Serialize)]
-pub struct CaseSummary {
-    pub settled: usize,
-    pub not_settled: usize,
-    pub not_fit: usize,
-    pub pending: usize,
+pub struct NatureOfCaseOption {
+    pub code: &'static str,
+    pub label: &'static str,
 }
 
 #[derive(Serialize)]
-pub struct CaseQueryResult {
-    pub cases: Vec<Case>,
-    pub summary: CaseSummary,
+pub struct DisposalOption {
+    pub code: &'static str,
+    pub label: &'static str,
+    pub is_final: bool,
+}
+
+/// A case is identified by its number *and* year; numbers restart every year.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CaseKey {
+    pub case_no: i32,
+    pub year: i32,
+}
+
+impl CaseKey {
+    pub fn not_found(self) -> AppError {
+        AppError::CaseNotFound {
+            case_no: self.case_no,
+            year: self.year,
+        }
+    }
+}
+
+/// One sitting's outcome as entered on the update form.
+#[derive(Debug, Clone)]
+pub struct CaseUpdate {
+    pub ndoh_date: NaiveDate,
+    pub ndoh_time: NaiveTime,
+    pub disposal_of_case: DisposalOfCase,
+    pub connected: Option<i32>,
+    /// Notes on this sitting, kept with its hearing.
+    pub remarks: Option<String>,
+    /// Required when `disposal_of_case` is final, ignored otherwise.
+    pub disposal: Option<DisposalDetails>,
+}
+
+/// The details captured when a case is registered, and the set that
+/// `edit_case` may correct afterwards.
+#[derive(Debug, Clone)]
+pub struct CaseIntake {
+    pub case_no: i32,
+    pub year: i32,
+    pub nature_of_case: NatureOfCase,
+    pub received_from: String,
+    pub time_slot: NaiveTime,
+    pub party1: String,
+    pub party2: String,
+    pub mediator_id: i32,
+}
+
+/// `assigned_to` and `disposal_reason` are looked up from `mediator_id` and
+/// `disposal_reason_id` when read, and ignored when a case is restored.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Case {
+    pub(crate) id: i32,
+    pub(crate) case_no: i32,
+    pub(crate) year: i32,
+    pub(crate) nature_of_case: NatureOfCase,
+    pub(crate) received_from: String,
+    pub(crate) date: NaiveDate,
+    pub(crate) time_slot: NaiveTime,
+    pub(crate) party1: String,
+    pub(crate) party2: String,
+    pub(crate) mediator_id: i32,
+    #[serde(default)]
+    pub(crate) assigned_to: String,
+    pub(crate) ndoh_date: Option<NaiveDate>,
+    pub(crate) ndoh_time: Option<NaiveTime>,
+    pub(crate) disposal_of_case: Option<DisposalOfCase>,
+    pub(crate) connected: i32,
+    pub(crate) disposal_date: Option<NaiveDate>,
+    pub(crate) disposal_reason_id: Option<i32>,
+    pub(crate) disposal_reason: Option<String>,
+    pub(crate) disposal_remarks: Option<String>,
+}
+
+impl Case {
+    pub(crate) fn audit_case(&self) -> AuditCase {
+        AuditCase {
+            id: self.id,
+            key: CaseKey {
+                case_no: self.case_no,
+                year: self.year,
+            },
+        }
+    }
+
+    /// `{ field: { "from": old, "to": new } }` for every intake field that
+    /// differs between this case and `corrected`.
+    fn intake_changes(&self, corrected: &CaseIntake) -> serde_json::Map<String, serde_json::Value> {
+        let pairs = [
+            ("case_no", json!(self.case_no), json!(corrected.case_no)),
+            ("year", json!(self.year), json!(corrected.year)),
+            ("nature_of_case", json!(self.nature_of_case), json!(corrected.nature_of_case)),
+            ("received_from", json!(self.received_from), json!(corrected.received_from)),
+            ("time_slot", json!(self.time_slot), json!(corrected.time_slot)),
+            ("party1", json!(self.party1), json!(corrected.party1)),
+            ("party2", json!(self.party2), json!(corrected.party2)),
+            ("mediator_id", json!(self.mediator_id), json!(corrected.mediator_id)),
+        ];
+
+        pairs
+            .into_iter()
+            .filter(|(_, from, to)| from != to)
+            .map(|(field, from, to)| (field.to_string(), json!({ "from": from, "to": to })))
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CaseEdit {
+    pub id: i32,
+    pub edited_at: String,
+    pub edited_by: String,
+    pub reason: String,
+    pub changes: serde_json::Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Hearing {
+    pub id: i32,
+    pub hearing_date: NaiveDate,
+    pub hearing_time: Option<NaiveTime>,
+    pub mediator_id: i32,
+    #[serde(default)]
+    pub mediator: String,
+    pub outcome: Option<DisposalOfCase>,
+    pub remarks: Option<String>,
+    pub recorded_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CaseTimeline {
+    pub case_no: i32,
+    pub year: i32,
+    pub registered_on: NaiveDate,
+    pub hearings: Vec<Hearing>,
+    pub transfers: Vec<CaseTransfer>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CaseTransfer {
+    pub id: i32,
+    pub from_mediator_id: i32,
+    #[serde(default)]
+    pub from_mediator: String,
+    pub to_mediator_id: i32,
+    #[serde(default)]
+    pub to_mediator: String,
+    pub transferred_on: NaiveDate,
+    pub reason: String,
+    pub recorded_at: String,
 }
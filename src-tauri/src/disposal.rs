@@ -0,0 +1,113 @@
+use chrono::NaiveDate;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::audit;
+use crate::database_init::Db;
+use crate::error::{AppError, AppResult};
+
+pub(crate) const DISPOSAL_REASON_COLUMNS: &str = "id, label, active, created_at";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DisposalReason {
+    pub id: i32,
+    pub label: String,
+    pub active: bool,
+    pub created_at: String,
+}
+
+/// What is recorded with a final disposal. `update_case_details` requires it
+/// whenever the outcome is one where `DisposalOfCase::is_final` holds.
+#[derive(Debug, Clone)]
+pub struct DisposalDetails {
+    pub date: NaiveDate,
+    pub reason_id: i32,
+    pub remarks: Option<String>,
+}
+
+impl Db {
+    /// Active reasons for the disposal form, or every reason when
+    /// `include_inactive` is set so old disposals can still be shown.
+    pub fn get_disposal_reasons(&self, include_inactive: bool) -> AppResult<Vec<DisposalReason>> {
+        let conn = self.reader()?;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM disposal_reasons
+             WHERE active = 1 OR ?1
+             ORDER BY active DESC, label",
+            DISPOSAL_REASON_COLUMNS
+        ))?;
+
+        let reasons = stmt
+            .query_map([include_inactive], disposal_reason_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(reasons)
+    }
+
+    pub fn add_disposal_reason(&self, label: &str) -> AppResult<DisposalReason> {
+        let operator = self.operator();
+        let conn = self.writer();
+        let tx = conn.unchecked_transaction()?;
+
+        tx.execute("INSERT INTO disposal_reasons (label) VALUES (?1)", [label])
+            .map_err(|e| AppError::on_duplicate_disposal_reason(e, label))?;
+
+        let added = find_disposal_reason(&tx, tx.last_insert_rowid() as i32)?;
+        audit::record(&tx, &operator, "add_disposal_reason", None, None, Some(&json!(added)))?;
+        tx.commit()?;
+
+        println!("Added disposal reason: {}", label);
+        Ok(added)
+    }
+
+    /// Retires or reinstates a reason. Cases already disposed of with a
+    /// retired reason keep it; it only stops being offered.
+    pub fn set_disposal_reason_active(&self, id: i32, active: bool) -> AppResult<DisposalReason> {
+        let operator = self.operator();
+        let conn = self.writer();
+        let tx = conn.unchecked_transaction()?;
+        let before = find_disposal_reason(&tx, id)?;
+
+        tx.execute(
+            "UPDATE disposal_reasons SET active = ?1 WHERE id = ?2",
+            params![active, id],
+        )?;
+
+        let after = find_disposal_reason(&tx, id)?;
+        audit::record(&tx, &operator, "set_disposal_reason_active", None, Some(&json!(before)), Some(&json!(after)))?;
+        tx.commit()?;
+
+        println!("Disposal reason {} set active = {}", id, active);
+        Ok(after)
+    }
+}
+
+fn find_disposal_reason(conn: &Connection, id: i32) -> AppResult<DisposalReason> {
+    conn.query_row(
+        &format!("SELECT {} FROM disposal_reasons WHERE id = ?1", DISPOSAL_REASON_COLUMNS),
+        [id],
+        disposal_reason_from_row,
+    )
+    .optional()?
+    .ok_or(AppError::DisposalReasonNotFound { id })
+}
+
+/// Fails unless `id` names a reason that can be given for a new disposal.
+pub(crate) fn require_active_reason(conn: &Connection, id: i32) -> AppResult<()> {
+    let reason = find_disposal_reason(conn, id)?;
+    if !reason.active {
+        return Err(AppError::DisposalReasonInactive { label: reason.label });
+    }
+    Ok(())
+}
+
+pub(crate) fn disposal_reason_from_row(row: &rusqlite::Row) -> rusqlite::Result<DisposalReason> {
+    Ok(DisposalReason {
+        id: row.get(0)?,
+        label: row.get(1)?,
+        active: row.get(2)?,
+        created_at: row.get(3)?,
+    })
+}
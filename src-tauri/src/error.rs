@@ -0,0 +1,263 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::fmt;
+
+pub type AppResult<T> = Result<T, AppError>;
+
+/// Every command returns this on failure. It reaches the frontend as
+/// `{ code, message, field }` so the UI can branch on `code` and show
+/// `message` as-is.
+#[derive(Debug)]
+pub enum AppError {
+    DbNotInitialised,
+    SchemaTooNew { found: i32, supported: i32 },
+    Migration { version: i32, source: rusqlite::Error },
+    DuplicateCase { case_no: i32, year: i32 },
+    CaseNotFound { case_no: i32, year: i32 },
+    DuplicateMediator { name: String },
+    MediatorNotFound { id: i32 },
+    MediatorInactive { name: String },
+    DuplicateDisposalReason { label: String },
+    DisposalReasonNotFound { id: i32 },
+    DisposalReasonInactive { label: String },
+    InvalidRoster { path: String, detail: String },
+    InvalidBackup { path: String, detail: String },
+    BackupFolderMissing { path: String },
+    ReportFailed { path: String, detail: String },
+    ExportFailed { path: String, detail: String },
+    InvalidImport { path: String, detail: String },
+    ImportProfileNotFound { id: i32 },
+    InvalidArchive { path: String, detail: String },
+    RegisterNotEmpty,
+    InvalidDate { field: &'static str, value: String },
+    InvalidTime { field: &'static str, value: String },
+    InvalidValue { field: &'static str, value: String },
+    CorruptData { field: Option<String>, detail: String },
+    TaskFailed { detail: String },
+    Io(std::io::Error),
+    Database(rusqlite::Error),
+}
+
+impl AppError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::DbNotInitialised => "DB_NOT_INITIALISED",
+            AppError::SchemaTooNew { .. } => "SCHEMA_TOO_NEW",
+            AppError::Migration { .. } => "MIGRATION_FAILED",
+            AppError::DuplicateCase { .. } => "DUPLICATE_CASE",
+            AppError::CaseNotFound { .. } => "CASE_NOT_FOUND",
+            AppError::DuplicateMediator { .. } => "DUPLICATE_MEDIATOR",
+            AppError::MediatorNotFound { .. } => "MEDIATOR_NOT_FOUND",
+            AppError::MediatorInactive { .. } => "MEDIATOR_INACTIVE",
+            AppError::DuplicateDisposalReason { .. } => "DUPLICATE_DISPOSAL_REASON",
+            AppError::DisposalReasonNotFound { .. } => "DISPOSAL_REASON_NOT_FOUND",
+            AppError::DisposalReasonInactive { .. } => "DISPOSAL_REASON_INACTIVE",
+            AppError::InvalidRoster { .. } => "INVALID_ROSTER",
+            AppError::InvalidBackup { .. } => "INVALID_BACKUP",
+            AppError::BackupFolderMissing { .. } => "BACKUP_FOLDER_MISSING",
+            AppError::ReportFailed { .. } => "REPORT_FAILED",
+            AppError::ExportFailed { .. } => "EXPORT_FAILED",
+            AppError::InvalidImport { .. } => "INVALID_IMPORT",
+            AppError::ImportProfileNotFound { .. } => "IMPORT_PROFILE_NOT_FOUND",
+            AppError::InvalidArchive { .. } => "INVALID_ARCHIVE",
+            AppError::RegisterNotEmpty => "REGISTER_NOT_EMPTY",
+            AppError::InvalidDate { .. } => "INVALID_DATE",
+            AppError::InvalidTime { .. } => "INVALID_TIME",
+            AppError::InvalidValue { .. } => "INVALID_VALUE",
+            AppError::CorruptData { .. } => "CORRUPT_DATA",
+            AppError::TaskFailed { .. } => "TASK_FAILED",
+            AppError::Io(_) => "IO_ERROR",
+            AppError::Database(_) => "DATABASE_ERROR",
+        }
+    }
+
+    pub fn field(&self) -> Option<&str> {
+        match self {
+            AppError::DuplicateCase { .. } | AppError::CaseNotFound { .. } => Some("case_no"),
+            AppError::DuplicateMediator { .. } => Some("display_name"),
+            AppError::MediatorNotFound { .. } | AppError::MediatorInactive { .. } => Some("mediator_id"),
+            AppError::DuplicateDisposalReason { .. } => Some("label"),
+            AppError::DisposalReasonNotFound { .. } | AppError::DisposalReasonInactive { .. } => {
+                Some("disposal_reason_id")
+            }
+            AppError::InvalidDate { field, .. }
+            | AppError::InvalidTime { field, .. }
+            | AppError::InvalidValue { field, .. } => Some(field),
+            AppError::CorruptData { field, .. } => field.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Maps a UNIQUE (case_no, year) violation to `DuplicateCase`, leaving
+    /// every other error untouched.
+    pub fn on_duplicate_case(e: rusqlite::Error, case_no: i32, year: i32) -> Self {
+        if is_unique_violation(&e) {
+            AppError::DuplicateCase { case_no, year }
+        } else {
+            e.into()
+        }
+    }
+
+    /// Same as `on_duplicate_case`, for the UNIQUE mediator display name.
+    pub fn on_duplicate_mediator(e: rusqlite::Error, name: &str) -> Self {
+        if is_unique_violation(&e) {
+            AppError::DuplicateMediator { name: name.to_string() }
+        } else {
+            e.into()
+        }
+    }
+
+    /// Same as `on_duplicate_case`, for the UNIQUE disposal reason label.
+    pub fn on_duplicate_disposal_reason(e: rusqlite::Error, label: &str) -> Self {
+        if is_unique_violation(&e) {
+            AppError::DuplicateDisposalReason { label: label.to_string() }
+        } else {
+            e.into()
+        }
+    }
+}
+
+fn is_unique_violation(e: &rusqlite::Error) -> bool {
+    matches!(
+        e,
+        rusqlite::Error::SqliteFailure(err, _)
+            if err.extended_code == rusqlite::ffi::SQLITE_CONSTRAINT_UNIQUE
+    )
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::DbNotInitialised => write!(f, "The case register has not been opened yet."),
+            AppError::SchemaTooNew { found, supported } => write!(
+                f,
+                "This register was written by a newer version of the app (schema {}, this app supports {}). Please update the app.",
+                found, supported
+            ),
+            AppError::Migration { version, source } => {
+                write!(f, "Upgrading the register to schema {} failed: {}", version, source)
+            }
+            AppError::DuplicateCase { case_no, year } => {
+                write!(f, "Case {} of {} is already registered.", case_no, year)
+            }
+            AppError::CaseNotFound { case_no, year } => {
+                write!(f, "No case found with case no. {} of {}.", case_no, year)
+            }
+            AppError::DuplicateMediator { name } => {
+                write!(f, "A mediator named {} is already on the register.", name)
+            }
+            AppError::MediatorNotFound { id } => write!(f, "No mediator found with id {}.", id),
+            AppError::MediatorInactive { name } => {
+                write!(f, "{} is no longer active and cannot be given new cases.", name)
+            }
+            AppError::DuplicateDisposalReason { label } => {
+                write!(f, "{:?} is already on the list of disposal reasons.", label)
+            }
+            AppError::DisposalReasonNotFound { id } => write!(f, "No disposal reason found with id {}.", id),
+            AppError::DisposalReasonInactive { label } => {
+                write!(f, "{:?} has been retired and cannot be used for new disposals.", label)
+            }
+            AppError::InvalidRoster { path, detail } => {
+                write!(f, "The mediator roster {} could not be read: {}", path, detail)
+            }
+            AppError::InvalidBackup { path, detail } => {
+                write!(f, "{} cannot be restored: {}", path, detail)
+            }
+            AppError::BackupFolderMissing { path } => write!(
+                f,
+                "The backup folder {} is not there. Connect the drive it is on, or choose another folder in the backup schedule.",
+                path
+            ),
+            AppError::ReportFailed { path, detail } => {
+                write!(f, "The report {} could not be written: {}", path, detail)
+            }
+            AppError::ExportFailed { path, detail } => {
+                write!(f, "The export {} could not be written: {}", path, detail)
+            }
+            AppError::InvalidImport { path, detail } => {
+                write!(f, "{} cannot be imported: {}", path, detail)
+            }
+            AppError::ImportProfileNotFound { id } => write!(f, "No import profile found with id {}.", id),
+            AppError::InvalidArchive { path, detail } => {
+                write!(f, "The archive {} cannot be imported: {}", path, detail)
+            }
+            AppError::RegisterNotEmpty => write!(
+                f,
+                "The open register already has cases, mediators or history in it. Open a new, empty register to import an archive into, with \"Leave it empty\" ticked if a mediators.toml or mediators.json sits next to it."
+            ),
+            AppError::InvalidDate { field, value } => {
+                write!(f, "Invalid date for {}: {:?} (expected YYYY-MM-DD).", field, value)
+            }
+            AppError::InvalidTime { field, value } => {
+                write!(f, "Invalid time for {}: {:?} (expected HH:MM).", field, value)
+            }
+            AppError::InvalidValue { field, value } => {
+                write!(f, "Invalid value for {}: {:?}.", field, value)
+            }
+            AppError::CorruptData { detail, .. } => write!(f, "Stored data could not be read: {}", detail),
+            AppError::TaskFailed { detail } => {
+                write!(f, "The operation stopped before it finished: {}", detail)
+            }
+            AppError::Io(e) => write!(f, "File error: {}", e),
+            AppError::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("field", &self.field())?;
+        state.end()
+    }
+}
+
+/// A stored value that doesn't parse into its Rust type. Row mappers box it
+/// into `FromSqlConversionFailure` so the column name survives to the UI.
+#[derive(Debug)]
+pub struct BadStoredValue {
+    pub column: String,
+    pub value: String,
+}
+
+impl fmt::Display for BadStoredValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unexpected value {:?} in column {}", self.value, self.column)
+    }
+}
+
+impl std::error::Error for BadStoredValue {}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(e: rusqlite::Error) -> Self {
+        match e {
+            rusqlite::Error::FromSqlConversionFailure(_, _, ref source) => {
+                match source.downcast_ref::<BadStoredValue>() {
+                    Some(bad) => AppError::CorruptData {
+                        field: Some(bad.column.clone()),
+                        detail: bad.to_string(),
+                    },
+                    None => AppError::CorruptData {
+                        field: None,
+                        detail: e.to_string(),
+                    },
+                }
+            }
+            rusqlite::Error::InvalidColumnType(_, ref name, _) => AppError::CorruptData {
+                field: Some(name.clone()),
+                detail: e.to_string(),
+            },
+            e => AppError::Database(e),
+        }
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        AppError::Io(e)
+    }
+}
@@ -0,0 +1,418 @@
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use chrono::{Local, NaiveDate, NaiveTime};
+use rust_xlsxwriter::{Color, Format, Workbook, Worksheet, XlsxError};
+use serde::Serialize;
+
+use crate::case_query::{CaseCounts, CaseFilter, CaseSummary, SortKey};
+use crate::database_init::{Case, Db};
+use crate::error::{AppError, AppResult};
+
+/// Header row of every export, in the order the cells are written.
+pub const EXPORT_COLUMNS: [&str; 16] = [
+    "Case No",
+    "Year",
+    "Nature of Case",
+    "Received From",
+    "Intake Date",
+    "Time Slot",
+    "Party 1",
+    "Party 2",
+    "Mediator",
+    "Next Hearing",
+    "Next Hearing Time",
+    "Disposal",
+    "Disposal Date",
+    "Disposal Reason",
+    "Disposal Remarks",
+    "Connected",
+];
+
+/// Widths for `EXPORT_COLUMNS` on the XLSX case sheet, in characters.
+const COLUMN_WIDTHS: [f64; 16] = [
+    9.0, 7.0, 22.0, 28.0, 12.0, 10.0, 32.0, 32.0, 24.0, 12.0, 10.0, 22.0, 12.0, 24.0, 32.0, 10.0,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Xlsx,
+}
+
+impl ExportFormat {
+    pub fn for_path(path: &Path) -> Option<Self> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("csv") => Some(ExportFormat::Csv),
+            Some(ext) if ext.eq_ignore_ascii_case("xlsx") => Some(ExportFormat::Xlsx),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportInfo {
+    pub path: String,
+    pub format: ExportFormat,
+    pub cases: usize,
+}
+
+impl Db {
+    /// Writes every case matching `filter` to `path`, as CSV or XLSX going
+    /// by its extension. Rows go straight from the query to the file.
+    pub fn export_cases(&self, filter: &CaseFilter, sort: &[SortKey], path: &Path) -> AppResult<ExportInfo> {
+        let format = ExportFormat::for_path(path).ok_or_else(|| AppError::InvalidValue {
+            field: "path",
+            value: path.display().to_string(),
+        })?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let cases = match format {
+            ExportFormat::Csv => self.export_csv(filter, sort, path)?,
+            ExportFormat::Xlsx => self.export_xlsx(filter, sort, path)?,
+        };
+
+        println!("Exported {} case(s) to {}", cases, path.display());
+        Ok(ExportInfo {
+            path: path.display().to_string(),
+            format,
+            cases,
+        })
+    }
+
+    fn export_csv(&self, filter: &CaseFilter, sort: &[SortKey], path: &Path) -> AppResult<usize> {
+        let failed = |e: csv::Error| export_failed(path, e.to_string());
+
+        let mut file = BufWriter::new(File::create(path)?);
+        // Excel only reads a CSV as UTF-8 when it starts with a byte order mark.
+        file.write_all("\u{feff}".as_bytes())?;
+
+        let mut writer = csv::Writer::from_writer(file);
+        writer.write_record(EXPORT_COLUMNS).map_err(failed)?;
+        let cases = self.for_each_case(filter, sort, |case| {
+            writer.write_record(csv_cells(case)).map_err(failed)
+        })?;
+        writer.flush()?;
+
+        Ok(cases)
+    }
+
+    /// A "Cases" sheet with real date cells and a frozen, filterable header,
+    /// then a "Summary" sheet with the filters used and the disposal counts.
+    fn export_xlsx(&self, filter: &CaseFilter, sort: &[SortKey], path: &Path) -> AppResult<usize> {
+        let failed = |e: XlsxError| export_failed(path, e.to_string());
+        let formats = Formats::new();
+
+        let counts = self.case_counts(filter)?;
+        let mut notes = vec![
+            self.centre_name()?,
+            format!(
+                "Case export generated {} by {}",
+                Local::now().format("%d.%m.%Y %H:%M"),
+                self.operator()
+            ),
+        ];
+        let criteria = self.describe_filter(filter)?;
+        if criteria.is_empty() {
+            notes.push("All cases on the register.".to_string());
+        } else {
+            notes.extend(criteria);
+        }
+
+        let mut workbook = Workbook::new();
+
+        // Constant memory mode flushes each row to disk as it is written.
+        let sheet = workbook.add_worksheet_with_constant_memory();
+        start_case_sheet(sheet, &formats).map_err(failed)?;
+        let mut row = 0;
+        let cases = self.for_each_case(filter, sort, |case| {
+            row += 1;
+            write_case_row(sheet, row, case, &formats).map_err(failed)
+        })?;
+        if cases > 0 {
+            sheet
+                .autofilter(0, 0, row, EXPORT_COLUMNS.len() as u16 - 1)
+                .map_err(failed)?;
+        }
+
+        let sheet = workbook.add_worksheet();
+        write_summary_sheet(sheet, &notes, &counts, &formats).map_err(failed)?;
+
+        workbook.save(path).map_err(failed)?;
+        Ok(cases)
+    }
+}
+
+fn export_failed(path: &Path, detail: String) -> AppError {
+    AppError::ExportFailed {
+        path: path.display().to_string(),
+        detail,
+    }
+}
+
+fn csv_cells(case: &Case) -> Vec<String> {
+    let date = |d: Option<NaiveDate>| d.map(|d| d.to_string()).unwrap_or_default();
+    let time = |t: Option<NaiveTime>| t.map(|t| t.format("%H:%M").to_string()).unwrap_or_default();
+
+    vec![
+        case.case_no.to_string(),
+        case.year.to_string(),
+        case.nature_of_case.label().to_string(),
+        case.received_from.clone(),
+        date(Some(case.date)),
+        time(Some(case.time_slot)),
+        case.party1.clone(),
+        case.party2.clone(),
+        case.assigned_to.clone(),
+        date(case.ndoh_date),
+        time(case.ndoh_time),
+        case.disposal_of_case.map(|d| d.label().to_string()).unwrap_or_default(),
+        date(case.disposal_date),
+        case.disposal_reason.clone().unwrap_or_default(),
+        case.disposal_remarks.clone().unwrap_or_default(),
+        case.connected.to_string(),
+    ]
+}
+
+pub(crate) struct Formats {
+    pub(crate) header: Format,
+    pub(crate) title: Format,
+    pub(crate) date: Format,
+    pub(crate) time: Format,
+}
+
+impl Formats {
+    pub(crate) fn new() -> Self {
+        Formats {
+            header: Format::new().set_bold().set_background_color(Color::RGB(0xE0E0E0)),
+            title: Format::new().set_bold(),
+            date: Format::new().set_num_format("dd.mm.yyyy"),
+            time: Format::new().set_num_format("hh:mm"),
+        }
+    }
+}
+
+fn start_case_sheet(sheet: &mut Worksheet, formats: &Formats) -> Result<(), XlsxError> {
+    sheet.set_name("Cases")?;
+    for (col, (title, width)) in EXPORT_COLUMNS.iter().zip(COLUMN_WIDTHS).enumerate() {
+        sheet.set_column_width(col as u16, width)?;
+        sheet.write_string_with_format(0, col as u16, *title, &formats.header)?;
+    }
+    sheet.set_freeze_panes(1, 0)?;
+    Ok(())
+}
+
+fn write_case_row(sheet: &mut Worksheet, row: u32, case: &Case, formats: &Formats) -> Result<(), XlsxError> {
+    let text = |sheet: &mut Worksheet, col: u16, value: Option<&str>| -> Result<(), XlsxError> {
+        if let Some(value) = value.filter(|v| !v.is_empty()) {
+            sheet.write_string(row, col, value)?;
+        }
+        Ok(())
+    };
+    let date = |sheet: &mut Worksheet, col: u16, value: Option<NaiveDate>| -> Result<(), XlsxError> {
+        if let Some(value) = value {
+            sheet.write_date_with_format(row, col, value, &formats.date)?;
+        }
+        Ok(())
+    };
+    let time = |sheet: &mut Worksheet, col: u16, value: Option<NaiveTime>| -> Result<(), XlsxError> {
+        if let Some(value) = value {
+            sheet.write_time_with_format(row, col, value, &formats.time)?;
+        }
+        Ok(())
+    };
+
+    sheet.write_number(row, 0, case.case_no)?;
+    sheet.write_number(row, 1, case.year)?;
+    text(sheet, 2, Some(case.nature_of_case.label()))?;
+    text(sheet, 3, Some(&case.received_from))?;
+    date(sheet, 4, Some(case.date))?;
+    time(sheet, 5, Some(case.time_slot))?;
+    text(sheet, 6, Some(&case.party1))?;
+    text(sheet, 7, Some(&case.party2))?;
+    text(sheet, 8, Some(&case.assigned_to))?;
+    date(sheet, 9, case.ndoh_date)?;
+    time(sheet, 10, case.ndoh_time)?;
+    text(sheet, 11, case.disposal_of_case.map(|d| d.label()))?;
+    date(sheet, 12, case.disposal_date)?;
+    text(sheet, 13, case.disposal_reason.as_deref())?;
+    text(sheet, 14, case.disposal_remarks.as_deref())?;
+    sheet.write_number(row, 15, case.connected)?;
+    Ok(())
+}
+
+/// `notes` one per line, then the counts overall, by nature of case and by
+/// mediator, each under its own header row.
+fn write_summary_sheet(
+    sheet: &mut Worksheet,
+    notes: &[String],
+    counts: &CaseCounts,
+    formats: &Formats,
+) -> Result<(), XlsxError> {
+    sheet.set_name("Summary")?;
+    sheet.set_column_width(0, 36)?;
+    for col in 1..=counts.summary.count_titles().len() as u16 {
+        sheet.set_column_width(col, 14)?;
+    }
+
+    let mut row = 0;
+    for (i, note) in notes.iter().enumerate() {
+        if i == 0 {
+            sheet.write_string_with_format(row, 0, note, &formats.title)?;
+        } else {
+            sheet.write_string(row, 0, note)?;
+        }
+        row += 1;
+    }
+
+    let mut table = |first: &str, rows: Vec<(&str, &CaseSummary)>| -> Result<(), XlsxError> {
+        row += 1;
+        sheet.write_string_with_format(row, 0, first, &formats.header)?;
+        for (col, title) in counts.summary.count_titles().into_iter().enumerate() {
+            sheet.write_string_with_format(row, col as u16 + 1, title, &formats.header)?;
+        }
+        for (label, summary) in rows {
+            row += 1;
+            sheet.write_string(row, 0, label)?;
+            for (col, count) in summary.counts().into_iter().enumerate() {
+                sheet.write_number(row, col as u16 + 1, count as u32)?;
+            }
+        }
+        row += 1;
+        Ok(())
+    };
+
+    table("Summary", vec![("All matching cases", &counts.summary)])?;
+    table(
+        "Nature of Case",
+        counts
+            .by_nature
+            .iter()
+            .map(|n| (n.nature_of_case.label(), &n.counts))
+            .collect(),
+    )?;
+    table(
+        "Mediator",
+        counts
+            .by_mediator
+            .iter()
+            .map(|m| (m.mediator.as_str(), &m.counts))
+            .collect(),
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use calamine::{open_workbook_auto, Data, Reader};
+    use chrono::Local;
+
+    use super::*;
+    use crate::case_query::DisposalFilter;
+    use crate::database_init::DisposalOfCase;
+    use crate::test_support::{dispose, intake, register, ScratchDir};
+
+    // Three cases taken in today, the second of them settled today.
+    fn register_with_cases(dir: &ScratchDir) -> Db {
+        let db = register(dir);
+        for case_no in 1..=3 {
+            db.insert_case(intake(case_no, 2024)).unwrap();
+        }
+        dispose(&db, 2, 2024, DisposalOfCase::Settled, Local::now().date_naive());
+        db
+    }
+
+    fn read_csv(path: &Path) -> (Vec<u8>, Vec<Vec<String>>) {
+        let bytes = fs::read(path).unwrap();
+        let rows = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(&bytes[3..])
+            .records()
+            .map(|r| r.unwrap().iter().map(str::to_string).collect())
+            .collect();
+        (bytes, rows)
+    }
+
+    #[test]
+    fn csv_starts_with_a_byte_order_mark_and_the_header_row() {
+        let dir = ScratchDir::new("export-csv");
+        let db = register_with_cases(&dir);
+        let path = dir.join("cases.csv");
+
+        let info = db.export_cases(&CaseFilter::default(), &[], &path).unwrap();
+        let (bytes, rows) = read_csv(&path);
+
+        assert_eq!(info.format, ExportFormat::Csv);
+        assert_eq!(info.cases, 3);
+        assert_eq!(&bytes[..3], "\u{feff}".as_bytes());
+        assert_eq!(rows[0], EXPORT_COLUMNS);
+        assert_eq!(rows.len(), 4);
+    }
+
+    #[test]
+    fn csv_holds_only_the_filtered_cases_with_iso_dates() {
+        let dir = ScratchDir::new("export-csv-filtered");
+        let db = register_with_cases(&dir);
+        let path = dir.join("settled.csv");
+        let filter = CaseFilter {
+            disposal: vec![DisposalFilter::Is(DisposalOfCase::Settled)],
+            ..Default::default()
+        };
+
+        let info = db.export_cases(&filter, &[], &path).unwrap();
+        let (_, rows) = read_csv(&path);
+
+        assert_eq!(info.cases, 1);
+        assert_eq!(rows.len(), 2);
+        let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+        let cell = |title: &str| rows[1][EXPORT_COLUMNS.iter().position(|c| *c == title).unwrap()].as_str();
+        assert_eq!(cell("Case No"), "2");
+        assert_eq!(cell("Intake Date"), today);
+        assert_eq!(cell("Time Slot"), "10:00");
+        assert_eq!(cell("Next Hearing"), today);
+        assert_eq!(cell("Next Hearing Time"), "11:00");
+        assert_eq!(cell("Disposal"), DisposalOfCase::Settled.label());
+        assert_eq!(cell("Disposal Date"), today);
+        assert_eq!(cell("Disposal Reason"), "Settlement reached");
+    }
+
+    #[test]
+    fn xlsx_summary_sheet_carries_the_totals() {
+        let dir = ScratchDir::new("export-xlsx");
+        let db = register_with_cases(&dir);
+        let path = dir.join("cases.xlsx");
+
+        let info = db.export_cases(&CaseFilter::default(), &[], &path).unwrap();
+        assert_eq!(info.format, ExportFormat::Xlsx);
+        assert_eq!(info.cases, 3);
+
+        let mut workbook = open_workbook_auto(&path).unwrap();
+        assert_eq!(workbook.sheet_names(), ["Cases", "Summary"]);
+
+        let cases = workbook.worksheet_range("Cases").unwrap();
+        assert_eq!(cases.height(), 4);
+        assert!(matches!(cases.get((1, 4)), Some(Data::DateTime(_))));
+
+        let summary = workbook.worksheet_range("Summary").unwrap();
+        assert_eq!(summary.get((0, 0)), Some(&Data::String(db.centre_name().unwrap())));
+        let rows: Vec<&[Data]> = summary.rows().collect();
+        let header = rows.iter().find(|r| r[0] == Data::String("Summary".to_string())).unwrap();
+        let totals = rows.iter().find(|r| r[0] == Data::String("All matching cases".to_string())).unwrap();
+        let count = |title: &str| {
+            let col = header.iter().position(|c| *c == Data::String(title.to_string())).unwrap();
+            match &totals[col] {
+                Data::Float(f) => *f as usize,
+                other => panic!("{} is not a count: {:?}", title, other),
+            }
+        };
+        assert_eq!(count("Total"), 3);
+        assert_eq!(count("Undisposed"), 2);
+        assert_eq!(count(DisposalOfCase::Settled.label()), 1);
+    }
+}
@@ -1,64 +1,117 @@
+mod archive;
+mod audit;
+mod backup;
+mod backup_schedule;
+mod case_import;
+mod case_query;
 mod database_init;
-
-use std::{fs, path::PathBuf, sync::{Arc, Mutex, OnceLock}};
-use chrono::{NaiveTime, NaiveDate};
+mod disposal;
+mod error;
+mod export;
+mod mediators;
+mod migrations;
+mod monthly_return;
+mod party_match;
+mod pool;
+mod report;
+mod roster;
+mod search;
+mod state;
+#[cfg(test)]
+mod test_support;
+
+use std::{path::PathBuf, thread, time::Duration};
+use chrono::{Local, NaiveTime, NaiveDate};
+use tauri::{AppHandle, Manager, State};
 use serde::{Deserialize};
-use crate::database_init::Db;
-
-static DB_PATH: OnceLock<PathBuf> = OnceLock::new();
-static DB_INSTANCE: OnceLock<Arc<Mutex<Db>>> = OnceLock::new();
+use crate::database_init::{CaseIntake, CaseKey, CaseUpdate, Db, DisposalOfCase, NatureOfCase};
+use crate::disposal::{DisposalDetails, DisposalReason};
+use crate::error::{AppError, AppResult};
+use crate::export::ExportInfo;
+use crate::archive::ArchiveInfo;
+use crate::audit::{AuditEntry, AuditVerification};
+use crate::backup::{BackupInfo, RestoreInfo};
+use crate::backup_schedule::{BackupKind, BackupSchedule, BackupStatus};
+use crate::case_import::{ImportField, ImportFieldOption, ImportMapping, ImportPreview, ImportProfile, ImportReport, ImportSource};
+use crate::case_query::{CaseFilter, CasePage, CasePageRequest, DateField, DateRange, DisposalFilter, SortField, SortKey};
+use crate::mediators::{Mediator, MediatorDetails};
+use crate::monthly_return::{MonthlyReturn, ReturnFileInfo};
+use crate::party_match::{RegisteredCase, SimilarParty, DEFAULT_MATCH_THRESHOLD};
+use crate::report::ReportInfo;
+use crate::roster::{Roster, RosterImportSummary};
+use crate::search::{CaseSearchHit, DEFAULT_SEARCH_LIMIT, MAX_SEARCH_LIMIT};
+use crate::state::{AppState, RecentDatabase};
+
+const RECENT_DATABASES_FILE: &str = "recent_databases.json";
+
+/// Runs `f` against the open register on Tauri's blocking thread pool, so
+/// no command holds up the others (or the window) while SQLite works.
+async fn with_db<T, F>(state: &AppState, f: F) -> AppResult<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&Db) -> AppResult<T> + Send + 'static,
+{
+    let db = state.db()?;
+    tauri::async_runtime::spawn_blocking(move || f(&db))
+        .await
+        .map_err(|e| AppError::TaskFailed { detail: e.to_string() })?
+}
 
+/// Opens the register at `path`, or switches to it if another one is open.
+/// Opening can mean migrating an old register, so it runs off the command
+/// thread like every other database call. With `seed` off, a roster file
+/// next to a new register is left alone so an archive can be imported.
 #[tauri::command]
-fn init_db(path: String) -> Result<(), String> {
-    let db_path = PathBuf::from(path);
-
-    println!("DB path: {}", db_path.display());
-
-
-    if DB_PATH.set(db_path.clone()).is_err() {
-        return Err("Database path has already been set!".to_string());
-    }
-
-
-    // Ensure directory exists
-    if !db_path.exists() {
-        if let Some(parent) = db_path.parent() {
-            fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create directory: {}", e))?;
-        }
-
-        // Create empty file
-        fs::File::create(&db_path)
-            .map_err(|e| format!("Failed to create file: {}", e))?;
-    }
-
-    println!("Initializing database at: {}", db_path.display());
-
-    // Initialize DB connection
-    let db = Db::init(db_path.clone())
-        .map_err(|e| format!("Failed to initialize DB: {}", e))?;
+async fn open_db(app: AppHandle, path: String, seed: Option<bool>) -> AppResult<()> {
+    tauri::async_runtime::spawn_blocking(move || {
+        app.state::<AppState>()
+            .open(PathBuf::from(path), seed.unwrap_or(true))
+            .map(|_| ())
+    })
+    .await
+    .map_err(|e| AppError::TaskFailed { detail: e.to_string() })?
+}
 
-    println!("Database initialized successfully at: {}", db_path.display());
+#[tauri::command]
+fn close_db(state: State<'_, AppState>) {
+    state.close();
+}
 
-    DB_INSTANCE
-        .set(Arc::new(Mutex::new(db)))
-        .map_err(|_| "Database already initialized".to_string())?;
-    println!("DB instance set successfully");
+/// Path of the register that is open now, if any.
+#[tauri::command]
+fn current_db(state: State<'_, AppState>) -> Option<String> {
+    state.open_path().map(|path| path.display().to_string())
+}
 
-    Ok(())
+#[tauri::command]
+fn get_recent_databases(state: State<'_, AppState>) -> AppResult<Vec<RecentDatabase>> {
+    state.recent_databases()
 }
 
+#[tauri::command]
+fn forget_recent_database(state: State<'_, AppState>, path: String) -> AppResult<Vec<RecentDatabase>> {
+    state.forget_recent(&path)
+}
 
-pub fn get_db_path() -> &'static PathBuf {
-    DB_PATH.get().expect("DB path not initialized")
+fn parse_date(field: &'static str, value: &str) -> AppResult<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| AppError::InvalidDate {
+        field,
+        value: value.to_string(),
+    })
 }
 
-pub fn init_db_path(path: String) {
-    let _ = DB_PATH.set(PathBuf::from(path));
+fn parse_time(field: &'static str, value: &str) -> AppResult<NaiveTime> {
+    NaiveTime::parse_from_str(value, "%H:%M").map_err(|_| AppError::InvalidTime {
+        field,
+        value: value.to_string(),
+    })
 }
 
-pub fn get_db_instance() -> &'static Arc<Mutex<Db>> {
-    DB_INSTANCE.get().expect("DB instance not initialized")
+fn parse_nature_of_case(value: &str) -> AppResult<NatureOfCase> {
+    NatureOfCase::from_code(value).ok_or_else(|| AppError::InvalidValue {
+        field: "nature_of_case",
+        value: value.to_string(),
+    })
 }
 
 #[derive(Deserialize,Debug)]
@@ -70,138 +123,777 @@ struct NewCasePayload {
     time_slot: String,
     party1: String,
     party2: String,
-    assigned_to: String,
+    mediator_id: i32,
+}
+
+impl NewCasePayload {
+    fn into_intake(self) -> AppResult<CaseIntake> {
+        Ok(CaseIntake {
+            case_no: self.case_no,
+            year: self.year,
+            nature_of_case: parse_nature_of_case(&self.nature_of_case)?,
+            received_from: self.received_from,
+            time_slot: parse_time("time_slot", &self.time_slot)?,
+            party1: self.party1,
+            party2: self.party2,
+            mediator_id: self.mediator_id,
+        })
+    }
 }
 
 #[tauri::command]
-fn insert_case(payload: NewCasePayload) -> Result<usize, String>{
+async fn insert_case(state: State<'_, AppState>, payload: NewCasePayload) -> AppResult<RegisteredCase> {
     
     print!("Inserting case: {:?}", payload);
 
-    let db = get_db_instance();
-    let db = db.lock().unwrap();
-
-    let nature = serde_plain::from_str(&payload.nature_of_case)
-        .map_err(|_| "Invalid nature_of_case".to_string())?;
-    let time = NaiveTime::parse_from_str(&payload.time_slot, "%H:%M")
-        .map_err(|_| "Invalid time format".to_string())?;
-
-    db.insert_case(
-        payload.case_no,
-        payload.year,
-        nature,
-        payload.received_from,
-        time,
-        payload.party1,
-        payload.party2,
-        payload.assigned_to,
-    )
-    .map_err(|e| e.to_string())
-    .map(|res| {
-        println!("Case inserted successfully: {:?}", payload.case_no);
-        res
+    let case_no = payload.case_no;
+    let intake = payload.into_intake()?;
+    with_db(&state, move |db| {
+        let (party1, party2) = (intake.party1.clone(), intake.party2.clone());
+        let id = db.insert_case(intake)?;
+        println!("Case inserted successfully: {:?}", case_no);
+
+        // The case is in either way; a failed lookup only loses the warning.
+        let open_matches = db
+            .open_cases_for_parties(id as i32, &party1, &party2)
+            .unwrap_or_else(|e| {
+                eprintln!("Could not check parties of case {} for open cases: {}", case_no, e);
+                Vec::new()
+            });
+        Ok(RegisteredCase { id, open_matches })
     })
+    .await
+}
+
+#[derive(Deserialize, Debug)]
+struct EditCasePayload {
+    case_no: i32,
+    year: i32,
+    corrected: NewCasePayload,
+    edited_by: String,
+    reason: String,
+}
+
+#[tauri::command]
+async fn edit_case(state: State<'_, AppState>, payload: EditCasePayload) -> AppResult<database_init::Case> {
+    println!("Editing case: {:?}", payload);
+
+    let edited_by = payload.edited_by.trim().to_string();
+    if edited_by.is_empty() {
+        return Err(AppError::InvalidValue { field: "edited_by", value: payload.edited_by });
+    }
+    let reason = payload.reason.trim().to_string();
+    if reason.is_empty() {
+        return Err(AppError::InvalidValue { field: "reason", value: payload.reason });
+    }
+
+    let key = CaseKey { case_no: payload.case_no, year: payload.year };
+    let corrected = payload.corrected.into_intake()?;
+    with_db(&state, move |db| db.edit_case(key, corrected, &edited_by, &reason)).await
+}
+
+#[tauri::command]
+async fn get_case_edits(state: State<'_, AppState>, case_no: i32, year: i32) -> AppResult<Vec<database_init::CaseEdit>> {
+    with_db(&state, move |db| db.get_case_edits(CaseKey { case_no, year })).await
 }
 
 #[derive(Deserialize, Debug)]
 struct UpdatePayload {
     case_no: i32,
+    year: i32,
     ndoh_date: String,
     ndoh_time: String,
     disposal_of_case: String,
     connected: Option<i32>, // Optional field for connected status   
+    remarks: Option<String>,
+    /// Defaults to today when a final disposal is recorded without one.
+    disposal_date: Option<String>,
+    disposal_reason_id: Option<i32>,
+    disposal_remarks: Option<String>,
 }
 
 #[tauri::command]
-fn update_case(payload: UpdatePayload) -> Result<usize, String> {
+async fn update_case(state: State<'_, AppState>, payload: UpdatePayload) -> AppResult<usize> {
 
     println!("Updating case: {:?}", payload);
 
-    let db = get_db_instance();
-    let db = db.lock().unwrap();
-
-    let ndoh_date = NaiveDate::parse_from_str(&payload.ndoh_date, "%Y-%m-%d")
-        .map_err(|_| "Invalid date format".to_string())?;
-    let ndoh_time = NaiveTime::parse_from_str(&payload.ndoh_time, "%H:%M")
-        .map_err(|_| "Invalid time format".to_string())?;
-    let disposal = serde_json::from_str(&format!("\"{}\"", payload.disposal_of_case))
-        .map_err(|_| "Invalid disposal_of_case".to_string())?;
+    let ndoh_date = parse_date("ndoh_date", &payload.ndoh_date)?;
+    let ndoh_time = parse_time("ndoh_time", &payload.ndoh_time)?;
+    let disposal_of_case = DisposalOfCase::from_code(&payload.disposal_of_case).ok_or_else(|| {
+        AppError::InvalidValue {
+            field: "disposal_of_case",
+            value: payload.disposal_of_case.clone(),
+        }
+    })?;
     let connected = payload.connected; // Default to 0 if not provided
 
-    db.update_case_details(payload.case_no, ndoh_date, ndoh_time, disposal, connected)
-        .map_err(|e| e.to_string())
+    let disposal = match payload.disposal_reason_id {
+        Some(reason_id) => Some(DisposalDetails {
+            date: match non_empty(&payload.disposal_date) {
+                Some(date) => parse_date("disposal_date", date)?,
+                None => Local::now().date_naive(),
+            },
+            reason_id,
+            remarks: payload
+                .disposal_remarks
+                .map(|r| r.trim().to_string())
+                .filter(|r| !r.is_empty()),
+        }),
+        None => None,
+    };
+
+    let update = CaseUpdate {
+        ndoh_date,
+        ndoh_time,
+        disposal_of_case,
+        connected,
+        remarks: payload.remarks,
+        disposal,
+    };
+
+    with_db(&state, move |db| {
+        db.update_case_details(CaseKey { case_no: payload.case_no, year: payload.year }, update)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn get_case(state: State<'_, AppState>, case_no: i32, year: i32) -> AppResult<database_init::Case> {
+    with_db(&state, move |db| db.get_case(CaseKey { case_no, year })).await
+}
+
+#[tauri::command]
+async fn get_case_timeline(state: State<'_, AppState>, case_no: i32, year: i32) -> AppResult<database_init::CaseTimeline> {
+    with_db(&state, move |db| db.get_case_timeline(CaseKey { case_no, year })).await
+}
+
+#[derive(Deserialize, Debug)]
+struct ReassignPayload {
+    case_no: i32,
+    year: i32,
+    to_mediator_id: i32,
+    transferred_on: String,
+    reason: String,
 }
 
 #[tauri::command]
-fn get_todays_cases() -> Result<Vec<database_init::Case>, String> {
-    let db = get_db_instance();
-    let db = db.lock().unwrap();
+async fn reassign_case(state: State<'_, AppState>, payload: ReassignPayload) -> AppResult<database_init::CaseTransfer> {
+    println!("Reassigning case: {:?}", payload);
+
+    let transferred_on = parse_date("transferred_on", &payload.transferred_on)?;
+    let reason = payload.reason.trim().to_string();
+    if reason.is_empty() {
+        return Err(AppError::InvalidValue { field: "reason", value: payload.reason });
+    }
 
-    db.get_cases_for_today().map_err(|e| e.to_string())
+    with_db(&state, move |db| {
+        db.reassign_case(
+            CaseKey { case_no: payload.case_no, year: payload.year },
+            payload.to_mediator_id,
+            transferred_on,
+            &reason,
+        )
+    })
+    .await
+}
+
+#[tauri::command]
+async fn get_todays_cases(state: State<'_, AppState>) -> AppResult<Vec<database_init::Case>> {
+    with_db(&state, |db| db.get_cases_for_today()).await
 }
 
 #[derive(Deserialize, Debug)]
 struct CaseQueryPayLoad {
     nature_of_case: Option<String>,
-    assigned_to: Option<String>,
-    start_date: String,
-    end_date: String,
-    // end_date: String,
+    mediator_id: Option<i32>,
+    assigned_as_of: Option<String>,
+    received_from: Option<String>,
+    /// Disposal codes, plus "None" for cases with nothing recorded yet.
+    #[serde(default)]
+    disposal: Vec<String>,
+    connected: Option<bool>,
+    case_no_from: Option<i32>,
+    case_no_to: Option<i32>,
+    year: Option<i32>,
+    party: Option<String>,
+    /// "any" (the default), "intake", "hearing", "disposal" or "listed".
+    date_field: Option<String>,
+    start_date: Option<String>,
+    end_date: Option<String>,
+    #[serde(default)]
+    sort: Vec<SortPayload>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+}
+
+#[derive(Deserialize, Debug)]
+struct SortPayload {
+    field: String,
+    #[serde(default)]
+    descending: bool,
 }
 
+impl CaseQueryPayLoad {
+    fn into_query(self) -> AppResult<(CaseFilter, CasePageRequest)> {
+        let date_range = match (non_empty(&self.start_date), non_empty(&self.end_date)) {
+            (None, None) => None,
+            (start, end) => {
+                let field = match non_empty(&self.date_field) {
+                    None => DateField::Any,
+                    Some(code) => DateField::from_code(code).ok_or_else(|| AppError::InvalidValue {
+                        field: "date_field",
+                        value: code.to_string(),
+                    })?,
+                };
+                // An open end means "from the beginning" or "until now".
+                Some(DateRange {
+                    field,
+                    start: start.map(|d| parse_date("start_date", d)).transpose()?,
+                    end: end.map(|d| parse_date("end_date", d)).transpose()?,
+                })
+            }
+        };
+
+        let filter = CaseFilter {
+            nature_of_case: non_empty(&self.nature_of_case).map(parse_nature_of_case).transpose()?,
+            mediator_id: self.mediator_id,
+            assigned_as_of: non_empty(&self.assigned_as_of)
+                .map(|d| parse_date("assigned_as_of", d))
+                .transpose()?,
+            received_from: self.received_from,
+            disposal: self
+                .disposal
+                .iter()
+                .map(|code| {
+                    DisposalFilter::from_code(code).ok_or_else(|| AppError::InvalidValue {
+                        field: "disposal",
+                        value: code.clone(),
+                    })
+                })
+                .collect::<AppResult<_>>()?,
+            connected: self.connected,
+            case_no_from: self.case_no_from,
+            case_no_to: self.case_no_to,
+            year: self.year,
+            party: self.party,
+            date_range,
+        };
+
+        let page = CasePageRequest {
+            sort: self
+                .sort
+                .iter()
+                .map(|key| {
+                    let field = SortField::from_code(&key.field).ok_or_else(|| AppError::InvalidValue {
+                        field: "sort",
+                        value: key.field.clone(),
+                    })?;
+                    Ok(SortKey { field, descending: key.descending })
+                })
+                .collect::<AppResult<_>>()?,
+            limit: self.limit,
+            offset: self.offset.unwrap_or(0),
+        };
+
+        Ok((filter, page))
+    }
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().filter(|v| !v.is_empty())
+}
+
+/// Cases matching every filter given, sorted and sliced as asked, with the
+/// total number of matches and their disposal counts. Without `limit` every
+/// match comes back, which is what the printed report wants.
 #[tauri::command]
-fn query_cases_with_filters(
+async fn query_cases_with_filters(
+    state: State<'_, AppState>,
     payload : CaseQueryPayLoad
- ) -> Result<database_init::CaseQueryResult, String> {
+ ) -> AppResult<CasePage> {
+    let (filter, page) = payload.into_query()?;
+    with_db(&state, move |db| db.query_cases_filtered(&filter, &page)).await
+}
 
-    // print!("Querying cases with filters: nature_of_case: {:?}, assigned_to: {:?}", payload.nature_of_case, payload.assigned_to);
+/// Prints the day's board, every case taken in or listed for today, to
+/// `path` as a PDF.
+#[tauri::command]
+async fn write_todays_report(state: State<'_, AppState>, path: String) -> AppResult<ReportInfo> {
+    let today = Local::now().date_naive();
+    let filter = CaseFilter {
+        date_range: Some(DateRange { field: DateField::Listed, start: Some(today), end: Some(today) }),
+        ..Default::default()
+    };
+    with_db(&state, move |db| {
+        db.write_case_report("Today's Case Report", &filter, Vec::new(), &PathBuf::from(path))
+    })
+    .await
+}
 
-    
-    let db = get_db_instance(); // Your global Arc<Mutex<Db>>
-    let db = db.lock().unwrap();
+/// Prints the cases `query_cases_with_filters` would return for `payload`
+/// to `path` as a PDF. Every match is printed; `limit` and `offset` are
+/// ignored.
+#[tauri::command]
+async fn write_filtered_report(
+    state: State<'_, AppState>,
+    payload: CaseQueryPayLoad,
+    path: String,
+) -> AppResult<ReportInfo> {
+    let (filter, page) = payload.into_query()?;
+    with_db(&state, move |db| {
+        db.write_case_report("Filtered Case Report", &filter, page.sort, &PathBuf::from(path))
+    })
+    .await
+}
 
-    // Parse dates from strings
-    let start = NaiveDate::parse_from_str(&payload.start_date, "%Y-%m-%d")
-        .map_err(|e| format!("Invalid start_date: {}", e))?;
-    let end = NaiveDate::parse_from_str(&payload.end_date, "%Y-%m-%d")
-        .map_err(|e| format!("Invalid end_date: {}", e))?;
+/// The statement of cases pending, received and disposed of during `month`
+/// of `year`, overall, by nature of case and by referring court.
+#[tauri::command]
+async fn monthly_return(state: State<'_, AppState>, year: i32, month: u32) -> AppResult<MonthlyReturn> {
+    with_db(&state, move |db| db.monthly_return(year, month)).await
+}
 
+/// Writes `monthly_return` to `path`, as a PDF or XLSX going by the extension.
+#[tauri::command]
+async fn write_monthly_return(
+    state: State<'_, AppState>,
+    year: i32,
+    month: u32,
+    path: String,
+) -> AppResult<ReturnFileInfo> {
+    with_db(&state, move |db| db.write_monthly_return(year, month, &PathBuf::from(path))).await
+}
+
+/// Writes the cases `query_cases_with_filters` would return for `payload`
+/// to `path` as a spreadsheet: CSV or XLSX, going by the extension. Every
+/// match is written; `limit` and `offset` are ignored.
+#[tauri::command]
+async fn export_cases(
+    state: State<'_, AppState>,
+    payload: CaseQueryPayLoad,
+    path: String,
+) -> AppResult<ExportInfo> {
+    let (filter, page) = payload.into_query()?;
+    with_db(&state, move |db| db.export_cases(&filter, &page.sort, &PathBuf::from(path))).await
+}
 
-    let result = db
-        .query_cases_filtered(payload.nature_of_case, payload.assigned_to, start, end)
-        .map_err(|e| e.to_string())?;
+/// Ranked full-text search over parties, referring courts, case numbers and
+/// hearing remarks.
+#[tauri::command]
+async fn search_cases(
+    state: State<'_, AppState>,
+    query: String,
+    limit: Option<u32>,
+) -> AppResult<Vec<CaseSearchHit>> {
+    let limit = limit.unwrap_or(DEFAULT_SEARCH_LIMIT).clamp(1, MAX_SEARCH_LIMIT);
+    with_db(&state, move |db| db.search_cases(&query, limit)).await
+}
 
-    // Ok(())
+/// Parties already on the register whose names look like `name`, allowing
+/// for titles, "s/o" tails and the usual spelling drift of transliterated
+/// names. `threshold` runs from 0 to 1; higher asks for closer matches.
+#[tauri::command]
+async fn find_similar_parties(
+    state: State<'_, AppState>,
+    name: String,
+    threshold: Option<f64>,
+    open_only: Option<bool>,
+    limit: Option<u32>,
+) -> AppResult<Vec<SimilarParty>> {
+    let threshold = threshold.unwrap_or(DEFAULT_MATCH_THRESHOLD);
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err(AppError::InvalidValue { field: "threshold", value: threshold.to_string() });
+    }
+    let limit = limit.unwrap_or(DEFAULT_SEARCH_LIMIT).clamp(1, MAX_SEARCH_LIMIT) as usize;
+    let open_only = open_only.unwrap_or(false);
+    with_db(&state, move |db| db.find_similar_parties(&name, threshold, open_only, limit)).await
+}
 
-    Ok(database_init::CaseQueryResult {
-        cases: result.0,
-        summary: result.1,
+#[tauri::command]
+fn get_nature_of_case_list() -> Vec<database_init::NatureOfCaseOption> {
+    NatureOfCase::ALL
+        .into_iter()
+        .map(|n| database_init::NatureOfCaseOption {
+            code: n.code(),
+            label: n.label(),
+        })
+        .collect()
+}
+
+/// Every outcome a sitting can record, in the order the forms list them.
+#[tauri::command]
+fn get_disposal_list() -> Vec<database_init::DisposalOption> {
+    DisposalOfCase::ALL
+        .into_iter()
+        .map(|d| database_init::DisposalOption {
+            code: d.code(),
+            label: d.label(),
+            is_final: d.is_final(),
+        })
+        .collect()
+}
+
+#[derive(Deserialize, Debug)]
+struct MediatorPayload {
+    display_name: String,
+    honorific: Option<String>,
+    phone: Option<String>,
+    email: Option<String>,
+    joined_on: Option<String>,
+}
+
+impl MediatorPayload {
+    fn into_details(self) -> AppResult<MediatorDetails> {
+        let display_name = self.display_name.trim().to_string();
+        if display_name.is_empty() {
+            return Err(AppError::InvalidValue { field: "display_name", value: self.display_name });
+        }
+
+        // Blank form fields arrive as "" rather than null.
+        let non_empty = |value: Option<String>| {
+            value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+        };
+
+        let joined_on = match non_empty(self.joined_on) {
+            Some(date) => Some(parse_date("joined_on", &date)?),
+            None => None,
+        };
+
+        Ok(MediatorDetails {
+            display_name,
+            honorific: non_empty(self.honorific).unwrap_or_default(),
+            phone: non_empty(self.phone),
+            email: non_empty(self.email),
+            joined_on,
+        })
+    }
+}
+
+#[tauri::command]
+async fn get_mediators(state: State<'_, AppState>, include_inactive: Option<bool>) -> AppResult<Vec<Mediator>> {
+    with_db(&state, move |db| db.get_mediators(include_inactive.unwrap_or(false))).await
+}
+
+#[tauri::command]
+async fn add_mediator(state: State<'_, AppState>, payload: MediatorPayload) -> AppResult<Mediator> {
+    let details = payload.into_details()?;
+    with_db(&state, move |db| db.add_mediator(details)).await
+}
+
+#[tauri::command]
+async fn update_mediator(state: State<'_, AppState>, id: i32, payload: MediatorPayload) -> AppResult<Mediator> {
+    let details = payload.into_details()?;
+    with_db(&state, move |db| db.update_mediator(id, details)).await
+}
+
+#[tauri::command]
+async fn set_mediator_active(state: State<'_, AppState>, id: i32, active: bool) -> AppResult<Mediator> {
+    with_db(&state, move |db| db.set_mediator_active(id, active)).await
+}
+
+
+
+
+#[tauri::command]
+async fn get_disposal_reasons(state: State<'_, AppState>, include_inactive: Option<bool>) -> AppResult<Vec<DisposalReason>> {
+    with_db(&state, move |db| db.get_disposal_reasons(include_inactive.unwrap_or(false))).await
+}
+
+#[tauri::command]
+async fn add_disposal_reason(state: State<'_, AppState>, label: String) -> AppResult<DisposalReason> {
+    let label = label.trim().to_string();
+    if label.is_empty() {
+        return Err(AppError::InvalidValue { field: "label", value: label });
+    }
+    with_db(&state, move |db| db.add_disposal_reason(&label)).await
+}
+
+#[tauri::command]
+async fn set_disposal_reason_active(state: State<'_, AppState>, id: i32, active: bool) -> AppResult<DisposalReason> {
+    with_db(&state, move |db| db.set_disposal_reason_active(id, active)).await
+}
+
+#[tauri::command]
+async fn needs_setup(state: State<'_, AppState>) -> AppResult<bool> {
+    with_db(&state, |db| db.needs_setup()).await
+}
+
+/// First-run setup from the names typed on the setup screen, one per line,
+/// and the centre's name if it was given.
+#[tauri::command]
+async fn setup_mediators(
+    state: State<'_, AppState>,
+    names: Vec<String>,
+    centre_name: Option<String>,
+) -> AppResult<RosterImportSummary> {
+    let mut roster = Roster::from_names(names.iter().map(String::as_str));
+    if roster.mediators.is_empty() {
+        return Err(AppError::InvalidValue { field: "names", value: names.join("\n") });
+    }
+    roster.centre_name = centre_name;
+
+    with_db(&state, move |db| db.import_roster(roster)).await
+}
+
+#[tauri::command]
+async fn import_roster(state: State<'_, AppState>, path: String) -> AppResult<RosterImportSummary> {
+    with_db(&state, move |db| {
+        let roster = Roster::read(&PathBuf::from(path))?;
+        db.import_roster(roster)
     })
+    .await
 }
 
+/// Writes the full roster to `path` as TOML or JSON, going by its extension.
 #[tauri::command]
-fn get_assigned_to_list() -> Result<Vec<String>, String> {
-    let db = get_db_instance();
-    let db = db.lock().unwrap();
-    db.get_assigned_to_list()
+async fn export_roster(state: State<'_, AppState>, path: String) -> AppResult<usize> {
+    with_db(&state, move |db| {
+        let roster = db.export_roster()?;
+        roster.write(&PathBuf::from(&path))?;
+
+        println!("Exported {} mediator(s) to {}", roster.mediators.len(), path);
+        Ok(roster.mediators.len())
+    })
+    .await
 }
 
+/// Register fields a column of a legacy sheet can be mapped to.
 #[tauri::command]
-fn add_assigned_to(name: String) -> Result<(), String> {
-    let db = get_db_instance();
-    let db = db.lock().unwrap();
-    db.add_assigned_to(name)
+fn get_import_fields() -> Vec<ImportFieldOption> {
+    ImportField::ALL
+        .into_iter()
+        .map(|f| ImportFieldOption {
+            code: f.code(),
+            label: f.label(),
+            required: f.required(),
+        })
+        .collect()
 }
 
+/// Sheet names, headers and the first few rows of a CSV or workbook, for
+/// building a mapping before anything is imported.
 #[tauri::command]
-fn delete_assigned_to(name: String) -> Result<(), String> {
-    let db = get_db_instance();
-    let db = db.lock().unwrap();
-    db.delete_assigned_to(name)
+async fn preview_import(path: String, sheet: Option<String>) -> AppResult<ImportPreview> {
+    tauri::async_runtime::spawn_blocking(move || {
+        ImportSource::read(&PathBuf::from(path), sheet.as_deref()).map(|source| source.preview())
+    })
+    .await
+    .map_err(|e| AppError::TaskFailed { detail: e.to_string() })?
 }
 
+/// Imports a legacy register through `mapping`. A dry run checks every row
+/// and reports what would happen without writing anything; otherwise the
+/// cases go in together, or not at all if any row has an error.
+#[tauri::command]
+async fn import_cases(
+    state: State<'_, AppState>,
+    path: String,
+    mapping: ImportMapping,
+    dry_run: Option<bool>,
+) -> AppResult<ImportReport> {
+    with_db(&state, move |db| {
+        let path = PathBuf::from(path);
+        let source = ImportSource::read(&path, mapping.sheet.as_deref())?;
+        db.import_cases(&path, &source, &mapping, dry_run.unwrap_or(true))
+    })
+    .await
+}
 
+#[tauri::command]
+async fn get_import_profiles(state: State<'_, AppState>) -> AppResult<Vec<ImportProfile>> {
+    with_db(&state, |db| db.get_import_profiles()).await
+}
 
+#[tauri::command]
+async fn save_import_profile(state: State<'_, AppState>, name: String, mapping: ImportMapping) -> AppResult<ImportProfile> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::InvalidValue { field: "name", value: name });
+    }
+    with_db(&state, move |db| db.save_import_profile(&name, &mapping)).await
+}
+
+#[tauri::command]
+async fn delete_import_profile(state: State<'_, AppState>, id: i32) -> AppResult<()> {
+    with_db(&state, move |db| db.delete_import_profile(id)).await
+}
+
+/// Who is at the desk; recorded against every write from now on.
+#[tauri::command]
+fn set_operator(state: State<'_, AppState>, operator: String) -> AppResult<()> {
+    let name = operator.trim();
+    if name.is_empty() {
+        return Err(AppError::InvalidValue { field: "operator", value: operator });
+    }
+
+    state.set_operator(name);
+    Ok(())
+}
+
+#[tauri::command]
+async fn verify_audit_log(state: State<'_, AppState>) -> AppResult<AuditVerification> {
+    with_db(&state, |db| db.verify_audit_log()).await
+}
+
+#[derive(Deserialize, Debug)]
+struct AuditQueryPayload {
+    case_no: Option<i32>,
+    year: Option<i32>,
+    from_date: Option<String>,
+    to_date: Option<String>,
+}
+
+#[tauri::command]
+async fn query_audit_log(state: State<'_, AppState>, payload: AuditQueryPayload) -> AppResult<Vec<AuditEntry>> {
+    let case = match (payload.case_no, payload.year) {
+        (Some(case_no), Some(year)) => Some(CaseKey { case_no, year }),
+        (None, None) => None,
+        (Some(_), None) => return Err(AppError::InvalidValue { field: "year", value: String::new() }),
+        (None, Some(year)) => return Err(AppError::InvalidValue { field: "case_no", value: year.to_string() }),
+    };
+
+    let from = match payload.from_date.as_deref() {
+        None | Some("") => None,
+        Some(date) => Some(parse_date("from_date", date)?),
+    };
+    let to = match payload.to_date.as_deref() {
+        None | Some("") => None,
+        Some(date) => Some(parse_date("to_date", date)?),
+    };
+
+    with_db(&state, move |db| db.query_audit_log(case, from, to)).await
+}
+
+/// Copies the live register to `path`, or to a timestamped file in the
+/// `backups` folder next to it when no path is given.
+#[tauri::command]
+async fn backup_db(state: State<'_, AppState>, path: Option<String>) -> AppResult<BackupInfo> {
+    with_db(&state, move |db| {
+        let dest = match path.as_deref().map(str::trim) {
+            None | Some("") => db
+                .backup_dir()
+                .join(format!("cases-{}.db", chrono::Local::now().format("%Y%m%d-%H%M%S"))),
+            Some(path) => PathBuf::from(path),
+        };
+
+        db.run_manual_backup(&dest)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn restore_db(state: State<'_, AppState>, path: String) -> AppResult<RestoreInfo> {
+    with_db(&state, move |db| db.restore_from(&PathBuf::from(path))).await
+}
+
+/// Writes the whole register, history included, to `path` as a JSON archive.
+#[tauri::command]
+async fn export_json(state: State<'_, AppState>, path: String) -> AppResult<ArchiveInfo> {
+    with_db(&state, move |db| db.export_json(&PathBuf::from(path))).await
+}
+
+/// Rebuilds the open register from a JSON archive. The register must be empty.
+#[tauri::command]
+async fn import_json(state: State<'_, AppState>, path: String) -> AppResult<ArchiveInfo> {
+    with_db(&state, move |db| db.import_json(&PathBuf::from(path))).await
+}
+
+#[tauri::command]
+async fn get_centre_name(state: State<'_, AppState>) -> AppResult<String> {
+    with_db(&state, |db| db.centre_name()).await
+}
+
+#[tauri::command]
+async fn set_centre_name(state: State<'_, AppState>, name: String) -> AppResult<String> {
+    with_db(&state, move |db| db.set_centre_name(&name)).await
+}
+
+#[tauri::command]
+async fn get_backup_schedule(state: State<'_, AppState>) -> AppResult<BackupSchedule> {
+    with_db(&state, |db| db.backup_schedule()).await
+}
+
+#[derive(Deserialize, Debug)]
+struct BackupSchedulePayload {
+    folder: Option<String>,
+    on_start: bool,
+    daily_at: Option<String>,
+    keep_daily: u32,
+    keep_weekly: u32,
+    keep_monthly: u32,
+}
+
+impl BackupSchedulePayload {
+    fn into_schedule(self) -> AppResult<BackupSchedule> {
+        // Always keep at least the snapshot just taken.
+        if self.keep_daily == 0 {
+            return Err(AppError::InvalidValue { field: "keep_daily", value: self.keep_daily.to_string() });
+        }
+
+        let daily_at = match self.daily_at.as_deref().map(str::trim) {
+            None | Some("") => None,
+            Some(time) => Some(parse_time("daily_at", time)?),
+        };
+
+        Ok(BackupSchedule {
+            folder: self.folder.map(|f| f.trim().to_string()).filter(|f| !f.is_empty()),
+            on_start: self.on_start,
+            daily_at,
+            keep_daily: self.keep_daily,
+            keep_weekly: self.keep_weekly,
+            keep_monthly: self.keep_monthly,
+        })
+    }
+}
+
+#[tauri::command]
+async fn set_backup_schedule(state: State<'_, AppState>, payload: BackupSchedulePayload) -> AppResult<BackupSchedule> {
+    let schedule = payload.into_schedule()?;
+    with_db(&state, move |db| db.set_backup_schedule(schedule)).await
+}
+
+/// Where scheduled backups go and how the latest ones went, for the
+/// "last successful backup" line in the UI.
+#[tauri::command]
+async fn get_backup_status(state: State<'_, AppState>) -> AppResult<BackupStatus> {
+    with_db(&state, |db| db.backup_status()).await
+}
+
+/// Takes the scheduled backups for as long as the app runs: one each time a
+/// register is opened (if its schedule asks for it), then the daily one
+/// whenever it falls due. Failures are recorded in `backup_runs` and never
+/// stop the loop.
+fn spawn_backup_scheduler(app: AppHandle) {
+    thread::Builder::new()
+        .name("backup-scheduler".to_string())
+        .spawn(move || {
+            let mut last_opened: Option<PathBuf> = None;
+            loop {
+                if let Ok(db) = app.state::<AppState>().db() {
+                    if let Err(e) = scheduled_backup_tick(&db, &mut last_opened) {
+                        eprintln!("Scheduled backup check failed: {}", e);
+                    }
+                }
+                thread::sleep(Duration::from_secs(5));
+            }
+        })
+        .expect("failed to start the backup scheduler");
+}
+
+fn scheduled_backup_tick(db: &Db, last_opened: &mut Option<PathBuf>) -> AppResult<()> {
+    if last_opened.as_ref() != Some(&db.path) {
+        *last_opened = Some(db.path.clone());
+        if db.backup_schedule()?.on_start {
+            db.run_scheduled_backup(BackupKind::Startup)?;
+        }
+    }
+
+    if db.daily_backup_due(chrono::Local::now().naive_local())? {
+        db.run_scheduled_backup(BackupKind::Daily)?;
+    }
+
+    Ok(())
+}
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -211,15 +903,65 @@ pub fn run() {
     )).build())
     .plugin(tauri_plugin_fs::init())
     .plugin(tauri_plugin_opener::init())
+    .setup(|app| {
+        let recent_file = app.path().app_data_dir()?.join(RECENT_DATABASES_FILE);
+        app.manage(AppState::new(recent_file));
+        spawn_backup_scheduler(app.handle().clone());
+        Ok(())
+    })
     .invoke_handler(tauri::generate_handler![
-        init_db,
+        open_db,
+        close_db,
+        current_db,
+        get_recent_databases,
+        forget_recent_database,
         insert_case,
+        edit_case,
+        get_case_edits,
         update_case,
+        get_case,
+        get_case_timeline,
+        reassign_case,
         get_todays_cases,
         query_cases_with_filters,
-        get_assigned_to_list,
-        add_assigned_to,
-        delete_assigned_to,
+        write_todays_report,
+        write_filtered_report,
+        monthly_return,
+        write_monthly_return,
+        export_cases,
+        search_cases,
+        find_similar_parties,
+        get_nature_of_case_list,
+        get_disposal_list,
+        get_mediators,
+        add_mediator,
+        update_mediator,
+        set_mediator_active,
+        get_disposal_reasons,
+        add_disposal_reason,
+        set_disposal_reason_active,
+        needs_setup,
+        setup_mediators,
+        import_roster,
+        export_roster,
+        get_import_fields,
+        preview_import,
+        import_cases,
+        get_import_profiles,
+        save_import_profile,
+        delete_import_profile,
+        set_operator,
+        verify_audit_log,
+        query_audit_log,
+        backup_db,
+        restore_db,
+        export_json,
+        import_json,
+        get_centre_name,
+        set_centre_name,
+        get_backup_schedule,
+        set_backup_schedule,
+        get_backup_status,
     ])
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
@@ -0,0 +1,209 @@
+use chrono::NaiveDate;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::audit;
+use crate::database_init::{parse_optional_date_column, Db};
+use crate::error::{AppError, AppResult};
+
+/// Titles and courtesy words written in front of a name, whether glued on
+/// by older builds ('Sh_Surender_Singh') or typed with a party's name. The
+/// one list for mediators, imported registers and party matching.
+const HONORIFICS: &[&str] = &[
+    "Sh", "Shri", "Sri", "Smt", "Shrimati", "Km", "Kumari", "Ms", "Mr", "Mrs", "Miss", "Dr", "Late",
+    "Master", "Baby",
+];
+
+pub(crate) const MEDIATOR_COLUMNS: &str =
+    "id, display_name, honorific, phone, email, active, joined_on, created_at";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Mediator {
+    pub id: i32,
+    pub display_name: String,
+    pub honorific: String,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    pub active: bool,
+    pub joined_on: Option<NaiveDate>,
+    pub created_at: String,
+}
+
+/// The editable part of a mediator's record.
+#[derive(Debug, Clone)]
+pub struct MediatorDetails {
+    pub display_name: String,
+    pub honorific: String,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    pub joined_on: Option<NaiveDate>,
+}
+
+impl Db {
+    /// Active mediators for intake forms, or everyone when `include_inactive`
+    /// is set so retired mediators can still be picked in reports.
+    pub fn get_mediators(&self, include_inactive: bool) -> AppResult<Vec<Mediator>> {
+        let conn = self.reader()?;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM mediators
+             WHERE active = 1 OR ?1
+             ORDER BY active DESC, display_name",
+            MEDIATOR_COLUMNS
+        ))?;
+
+        let mediators = stmt
+            .query_map([include_inactive], mediator_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(mediators)
+    }
+
+    pub fn add_mediator(&self, details: MediatorDetails) -> AppResult<Mediator> {
+        let operator = self.operator();
+        let conn = self.writer();
+        let tx = conn.unchecked_transaction()?;
+
+        tx.execute(
+            "INSERT INTO mediators (display_name, honorific, phone, email, joined_on)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                details.display_name,
+                details.honorific,
+                details.phone,
+                details.email,
+                details.joined_on.map(|d| d.to_string())
+            ],
+        )
+        .map_err(|e| AppError::on_duplicate_mediator(e, &details.display_name))?;
+
+        let added = find_mediator(&tx, tx.last_insert_rowid() as i32)?;
+        audit::record(&tx, &operator, "add_mediator", None, None, Some(&json!(added)))?;
+        tx.commit()?;
+
+        println!("Added mediator: {}", details.display_name);
+        Ok(added)
+    }
+
+    pub fn update_mediator(&self, id: i32, details: MediatorDetails) -> AppResult<Mediator> {
+        let operator = self.operator();
+        let conn = self.writer();
+        let tx = conn.unchecked_transaction()?;
+        let before = find_mediator(&tx, id)?;
+
+        tx.execute(
+                "UPDATE mediators
+                 SET display_name = ?1, honorific = ?2, phone = ?3, email = ?4, joined_on = ?5
+                 WHERE id = ?6",
+                params![
+                    details.display_name,
+                    details.honorific,
+                    details.phone,
+                    details.email,
+                    details.joined_on.map(|d| d.to_string()),
+                    id
+                ],
+            )
+            .map_err(|e| AppError::on_duplicate_mediator(e, &details.display_name))?;
+
+        let after = find_mediator(&tx, id)?;
+        audit::record(&tx, &operator, "update_mediator", None, Some(&json!(before)), Some(&json!(after)))?;
+        tx.commit()?;
+
+        Ok(after)
+    }
+
+    /// Retires or reinstates a mediator. Retired mediators keep every case,
+    /// hearing and transfer that names them; they only stop being offered
+    /// for new allocations.
+    pub fn set_mediator_active(&self, id: i32, active: bool) -> AppResult<Mediator> {
+        let operator = self.operator();
+        let conn = self.writer();
+        let tx = conn.unchecked_transaction()?;
+        let before = find_mediator(&tx, id)?;
+
+        tx.execute(
+            "UPDATE mediators SET active = ?1 WHERE id = ?2",
+            params![active, id],
+        )?;
+
+        let after = find_mediator(&tx, id)?;
+        audit::record(&tx, &operator, "set_mediator_active", None, Some(&json!(before)), Some(&json!(after)))?;
+        tx.commit()?;
+
+        println!("Mediator {} set active = {}", id, active);
+        Ok(after)
+    }
+}
+
+pub(crate) fn find_mediator(conn: &Connection, id: i32) -> AppResult<Mediator> {
+    conn.query_row(
+        &format!("SELECT {} FROM mediators WHERE id = ?1", MEDIATOR_COLUMNS),
+        [id],
+        mediator_from_row,
+    )
+    .optional()?
+    .ok_or(AppError::MediatorNotFound { id })
+}
+
+/// Fails unless `id` names a mediator who can take new cases.
+pub(crate) fn require_active_mediator(conn: &Connection, id: i32) -> AppResult<()> {
+    let mediator = find_mediator(conn, id)?;
+    if !mediator.active {
+        return Err(AppError::MediatorInactive {
+            name: mediator.full_name(),
+        });
+    }
+    Ok(())
+}
+
+impl Mediator {
+    pub fn full_name(&self) -> String {
+        if self.honorific.is_empty() {
+            self.display_name.clone()
+        } else {
+            format!("{} {}", self.honorific, self.display_name)
+        }
+    }
+}
+
+pub(crate) fn mediator_from_row(row: &rusqlite::Row) -> rusqlite::Result<Mediator> {
+    Ok(Mediator {
+        id: row.get(0)?,
+        display_name: row.get(1)?,
+        honorific: row.get(2)?,
+        phone: row.get(3)?,
+        email: row.get(4)?,
+        active: row.get(5)?,
+        joined_on: parse_optional_date_column(row, 6)?,
+        created_at: row.get(7)?,
+    })
+}
+
+/// The title `word` is, in its usual spelling, ignoring case and a
+/// trailing full stop.
+pub(crate) fn honorific(word: &str) -> Option<&'static str> {
+    let title = word.trim_end_matches('.');
+    HONORIFICS.iter().find(|h| h.eq_ignore_ascii_case(title)).copied()
+}
+
+/// Splits a legacy name such as 'Sh_Surender_Singh' into its honorific and
+/// display name ("Sh", "Surender Singh"). Names without a recognised title
+/// come back with an empty honorific.
+pub fn parse_legacy_name(name: &str) -> (String, String) {
+    let words: Vec<&str> = name
+        .split(|c: char| c == '_' || c.is_whitespace())
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    match words.split_first() {
+        Some((first, rest)) if !rest.is_empty() => {
+            match honorific(first) {
+                Some(honorific) => (honorific.to_string(), rest.join(" ")),
+                None => (String::new(), words.join(" ")),
+            }
+        }
+        _ => (String::new(), words.join(" ")),
+    }
+}
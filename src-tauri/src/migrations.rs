@@ -0,0 +1,742 @@
+use chrono::Local;
+use rusqlite::{params, Connection};
+use std::path::{Path, PathBuf};
+
+use crate::error::{AppError, AppResult};
+
+/// A single schema step. Steps are applied in order and each one bumps
+/// `PRAGMA user_version` to its `version` inside the same transaction.
+pub struct Migration {
+    pub version: i32,
+    pub description: &'static str,
+    pub up: fn(&Connection) -> rusqlite::Result<()>,
+}
+
+/// Every schema change goes here, appended at the end with the next version.
+/// Never edit or reorder a step that has already shipped.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "initial case_table and assigned_to schema",
+        up: initial_schema,
+    },
+    Migration {
+        version: 2,
+        description: "hearings table with one row per sitting",
+        up: hearings_table,
+    },
+    Migration {
+        version: 3,
+        description: "store nature_of_case as NatureOfCase codes",
+        up: nature_of_case_codes,
+    },
+    Migration {
+        version: 4,
+        description: "case_edits log of intake corrections",
+        up: case_edits_table,
+    },
+    Migration {
+        version: 5,
+        description: "case_transfers log of mediator reassignments",
+        up: case_transfers_table,
+    },
+    Migration {
+        version: 6,
+        description: "mediators registry referenced by id from cases, hearings and transfers",
+        up: mediators_table,
+    },
+    Migration {
+        version: 7,
+        description: "hash-chained audit_log of every write",
+        up: audit_log_table,
+    },
+    Migration {
+        version: 8,
+        description: "backup_schedule settings and backup_runs history",
+        up: backup_schedule_tables,
+    },
+    Migration {
+        version: 9,
+        description: "case_search full-text index over parties, courts and notes",
+        up: case_search_index,
+    },
+    Migration {
+        version: 10,
+        description: "disposal_date, reason and remarks on case_table with a disposal_reasons list",
+        up: disposal_details,
+    },
+    Migration {
+        version: 11,
+        description: "store disposal_of_case and hearing outcomes as DisposalOfCase codes",
+        up: disposal_codes,
+    },
+    Migration {
+        version: 12,
+        description: "import_profiles of saved column mappings for legacy registers",
+        up: import_profiles_table,
+    },
+    Migration {
+        version: 13,
+        description: "centre_details with the centre name printed on reports and exports",
+        up: centre_details_table,
+    },
+    Migration {
+        version: 14,
+        description: "audit_entries and audit_head on backup_runs",
+        up: backup_run_audit_head,
+    },
+];
+
+/// Schema version written by this build of the app.
+pub fn latest_version() -> i32 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+pub fn current_version(conn: &Connection) -> rusqlite::Result<i32> {
+    conn.pragma_query_value(None, "user_version", |row| row.get(0))
+}
+
+/// Brings the database at `db_path` up to `latest_version()`.
+///
+/// A database written by a newer app is refused rather than opened, and an
+/// existing register is copied next to itself before any step is applied.
+/// Returns the path of that copy, if one was taken.
+pub fn migrate(conn: &Connection, db_path: &Path) -> AppResult<Option<PathBuf>> {
+    let current = current_version(conn)?;
+    let latest = latest_version();
+
+    if current > latest {
+        return Err(AppError::SchemaTooNew {
+            found: current,
+            supported: latest,
+        });
+    }
+
+    if current == latest {
+        return Ok(None);
+    }
+
+    let backup = if has_tables(conn)? {
+        let path = backup_path(db_path, current);
+        conn.execute("VACUUM INTO ?1", [path.to_string_lossy()])?;
+        println!("Pre-migration backup written to: {}", path.display());
+        Some(path)
+    } else {
+        None
+    };
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        println!(
+            "Applying migration {}: {}",
+            migration.version, migration.description
+        );
+        apply(conn, migration).map_err(|source| AppError::Migration {
+            version: migration.version,
+            source,
+        })?;
+    }
+
+    Ok(backup)
+}
+
+fn apply(conn: &Connection, migration: &Migration) -> rusqlite::Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    (migration.up)(&tx)?;
+    tx.pragma_update(None, "user_version", migration.version)?;
+    tx.commit()
+}
+
+fn has_tables(conn: &Connection) -> rusqlite::Result<bool> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+fn backup_path(db_path: &Path, version: i32) -> PathBuf {
+    let file_name = db_path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "cases.db".to_string());
+    let stamp = Local::now().format("%Y%m%d-%H%M%S");
+    db_path.with_file_name(format!("{}.v{}-{}.bak", file_name, version, stamp))
+}
+
+// Version 1 is the schema the app shipped with before migrations existed,
+// so it has to be a no-op on registers created by those builds.
+fn initial_schema(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS case_table (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            case_no INTEGER NOT NULL,
+            year INTEGER NOT NULL,
+            nature_of_case TEXT NOT NULL,
+            received_from TEXT NOT NULL,
+            date TEXT NOT NULL,
+            time_slot TEXT NOT NULL,
+            party1 TEXT NOT NULL,
+            party2 TEXT NOT NULL,
+            assigned_to TEXT NOT NULL,
+            ndoh_date TEXT,
+            ndoh_time TEXT,
+            disposal_of_case TEXT,
+            connected INTEGER DEFAULT 0,
+            UNIQUE (case_no, year)
+        );
+
+        CREATE TABLE IF NOT EXISTS assigned_to (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT UNIQUE NOT NULL
+        );",
+    )
+}
+
+// The last sitting of every existing case is all that survived on case_table,
+// so it becomes the first row of that case's history.
+fn hearings_table(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE hearings (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            case_id INTEGER NOT NULL REFERENCES case_table(id) ON DELETE CASCADE,
+            hearing_date TEXT NOT NULL,
+            hearing_time TEXT,
+            mediator TEXT NOT NULL,
+            outcome TEXT,
+            remarks TEXT,
+            recorded_at TEXT NOT NULL DEFAULT (datetime('now', 'localtime'))
+        );
+
+        CREATE INDEX idx_hearings_case_id ON hearings (case_id);
+
+        INSERT INTO hearings (case_id, hearing_date, hearing_time, mediator, outcome)
+        SELECT id, ndoh_date, ndoh_time, assigned_to, disposal_of_case
+        FROM case_table
+        WHERE ndoh_date IS NOT NULL;",
+    )
+}
+
+// Older builds stored whatever label the form sent. Anything that can't be
+// matched is filed as OtherCivilSuit, since a value the enum doesn't know
+// would stop every listing that reaches it.
+fn nature_of_case_codes(conn: &Connection) -> rusqlite::Result<()> {
+    let stored: Vec<String> = conn
+        .prepare("SELECT DISTINCT nature_of_case FROM case_table")?
+        .query_map([], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+
+    for text in stored {
+        match legacy_code(V3_NATURES, &text) {
+            Some(code) if code != text => {
+                conn.execute(
+                    "UPDATE case_table SET nature_of_case = ?1 WHERE nature_of_case = ?2",
+                    params![code, text],
+                )?;
+            }
+            Some(_) => {}
+            None => {
+                println!("Unrecognised nature_of_case filed as OtherCivilSuit: {:?}", text);
+                conn.execute(
+                    "UPDATE case_table SET nature_of_case = 'OtherCivilSuit' WHERE nature_of_case = ?1",
+                    [text],
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn case_edits_table(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE case_edits (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            case_id INTEGER NOT NULL REFERENCES case_table(id) ON DELETE CASCADE,
+            edited_at TEXT NOT NULL DEFAULT (datetime('now', 'localtime')),
+            edited_by TEXT NOT NULL,
+            reason TEXT NOT NULL,
+            changes TEXT NOT NULL
+        );
+
+        CREATE INDEX idx_case_edits_case_id ON case_edits (case_id);",
+    )
+}
+
+fn case_transfers_table(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE case_transfers (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            case_id INTEGER NOT NULL REFERENCES case_table(id) ON DELETE CASCADE,
+            from_mediator TEXT NOT NULL,
+            to_mediator TEXT NOT NULL,
+            transferred_on TEXT NOT NULL,
+            reason TEXT NOT NULL,
+            recorded_at TEXT NOT NULL DEFAULT (datetime('now', 'localtime'))
+        );
+
+        CREATE INDEX idx_case_transfers_case_id ON case_transfers (case_id);",
+    )
+}
+
+// Mediators used to be bare strings such as 'Sh_Surender_Singh', both in the
+// assigned_to table and copied onto every case, hearing and transfer. Each
+// distinct string becomes one mediators row; names that had already been
+// deleted from assigned_to come across as inactive so their history stays.
+fn mediators_table(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE mediators (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            display_name TEXT NOT NULL UNIQUE,
+            honorific TEXT NOT NULL DEFAULT '',
+            phone TEXT,
+            email TEXT,
+            active INTEGER NOT NULL DEFAULT 1,
+            joined_on TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now', 'localtime'))
+        );",
+    )?;
+
+    let listed: Vec<String> = conn
+        .prepare("SELECT name FROM assigned_to ORDER BY id")?
+        .query_map([], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+
+    let referenced: Vec<String> = conn
+        .prepare(
+            "SELECT assigned_to FROM case_table
+             UNION SELECT mediator FROM hearings
+             UNION SELECT from_mediator FROM case_transfers
+             UNION SELECT to_mediator FROM case_transfers",
+        )?
+        .query_map([], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+
+    let legacy = listed
+        .iter()
+        .map(|name| (name, true))
+        .chain(referenced.iter().map(|name| (name, false)));
+
+    let mut ids = Vec::new();
+    for (name, active) in legacy {
+        let (honorific, display_name) = v6_split_name(name);
+        conn.execute(
+            "INSERT OR IGNORE INTO mediators (display_name, honorific, active) VALUES (?1, ?2, ?3)",
+            params![display_name, honorific, active],
+        )?;
+        let id: i64 = conn.query_row(
+            "SELECT id FROM mediators WHERE display_name = ?1",
+            [&display_name],
+            |row| row.get(0),
+        )?;
+        ids.push((name.clone(), id));
+    }
+
+    conn.execute_batch(
+        "ALTER TABLE case_table ADD COLUMN mediator_id INTEGER REFERENCES mediators(id);
+        ALTER TABLE hearings ADD COLUMN mediator_id INTEGER REFERENCES mediators(id);
+        ALTER TABLE case_transfers ADD COLUMN from_mediator_id INTEGER REFERENCES mediators(id);
+        ALTER TABLE case_transfers ADD COLUMN to_mediator_id INTEGER REFERENCES mediators(id);",
+    )?;
+
+    for (name, id) in &ids {
+        conn.execute("UPDATE case_table SET mediator_id = ?1 WHERE assigned_to = ?2", params![id, name])?;
+        conn.execute("UPDATE hearings SET mediator_id = ?1 WHERE mediator = ?2", params![id, name])?;
+        conn.execute(
+            "UPDATE case_transfers SET from_mediator_id = ?1 WHERE from_mediator = ?2",
+            params![id, name],
+        )?;
+        conn.execute(
+            "UPDATE case_transfers SET to_mediator_id = ?1 WHERE to_mediator = ?2",
+            params![id, name],
+        )?;
+    }
+
+    conn.execute_batch(
+        "ALTER TABLE case_table DROP COLUMN assigned_to;
+        ALTER TABLE hearings DROP COLUMN mediator;
+        ALTER TABLE case_transfers DROP COLUMN from_mediator;
+        ALTER TABLE case_transfers DROP COLUMN to_mediator;
+        DROP TABLE assigned_to;
+
+        CREATE INDEX idx_case_table_mediator_id ON case_table (mediator_id);",
+    )
+}
+
+// Rows are only ever appended. The triggers stop the app (or anyone using
+// the sqlite shell casually) from rewriting history; the hash chain is what
+// catches someone who drops them first.
+fn audit_log_table(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE audit_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            recorded_at TEXT NOT NULL,
+            operator TEXT NOT NULL,
+            command TEXT NOT NULL,
+            case_id INTEGER,
+            case_no INTEGER,
+            year INTEGER,
+            before_value TEXT,
+            after_value TEXT,
+            prev_hash TEXT NOT NULL,
+            hash TEXT NOT NULL
+        );
+
+        CREATE INDEX idx_audit_log_case_id ON audit_log (case_id);
+        CREATE INDEX idx_audit_log_recorded_at ON audit_log (recorded_at);
+
+        CREATE TRIGGER audit_log_no_update BEFORE UPDATE ON audit_log
+        BEGIN
+            SELECT RAISE(ABORT, 'audit_log is append-only');
+        END;
+
+        CREATE TRIGGER audit_log_no_delete BEFORE DELETE ON audit_log
+        BEGIN
+            SELECT RAISE(ABORT, 'audit_log is append-only');
+        END;",
+    )
+}
+
+// A single settings row, created with the defaults so a register that has
+// never been configured is still backed up: at every start and once a day
+// into `backups/` next to itself.
+fn backup_schedule_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE backup_schedule (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            folder TEXT,
+            on_start INTEGER NOT NULL DEFAULT 1,
+            daily_at TEXT DEFAULT '13:00:00',
+            keep_daily INTEGER NOT NULL DEFAULT 7,
+            keep_weekly INTEGER NOT NULL DEFAULT 4,
+            keep_monthly INTEGER NOT NULL DEFAULT 12
+        );
+
+        INSERT INTO backup_schedule (id) VALUES (1);
+
+        CREATE TABLE backup_runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            started_at TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            path TEXT,
+            size_bytes INTEGER,
+            pruned INTEGER NOT NULL DEFAULT 0,
+            error TEXT
+        );
+
+        CREATE INDEX idx_backup_runs_started_at ON backup_runs (started_at);",
+    )
+}
+
+// One index row per case, keyed by case_table.id. Notes are the remarks of
+// all the case's hearings run together, so the hearings triggers rebuild
+// them whenever a hearing changes.
+fn case_search_index(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE VIRTUAL TABLE case_search USING fts5(
+            case_no, year, party1, party2, received_from, notes,
+            tokenize = 'unicode61 remove_diacritics 2'
+        );
+
+        INSERT INTO case_search (rowid, case_no, year, party1, party2, received_from, notes)
+        SELECT c.id, c.case_no, c.year, c.party1, c.party2, c.received_from,
+               (SELECT group_concat(h.remarks, ' ') FROM hearings h WHERE h.case_id = c.id)
+        FROM case_table c;
+
+        CREATE TRIGGER case_search_insert AFTER INSERT ON case_table
+        BEGIN
+            INSERT INTO case_search (rowid, case_no, year, party1, party2, received_from, notes)
+            VALUES (new.id, new.case_no, new.year, new.party1, new.party2, new.received_from, NULL);
+        END;
+
+        CREATE TRIGGER case_search_update
+        AFTER UPDATE OF case_no, year, party1, party2, received_from ON case_table
+        BEGIN
+            UPDATE case_search
+            SET case_no = new.case_no, year = new.year, party1 = new.party1,
+                party2 = new.party2, received_from = new.received_from
+            WHERE rowid = new.id;
+        END;
+
+        CREATE TRIGGER case_search_delete AFTER DELETE ON case_table
+        BEGIN
+            DELETE FROM case_search WHERE rowid = old.id;
+        END;
+
+        CREATE TRIGGER case_search_notes_insert AFTER INSERT ON hearings
+        BEGIN
+            UPDATE case_search
+            SET notes = (SELECT group_concat(remarks, ' ') FROM hearings WHERE case_id = new.case_id)
+            WHERE rowid = new.case_id;
+        END;
+
+        CREATE TRIGGER case_search_notes_update AFTER UPDATE OF remarks, case_id ON hearings
+        BEGIN
+            UPDATE case_search
+            SET notes = (SELECT group_concat(remarks, ' ') FROM hearings WHERE case_id = old.case_id)
+            WHERE rowid = old.case_id;
+            UPDATE case_search
+            SET notes = (SELECT group_concat(remarks, ' ') FROM hearings WHERE case_id = new.case_id)
+            WHERE rowid = new.case_id;
+        END;
+
+        CREATE TRIGGER case_search_notes_delete AFTER DELETE ON hearings
+        BEGIN
+            UPDATE case_search
+            SET notes = (SELECT group_concat(remarks, ' ') FROM hearings WHERE case_id = old.case_id)
+            WHERE rowid = old.case_id;
+        END;",
+    )
+}
+
+// Cases disposed of before this version get the date of the first hearing
+// that recorded a final outcome; their reason and remarks stay empty. The
+// reasons list starts with the ones the centre uses most and can be edited.
+fn disposal_details(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE disposal_reasons (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            label TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            active INTEGER NOT NULL DEFAULT 1,
+            created_at TEXT NOT NULL DEFAULT (datetime('now', 'localtime'))
+        );
+
+        INSERT INTO disposal_reasons (label) VALUES
+            ('Settlement reached'),
+            ('No consensus'),
+            ('Party absent'),
+            ('Referred back to court'),
+            ('Withdrawn by parties');
+
+        ALTER TABLE case_table ADD COLUMN disposal_date TEXT;
+        ALTER TABLE case_table ADD COLUMN disposal_reason_id INTEGER REFERENCES disposal_reasons(id);
+        ALTER TABLE case_table ADD COLUMN disposal_remarks TEXT;
+
+        UPDATE case_table
+        SET disposal_date = (
+            SELECT MIN(h.hearing_date) FROM hearings h
+            WHERE h.case_id = case_table.id
+              AND h.outcome IN ('Settled', 'NotSettled', 'NotFitForMediation')
+        )
+        WHERE disposal_of_case IN ('Settled', 'NotSettled', 'NotFitForMediation');
+
+        CREATE INDEX idx_case_table_disposal_date ON case_table (disposal_date);",
+    )
+}
+
+// Older builds and hand-edited registers hold outcomes such as "Not Settled"
+// or "settled". Blank ones become NULL, i.e. nothing recorded yet, and so do
+// ones that can't be matched; their text is kept in case_edits, or in the
+// remarks of the hearing. Cases whose spelling kept version 10 from dating
+// their disposal are dated here.
+fn disposal_codes(conn: &Connection) -> rusqlite::Result<()> {
+    for (table, column) in [("case_table", "disposal_of_case"), ("hearings", "outcome")] {
+        conn.execute(
+            &format!("UPDATE {table} SET {column} = NULL WHERE TRIM({column}) = ''"),
+            [],
+        )?;
+
+        let stored: Vec<String> = conn
+            .prepare(&format!("SELECT DISTINCT {column} FROM {table} WHERE {column} IS NOT NULL"))?
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+
+        for text in stored {
+            match legacy_code(V11_DISPOSALS, &text) {
+                Some(code) if code != text => {
+                    conn.execute(
+                        &format!("UPDATE {table} SET {column} = ?1 WHERE {column} = ?2"),
+                        params![code, text],
+                    )?;
+                }
+                Some(_) => {}
+                None => {
+                    println!("Unrecognised {}.{} cleared: {:?}", table, column, text);
+                    clear_unrecognised_outcome(conn, table, &text)?;
+                }
+            }
+        }
+    }
+
+    let finals = sql_list(V11_FINAL_DISPOSALS);
+    conn.execute(
+        &format!(
+            "UPDATE case_table
+             SET disposal_date = (
+                 SELECT MIN(h.hearing_date) FROM hearings h
+                 WHERE h.case_id = case_table.id AND h.outcome IN ({finals})
+             )
+             WHERE disposal_date IS NULL AND disposal_of_case IN ({finals})"
+        ),
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn clear_unrecognised_outcome(conn: &Connection, table: &str, text: &str) -> rusqlite::Result<()> {
+    if table == "case_table" {
+        conn.execute(
+            "INSERT INTO case_edits (case_id, edited_by, reason, changes)
+             SELECT id, 'migration', 'Disposal not recognised by this version',
+                    json_object('disposal_of_case', json_object('from', disposal_of_case, 'to', NULL))
+             FROM case_table WHERE disposal_of_case = ?1",
+            [text],
+        )?;
+        conn.execute("UPDATE case_table SET disposal_of_case = NULL WHERE disposal_of_case = ?1", [text])?;
+    } else {
+        conn.execute(
+            "UPDATE hearings
+             SET remarks = TRIM(COALESCE(remarks, '') || ' (recorded outcome: ' || outcome || ')'),
+                 outcome = NULL
+             WHERE outcome = ?1",
+            [text],
+        )?;
+    }
+    Ok(())
+}
+
+// A profile is a column mapping saved under a name, kept as the JSON of an
+// ImportMapping so adding a mapping option never needs a schema change.
+fn import_profiles_table(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE import_profiles (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            mapping TEXT NOT NULL,
+            updated_at TEXT NOT NULL DEFAULT (datetime('now', 'localtime'))
+        );",
+    )
+}
+
+// One row, like backup_schedule. A new register starts with a generic name
+// until the centre sets its own on the setup screen or in its roster file;
+// a register already in use keeps the name its reports have always carried.
+fn centre_details_table(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE centre_details (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            name TEXT NOT NULL DEFAULT 'Mediation Centre'
+        );
+
+        INSERT INTO centre_details (id) VALUES (1);
+
+        UPDATE centre_details SET name = 'Delhi Mediation Centre, Rohini Courts, Delhi'
+        WHERE EXISTS (SELECT 1 FROM case_table) OR EXISTS (SELECT 1 FROM mediators);",
+    )
+}
+
+// Runs before this version recorded nothing about the audit log, so they
+// leave both columns NULL and play no part in verifying it.
+fn backup_run_audit_head(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE backup_runs ADD COLUMN audit_entries INTEGER;
+        ALTER TABLE backup_runs ADD COLUMN audit_head TEXT;",
+    )
+}
+
+// Steps that translate old free text carry their own copy of the spellings
+// they accepted when they shipped, so later changes to the enums or to
+// mediators.rs never change what an old step does to an old register.
+
+/// Natures of case as version 3 knew them: each code with its label and
+/// any older spellings.
+const V3_NATURES: &[(&str, &[&str])] = &[
+    ("CivilRecovery", &["Civil Recovery"]),
+    ("CivilPartition", &["Civil Partition"]),
+    ("CivilInjunction", &["Civil Injunction"]),
+    ("CivilPossession", &["Civil Possession"]),
+    ("CivilProbate", &["Civil Probate"]),
+    ("Arbitration", &["Arbitration"]),
+    ("CivilAppeal", &["Civil Appeal"]),
+    ("CivilExecution", &["Civil Execution"]),
+    ("OtherCivilSuit", &["Other Civil Suit"]),
+    ("CriminalMatter", &["Criminal Matter"]),
+    ("CriminalRevision", &["Criminal Revision"]),
+    ("CriminalAppeal", &["Criminal Appeal"]),
+    ("PetitionForDivorce", &["Petition For Divorce"]),
+    ("PetitionForMaintenance", &["Petition For Maintenance"]),
+    ("PetitionForCustody", &["Petition For Custody"]),
+    (
+        "PetitionForDomesticViolenceAct",
+        &["Petition For Domestic Violence Act", "Petition For Domestic Voilence Act"],
+    ),
+    ("CawCellN", &["CAW Cell (N)"]),
+    ("CawCellOD", &["CAW Cell (OD)"]),
+    ("PetitionForRecoveryOfRent", &["Petition For Recovery Of Rent"]),
+    (
+        "PetitionUs138OfNiActAndPasaAct",
+        &["Petition U/s 138 of NI Act & PASA Act", "PetitionUs138OfNiActNPasaAct"],
+    ),
+    ("PetitionUnderElectricityAct", &["Petition Under Electricity Act"]),
+    ("MactCase", &["MACT Case"]),
+];
+
+/// Outcomes as version 11 knew them, with their labels and aliases.
+const V11_DISPOSALS: &[(&str, &[&str])] = &[
+    ("Settled", &["Settled"]),
+    ("PartiallySettled", &["Partially Settled", "Part Settled"]),
+    ("NotSettled", &["Not Settled"]),
+    ("NotFitForMediation", &["Not Fit for Mediation", "Not Fit"]),
+    ("Withdrawn", &["Withdrawn"]),
+    ("ReturnedUnserved", &["Returned Unserved", "Unserved"]),
+    ("ReferredToLokAdalat", &["Referred to Lok Adalat", "Lok Adalat"]),
+    ("NonAppearance", &["Non-appearance of Parties", "Non Appearance"]),
+    ("Pending", &["Pending", "Adjourned"]),
+];
+
+/// The outcomes version 11 treated as final.
+const V11_FINAL_DISPOSALS: &[&str] = &[
+    "Settled",
+    "PartiallySettled",
+    "NotSettled",
+    "NotFitForMediation",
+    "Withdrawn",
+    "ReturnedUnserved",
+    "ReferredToLokAdalat",
+];
+
+/// Titles version 6 split off the front of a legacy mediator name.
+const V6_HONORIFICS: &[&str] = &["Sh", "Shri", "Smt", "Ms", "Mr", "Mrs", "Dr"];
+
+/// The code in `table` that `text` names, ignoring case, spacing and
+/// punctuation.
+fn legacy_code(table: &[(&'static str, &[&str])], text: &str) -> Option<&'static str> {
+    let key = legacy_key(text);
+    table
+        .iter()
+        .find(|(code, spellings)| legacy_key(code) == key || spellings.iter().any(|s| legacy_key(s) == key))
+        .map(|(code, _)| *code)
+}
+
+fn legacy_key(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// 'Sh_Surender_Singh' as version 6 read it: ("Sh", "Surender Singh").
+fn v6_split_name(name: &str) -> (String, String) {
+    let words: Vec<&str> = name
+        .split(|c: char| c == '_' || c.is_whitespace())
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    match words.split_first() {
+        Some((first, rest)) if !rest.is_empty() => {
+            let title = first.trim_end_matches('.');
+            match V6_HONORIFICS.iter().find(|h| h.eq_ignore_ascii_case(title)) {
+                Some(honorific) => (honorific.to_string(), rest.join(" ")),
+                None => (String::new(), words.join(" ")),
+            }
+        }
+        _ => (String::new(), words.join(" ")),
+    }
+}
+
+/// Codes quoted for an SQL `IN (...)`. They are fixed identifiers, so
+/// nothing needs escaping.
+fn sql_list(codes: &[&str]) -> String {
+    codes.iter().map(|c| format!("'{}'", c)).collect::<Vec<_>>().join(", ")
+}
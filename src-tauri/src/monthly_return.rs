@@ -0,0 +1,582 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use chrono::{Local, Months, NaiveDate};
+use rust_xlsxwriter::{Workbook, Worksheet, XlsxError};
+use serde::Serialize;
+
+use crate::database_init::{parse_date_column, Db, DisposalOfCase, NatureOfCase};
+use crate::error::{AppError, AppResult};
+use crate::export::Formats;
+use crate::report::{format_date, Column, Report};
+
+/// The heads a final disposal is shown under in the monthly return.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ReturnHead {
+    Settled,
+    NotSettled,
+    NotFit,
+    Returned,
+}
+
+impl ReturnHead {
+    /// Partial settlements count as settled. Withdrawals, unserved notices
+    /// and referrals to the Lok Adalat all send the case back to the court
+    /// without a mediated result, so they are returned.
+    pub fn for_disposal(disposal: DisposalOfCase) -> Option<Self> {
+        match disposal {
+            DisposalOfCase::Settled | DisposalOfCase::PartiallySettled => Some(ReturnHead::Settled),
+            DisposalOfCase::NotSettled => Some(ReturnHead::NotSettled),
+            DisposalOfCase::NotFitForMediation => Some(ReturnHead::NotFit),
+            DisposalOfCase::Withdrawn | DisposalOfCase::ReturnedUnserved | DisposalOfCase::ReferredToLokAdalat => {
+                Some(ReturnHead::Returned)
+            }
+            DisposalOfCase::NonAppearance | DisposalOfCase::Pending => None,
+        }
+    }
+}
+
+/// One line of the return. `opening + received - disposed == closing`.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct ReturnCounts {
+    /// Pending on the first day of the month, before anything came in.
+    pub opening: usize,
+    pub received: usize,
+    pub settled: usize,
+    pub not_settled: usize,
+    pub not_fit: usize,
+    pub returned: usize,
+    /// The four heads together.
+    pub disposed: usize,
+    /// Pending at the end of the last day of the month.
+    pub closing: usize,
+}
+
+/// Column titles for `ReturnCounts::cells`, as the statement prints them.
+pub const RETURN_COLUMNS: [&str; 9] = [
+    "Opening Balance",
+    "Received",
+    "Total",
+    "Settled",
+    "Not Settled",
+    "Not Fit",
+    "Returned",
+    "Total Disposed",
+    "Closing Balance",
+];
+
+impl ReturnCounts {
+    pub fn cells(&self) -> [usize; 9] {
+        [
+            self.opening,
+            self.received,
+            self.opening + self.received,
+            self.settled,
+            self.not_settled,
+            self.not_fit,
+            self.returned,
+            self.disposed,
+            self.closing,
+        ]
+    }
+
+    fn is_empty(&self) -> bool {
+        self.cells().iter().all(|&n| n == 0)
+    }
+
+    fn add(&mut self, case: &ReturnCase, start: NaiveDate) {
+        match case.disposed {
+            Some((_, on)) if on < start => return,
+            Some((head, _)) => {
+                self.disposed += 1;
+                match head {
+                    ReturnHead::Settled => self.settled += 1,
+                    ReturnHead::NotSettled => self.not_settled += 1,
+                    ReturnHead::NotFit => self.not_fit += 1,
+                    ReturnHead::Returned => self.returned += 1,
+                }
+            }
+            None => self.closing += 1,
+        }
+
+        if case.date < start {
+            self.opening += 1;
+        } else {
+            self.received += 1;
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct NatureReturn {
+    pub nature_of_case: NatureOfCase,
+    pub label: &'static str,
+    pub counts: ReturnCounts,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CourtReturn {
+    pub received_from: String,
+    pub counts: ReturnCounts,
+}
+
+/// The statement of cases for one calendar month.
+#[derive(Debug, Serialize)]
+pub struct MonthlyReturn {
+    pub year: i32,
+    pub month: u32,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub totals: ReturnCounts,
+    /// Natures of case with something to show, in the usual order.
+    pub by_nature: Vec<NatureReturn>,
+    /// Referring courts with something to show, by name. Spellings that
+    /// differ only in case or surrounding spaces are one court.
+    pub by_court: Vec<CourtReturn>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReturnFormat {
+    Pdf,
+    Xlsx,
+}
+
+impl ReturnFormat {
+    pub fn for_path(path: &Path) -> Option<Self> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("pdf") => Some(ReturnFormat::Pdf),
+            Some(ext) if ext.eq_ignore_ascii_case("xlsx") => Some(ReturnFormat::Xlsx),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReturnFileInfo {
+    pub path: String,
+    pub format: ReturnFormat,
+}
+
+/// What the return needs to know about a case registered by the end of the
+/// month. Those disposed of before it began play no part.
+struct ReturnCase {
+    nature_of_case: NatureOfCase,
+    received_from: String,
+    date: NaiveDate,
+    /// The head and day of a final disposal made by the end of the month.
+    disposed: Option<(ReturnHead, NaiveDate)>,
+}
+
+impl Db {
+    /// Counts for the return of `month` in `year`, worked out from what is
+    /// on the register now.
+    ///
+    /// A case is pending from its intake date until the day it is finally
+    /// disposed of. Disposals recorded before disposal dates were kept are
+    /// dated by the sitting they were recorded at, and a disposal dated
+    /// before intake counts as made on the intake date, so every line of the
+    /// return balances.
+    pub fn monthly_return(&self, year: i32, month: u32) -> AppResult<MonthlyReturn> {
+        let invalid_month = || AppError::InvalidValue {
+            field: "month",
+            value: format!("{}-{:02}", year, month),
+        };
+        let start = NaiveDate::from_ymd_opt(year, month, 1).ok_or_else(invalid_month)?;
+        let end = start
+            .checked_add_months(Months::new(1))
+            .and_then(|next| next.pred_opt())
+            .ok_or_else(invalid_month)?;
+
+        let conn = self.reader()?;
+        let mut stmt = conn.prepare(
+            "SELECT nature_of_case, TRIM(received_from), date, disposal_of_case,
+                    MAX(COALESCE(disposal_date, ndoh_date, date), date)
+             FROM case_table
+             WHERE date <= ?1",
+        )?;
+        let cases = stmt
+            .query_map([end.to_string()], |row| {
+                let disposal: Option<DisposalOfCase> = row.get(3)?;
+                let disposed_on = parse_date_column(row, 4)?;
+                Ok(ReturnCase {
+                    nature_of_case: row.get(0)?,
+                    received_from: row.get(1)?,
+                    date: parse_date_column(row, 2)?,
+                    disposed: disposal
+                        .and_then(ReturnHead::for_disposal)
+                        .map(|head| (head, disposed_on))
+                        .filter(|(_, on)| *on <= end),
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut totals = ReturnCounts::default();
+        let mut by_nature: BTreeMap<usize, ReturnCounts> = BTreeMap::new();
+        let mut by_court: BTreeMap<String, (String, ReturnCounts)> = BTreeMap::new();
+
+        for case in &cases {
+            totals.add(case, start);
+
+            let position = NatureOfCase::ALL
+                .iter()
+                .position(|n| *n == case.nature_of_case)
+                .unwrap_or_default();
+            by_nature.entry(position).or_default().add(case, start);
+
+            let court = if case.received_from.is_empty() { "Not recorded" } else { &case.received_from };
+            by_court
+                .entry(court.to_lowercase())
+                .or_insert_with(|| (court.to_string(), ReturnCounts::default()))
+                .1
+                .add(case, start);
+        }
+
+        Ok(MonthlyReturn {
+            year,
+            month,
+            period_start: start,
+            period_end: end,
+            totals,
+            by_nature: by_nature
+                .into_iter()
+                .filter(|(_, counts)| !counts.is_empty())
+                .map(|(position, counts)| {
+                    let nature_of_case = NatureOfCase::ALL[position];
+                    NatureReturn { nature_of_case, label: nature_of_case.label(), counts }
+                })
+                .collect(),
+            by_court: by_court
+                .into_values()
+                .filter(|(_, counts)| !counts.is_empty())
+                .map(|(received_from, counts)| CourtReturn { received_from, counts })
+                .collect(),
+        })
+    }
+
+    /// Writes the return for `month` in `year` to `path`, as a PDF or an
+    /// XLSX workbook going by its extension.
+    pub fn write_monthly_return(&self, year: i32, month: u32, path: &Path) -> AppResult<ReturnFileInfo> {
+        let format = ReturnFormat::for_path(path).ok_or_else(|| AppError::InvalidValue {
+            field: "path",
+            value: path.display().to_string(),
+        })?;
+        let statement = self.monthly_return(year, month)?;
+        let centre = self.centre_name()?;
+        let prepared_by = self.operator();
+
+        match format {
+            ReturnFormat::Pdf => {
+                let pages = return_report(&centre, &statement, &prepared_by).write(path)?;
+                println!("Wrote {} ({} page(s))", path.display(), pages);
+            }
+            ReturnFormat::Xlsx => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                write_return_workbook(&centre, &statement, &prepared_by, path).map_err(|e| AppError::ExportFailed {
+                    path: path.display().to_string(),
+                    detail: e.to_string(),
+                })?;
+                println!("Wrote {}", path.display());
+            }
+        }
+
+        Ok(ReturnFileInfo {
+            path: path.display().to_string(),
+            format,
+        })
+    }
+}
+
+impl MonthlyReturn {
+    fn title(&self) -> String {
+        format!("Monthly Return for {}", self.period_start.format("%B %Y"))
+    }
+
+    fn period(&self) -> String {
+        format!(
+            "Cases referred for mediation, {} to {}.",
+            format_date(self.period_start),
+            format_date(self.period_end)
+        )
+    }
+
+    /// The three tables of the statement, the breakdowns each ending in a
+    /// total row where there is more than one line.
+    fn tables(&self) -> Vec<ReturnTable<'_>> {
+        vec![
+            ReturnTable {
+                heading: "Abstract",
+                first: "",
+                rows: vec![("All cases".to_string(), &self.totals)],
+            },
+            ReturnTable {
+                heading: "By Nature of Case",
+                first: "Nature of Case",
+                rows: self.with_total(self.by_nature.iter().map(|n| (n.label.to_string(), &n.counts)).collect()),
+            },
+            ReturnTable {
+                heading: "By Referring Court",
+                first: "Referring Court",
+                rows: self.with_total(self.by_court.iter().map(|c| (c.received_from.clone(), &c.counts)).collect()),
+            },
+        ]
+    }
+
+    fn with_total<'a>(&'a self, mut rows: Vec<(String, &'a ReturnCounts)>) -> Vec<(String, &'a ReturnCounts)> {
+        if rows.len() > 1 {
+            rows.push(("Total".to_string(), &self.totals));
+        }
+        rows
+    }
+}
+
+struct ReturnTable<'a> {
+    heading: &'static str,
+    /// Title of the label column.
+    first: &'static str,
+    rows: Vec<(String, &'a ReturnCounts)>,
+}
+
+const HEADS_NOTE: &str = "Settled includes partial settlements. Returned covers cases withdrawn, \
+     returned unserved or referred to the Lok Adalat.";
+
+fn return_report(centre: &str, statement: &MonthlyReturn, prepared_by: &str) -> Report {
+    let mut report = Report::new(centre, &statement.title(), prepared_by, Local::now().naive_local());
+    report.paragraph(&statement.period(), 8.0, false);
+    report.paragraph(HEADS_NOTE, 8.0, false);
+
+    for ReturnTable { heading, first, rows } in statement.tables() {
+        report.heading(heading);
+        if rows.is_empty() {
+            report.paragraph("No cases.", 9.0, false);
+            continue;
+        }
+        let rows: Vec<Vec<String>> = rows
+            .into_iter()
+            .map(|(label, counts)| {
+                let mut cells = vec![label];
+                cells.extend(counts.cells().iter().map(usize::to_string));
+                cells
+            })
+            .collect();
+        let mut columns = vec![Column::text(first, 40.0)];
+        columns.extend(RETURN_COLUMNS.iter().map(|title| Column::count(*title, 14.0)));
+        report.table(&columns, &rows);
+    }
+
+    report.signatures(&[("Prepared by", prepared_by), ("Coordinator", "")]);
+    report
+}
+
+/// One "Return" sheet laid out like the PDF.
+fn write_return_workbook(
+    centre: &str,
+    statement: &MonthlyReturn,
+    prepared_by: &str,
+    path: &Path,
+) -> Result<(), XlsxError> {
+    let formats = Formats::new();
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet();
+    sheet.set_name("Return")?;
+    sheet.set_column_width(0, 36)?;
+    for col in 1..=RETURN_COLUMNS.len() as u16 {
+        sheet.set_column_width(col, 14)?;
+    }
+
+    sheet.write_string_with_format(0, 0, centre, &formats.title)?;
+    sheet.write_string_with_format(1, 0, statement.title(), &formats.title)?;
+    sheet.write_string(2, 0, statement.period())?;
+    sheet.write_string(3, 0, HEADS_NOTE)?;
+    sheet.write_string(
+        4,
+        0,
+        format!("Generated {} by {}", Local::now().format("%d.%m.%Y %H:%M"), prepared_by),
+    )?;
+
+    let mut row = 5;
+    for ReturnTable { heading, first, rows } in statement.tables() {
+        row += 1;
+        sheet.write_string_with_format(row, 0, heading, &formats.title)?;
+        row += 1;
+        write_return_table(sheet, &mut row, first, &rows, &formats)?;
+    }
+
+    workbook.save(path)
+}
+
+fn write_return_table(
+    sheet: &mut Worksheet,
+    row: &mut u32,
+    first: &str,
+    rows: &[(String, &ReturnCounts)],
+    formats: &Formats,
+) -> Result<(), XlsxError> {
+    sheet.write_string_with_format(*row, 0, first, &formats.header)?;
+    for (col, title) in RETURN_COLUMNS.iter().enumerate() {
+        sheet.write_string_with_format(*row, col as u16 + 1, *title, &formats.header)?;
+    }
+    for (label, counts) in rows {
+        *row += 1;
+        sheet.write_string(*row, 0, label)?;
+        for (col, count) in counts.cells().into_iter().enumerate() {
+            sheet.write_number(*row, col as u16 + 1, count as u32)?;
+        }
+    }
+    *row += 1;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::params;
+
+    use super::*;
+    use crate::test_support::{intake, register, ScratchDir};
+
+    struct Dated {
+        case_no: i32,
+        nature_of_case: NatureOfCase,
+        received_from: &'static str,
+        date: &'static str,
+        disposal: Option<DisposalOfCase>,
+        disposal_date: Option<&'static str>,
+        ndoh_date: Option<&'static str>,
+    }
+
+    fn dated(case_no: i32, nature_of_case: NatureOfCase, received_from: &'static str, date: &'static str) -> Dated {
+        Dated {
+            case_no,
+            nature_of_case,
+            received_from,
+            date,
+            disposal: None,
+            disposal_date: None,
+            ndoh_date: None,
+        }
+    }
+
+    fn disposed(
+        case: Dated,
+        disposal: DisposalOfCase,
+        disposal_date: Option<&'static str>,
+        ndoh_date: &'static str,
+    ) -> Dated {
+        Dated { disposal: Some(disposal), disposal_date, ndoh_date: Some(ndoh_date), ..case }
+    }
+
+    // Cases are taken in today, so each is back-dated afterwards.
+    fn register_with(dir: &ScratchDir, cases: Vec<Dated>) -> Db {
+        let db = register(dir);
+        for case in cases {
+            db.insert_case(intake(case.case_no, 2024)).unwrap();
+            db.writer()
+                .execute(
+                    "UPDATE case_table
+                     SET nature_of_case = ?2, received_from = ?3, date = ?4,
+                         disposal_of_case = ?5, disposal_date = ?6, ndoh_date = ?7
+                     WHERE case_no = ?1",
+                    params![
+                        case.case_no,
+                        case.nature_of_case,
+                        case.received_from,
+                        case.date,
+                        case.disposal.map(|d| d.code()),
+                        case.disposal_date,
+                        case.ndoh_date,
+                    ],
+                )
+                .unwrap();
+        }
+        db
+    }
+
+    fn balances(counts: &ReturnCounts) -> bool {
+        counts.opening + counts.received == counts.disposed + counts.closing
+            && counts.disposed == counts.settled + counts.not_settled + counts.not_fit + counts.returned
+    }
+
+    #[test]
+    fn march_return_counts_each_case_under_the_right_head() {
+        use DisposalOfCase::*;
+        use NatureOfCase::{MactCase, OtherCivilSuit};
+
+        let dir = ScratchDir::new("monthly-return");
+        let db = register_with(
+            &dir,
+            vec![
+                // Taken in before March: still pending, disposed of in
+                // February, and disposed of in March.
+                dated(1, MactCase, "Court", "2024-02-10"),
+                disposed(dated(2, MactCase, "Court", "2024-02-05"), Settled, Some("2024-02-20"), "2024-02-20"),
+                disposed(dated(3, MactCase, "Court", "2024-02-15"), NotSettled, Some("2024-03-10"), "2024-03-10"),
+                // Taken in during March: withdrawn with only its last sitting
+                // to date it, disposed of in April, not final, and settled on
+                // the last day of the month.
+                disposed(dated(4, MactCase, "MACT Court", "2024-03-05"), Withdrawn, None, "2024-03-25"),
+                disposed(dated(5, OtherCivilSuit, "Court", "2024-03-12"), Settled, Some("2024-04-02"), "2024-04-02"),
+                disposed(dated(6, OtherCivilSuit, " mact court ", "2024-03-20"), NonAppearance, None, "2024-03-28"),
+                disposed(
+                    dated(8, OtherCivilSuit, "Court", "2024-03-01"),
+                    PartiallySettled,
+                    Some("2024-03-31"),
+                    "2024-03-31",
+                ),
+                // Taken in after March.
+                dated(7, MactCase, "Court", "2024-04-01"),
+            ],
+        );
+
+        let statement = db.monthly_return(2024, 3).unwrap();
+
+        assert_eq!(
+            (statement.period_start.to_string(), statement.period_end.to_string()),
+            ("2024-03-01".to_string(), "2024-03-31".to_string())
+        );
+        assert_eq!(statement.totals.cells(), [2, 4, 6, 1, 1, 0, 1, 3, 3]);
+        assert!(balances(&statement.totals));
+
+        // Natures come in the order of `NatureOfCase::ALL`, courts by name.
+        let by_nature: Vec<(NatureOfCase, [usize; 9])> =
+            statement.by_nature.iter().map(|n| (n.nature_of_case, n.counts.cells())).collect();
+        assert_eq!(
+            by_nature,
+            [(OtherCivilSuit, [0, 3, 3, 1, 0, 0, 0, 1, 2]), (MactCase, [2, 1, 3, 0, 1, 0, 1, 2, 1])]
+        );
+        assert!(statement.by_nature.iter().all(|n| balances(&n.counts)));
+
+        let by_court: Vec<(&str, [usize; 9])> =
+            statement.by_court.iter().map(|c| (c.received_from.as_str(), c.counts.cells())).collect();
+        assert_eq!(
+            by_court,
+            [("Court", [2, 2, 4, 1, 1, 0, 0, 2, 2]), ("MACT Court", [0, 2, 2, 0, 0, 0, 1, 1, 1])]
+        );
+        assert!(statement.by_court.iter().all(|c| balances(&c.counts)));
+    }
+
+    #[test]
+    fn next_month_opens_with_this_months_closing_balance() {
+        let dir = ScratchDir::new("monthly-return-carry");
+        let db = register_with(
+            &dir,
+            vec![
+                dated(1, NatureOfCase::MactCase, "Court", "2024-02-10"),
+                disposed(
+                    dated(2, NatureOfCase::MactCase, "Court", "2024-03-05"),
+                    DisposalOfCase::Settled,
+                    Some("2024-04-02"),
+                    "2024-04-02",
+                ),
+            ],
+        );
+
+        let march = db.monthly_return(2024, 3).unwrap();
+        let april = db.monthly_return(2024, 4).unwrap();
+
+        assert_eq!(april.totals.opening, march.totals.closing);
+        assert_eq!(april.totals.cells(), [2, 0, 2, 1, 0, 0, 0, 1, 1]);
+    }
+}
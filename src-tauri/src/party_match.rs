@@ -0,0 +1,354 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::database_init::{parse_date_column, parse_optional_date_column, Db, DisposalOfCase};
+use crate::error::AppResult;
+use crate::mediators::honorific;
+
+/// Score at or above which two names are taken to be the same party.
+pub const DEFAULT_MATCH_THRESHOLD: f64 = 0.8;
+
+/// Most open cases reported per party when a new case is registered.
+const OPEN_MATCH_LIMIT: usize = 10;
+
+/// Words after which the rest of the name describes someone else
+/// ("Ramesh s/o Suresh"). Slashes are gone by the time these are checked,
+/// so "s/o" arrives as "s" "o" and is caught by `relation_at`.
+const RELATIONS: &[&str] = &["so", "do", "wo", "ho", "alias", "urf", "through", "thr"];
+
+/// Spellings of the same given name, mapped to one form before comparing.
+const NAME_VARIANTS: &[(&[&str], &str)] = &[
+    (&["mohd", "md", "mohammad", "mohammed", "muhammad", "muhammed", "mohamad", "mohamed", "mohmmad"], "mohammad"),
+    (&["abdul", "abdool", "abdhul"], "abdul"),
+    (&["kr", "kumaar"], "kumar"),
+    (&["pd", "prashad", "parsad"], "prasad"),
+    (&["singh", "sing", "sinh"], "singh"),
+];
+
+#[derive(Debug, Serialize)]
+pub struct SimilarParty {
+    pub case_id: i32,
+    pub case_no: i32,
+    pub year: i32,
+    /// The name as it was entered on that case.
+    pub party: String,
+    /// "party1" or "party2".
+    pub role: &'static str,
+    /// 1.0 for names that normalise to the same thing, down to 0.0.
+    pub score: f64,
+    /// Still in mediation: no disposal recorded, or only an interim one.
+    pub open: bool,
+    pub date: NaiveDate,
+    pub ndoh_date: Option<NaiveDate>,
+}
+
+/// What `insert_case` hands back: the new case's id and any open cases its
+/// parties already appear in.
+#[derive(Debug, Serialize)]
+pub struct RegisteredCase {
+    pub id: usize,
+    pub open_matches: Vec<SimilarParty>,
+}
+
+impl Db {
+    /// Parties on any case whose names score at least `threshold` against
+    /// `name`, best first. With `open_only`, disposed cases are skipped.
+    pub fn find_similar_parties(
+        &self,
+        name: &str,
+        threshold: f64,
+        open_only: bool,
+        limit: usize,
+    ) -> AppResult<Vec<SimilarParty>> {
+        let wanted = PartyName::new(name);
+        if wanted.tokens.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let conn = self.reader()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, case_no, year, party1, party2, date, ndoh_date, disposal_of_case
+             FROM case_table
+             WHERE ?1 = 0 OR disposal_of_case IS NULL OR disposal_of_case IN ({})",
+            DisposalOfCase::sql_codes(false)
+        ))?;
+
+        let mut rows = stmt.query([open_only])?;
+        let mut found = Vec::new();
+
+        while let Some(row) = rows.next()? {
+            let disposal: Option<DisposalOfCase> = row.get(7)?;
+            let open = disposal.is_none_or(|d| !d.is_final());
+
+            for (role, idx) in [("party1", 3), ("party2", 4)] {
+                let party: String = row.get(idx)?;
+                let score = wanted.similarity(&PartyName::new(&party));
+                if score >= threshold {
+                    found.push(SimilarParty {
+                        case_id: row.get(0)?,
+                        case_no: row.get(1)?,
+                        year: row.get(2)?,
+                        party,
+                        role,
+                        score,
+                        open,
+                        date: parse_date_column(row, 5)?,
+                        ndoh_date: parse_optional_date_column(row, 6)?,
+                    });
+                }
+            }
+        }
+
+        found.sort_by(|a, b| b.score.total_cmp(&a.score).then(b.date.cmp(&a.date)));
+        found.truncate(limit);
+        Ok(found)
+    }
+
+    /// Other open cases either party of case `id` seems to be in already.
+    /// Checked after registering a case so the clerk can flag a repeat
+    /// filing or link the two matters.
+    pub fn open_cases_for_parties(&self, id: i32, party1: &str, party2: &str) -> AppResult<Vec<SimilarParty>> {
+        let mut found = Vec::new();
+        for party in [party1, party2] {
+            found.extend(
+                self.find_similar_parties(party, DEFAULT_MATCH_THRESHOLD, true, OPEN_MATCH_LIMIT)?
+                    .into_iter()
+                    .filter(|m| m.case_id != id),
+            );
+        }
+        Ok(found)
+    }
+}
+
+/// A party name reduced to what identifies the person: lower case, no
+/// punctuation or titles, common spelling variants merged, and each word
+/// also kept as a rough phonetic key for comparing romanised Hindi names.
+#[derive(Debug)]
+pub struct PartyName {
+    tokens: Vec<String>,
+    keys: Vec<String>,
+}
+
+impl PartyName {
+    pub fn new(raw: &str) -> Self {
+        let words: Vec<String> = raw
+            .to_lowercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        let mut tokens = Vec::new();
+        let mut i = 0;
+        while i < words.len() {
+            if relation_at(&words, i) {
+                break;
+            }
+            // A title says nothing about who the party is, but only counts
+            // as one while no name has been seen yet, so a party actually
+            // called "Dr" or "Ms" isn't emptied out. "M/s" (messrs) arrives
+            // as "m" "s" once the slash is gone; a lone "M" or "S" is an
+            // initial and is kept.
+            if tokens.is_empty() && i + 2 < words.len() && messrs_at(&words, i) {
+                i += 2;
+                continue;
+            }
+            let word = words[i].as_str();
+            if !(tokens.is_empty() && i + 1 < words.len() && honorific(word).is_some()) {
+                tokens.push(canonical(word).to_string());
+            }
+            i += 1;
+        }
+
+        let keys = tokens.iter().map(|t| phonetic_key(t)).collect();
+        Self { tokens, keys }
+    }
+
+    /// How alike two names are, from 0.0 to 1.0. Each word is matched to
+    /// its closest counterpart in the other name (initials match any word
+    /// starting with that letter), and the average is taken both ways so
+    /// that an extra surname costs something without sinking the match.
+    pub fn similarity(&self, other: &PartyName) -> f64 {
+        if self.tokens.is_empty() || other.tokens.is_empty() {
+            return 0.0;
+        }
+        if self.tokens == other.tokens {
+            return 1.0;
+        }
+        (self.coverage(other) + other.coverage(self)) / 2.0
+    }
+
+    fn coverage(&self, other: &PartyName) -> f64 {
+        let total: f64 = self
+            .tokens
+            .iter()
+            .zip(&self.keys)
+            .map(|(token, key)| {
+                other
+                    .tokens
+                    .iter()
+                    .zip(&other.keys)
+                    .map(|(o_token, o_key)| word_similarity(token, key, o_token, o_key))
+                    .fold(0.0, f64::max)
+            })
+            .sum();
+        total / self.tokens.len() as f64
+    }
+}
+
+fn relation_at(words: &[String], i: usize) -> bool {
+    let word = words[i].as_str();
+    if RELATIONS.contains(&word) {
+        return true;
+    }
+    // "s/o", "d/o", "w/o", "h/o" split into two words.
+    matches!(word, "s" | "d" | "w" | "h") && words.get(i + 1).map(String::as_str) == Some("o")
+}
+
+// "M/s" split into two words.
+fn messrs_at(words: &[String], i: usize) -> bool {
+    words[i] == "m" && words.get(i + 1).map(String::as_str) == Some("s")
+}
+
+fn canonical(word: &str) -> &str {
+    NAME_VARIANTS
+        .iter()
+        .find(|(variants, _)| variants.contains(&word))
+        .map(|(_, canonical)| *canonical)
+        .unwrap_or(word)
+}
+
+fn word_similarity(a: &str, a_key: &str, b: &str, b_key: &str) -> f64 {
+    if a == b || a_key == b_key {
+        return 1.0;
+    }
+    if (a.len() == 1 && b.starts_with(a)) || (b.len() == 1 && a.starts_with(b)) {
+        return 0.8;
+    }
+    let spelled = edit_similarity(a, b);
+    let sounded = edit_similarity(a_key, b_key);
+    // Sounding alike is weaker evidence than being spelled alike.
+    spelled.max(sounded * 0.95)
+}
+
+/// 1.0 minus the Levenshtein distance scaled by the longer word.
+fn edit_similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - levenshtein(&a, &b) as f64 / longest as f64
+}
+
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+
+    for (i, ca) in a.iter().enumerate() {
+        curr[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != cb);
+            curr[j + 1] = substitution.min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    prev[b.len()]
+}
+
+/// Folds the spellings romanised Hindi names drift between: aspirated
+/// consonants, long vowels written double, v/w, z/j, q/k, ph/f, a final
+/// "a" or "y", and doubled letters.
+fn phonetic_key(word: &str) -> String {
+    const FOLDS: &[(&str, &str)] = &[
+        ("kh", "k"), ("gh", "g"), ("chh", "c"), ("ch", "c"), ("jh", "j"), ("th", "t"),
+        ("dh", "d"), ("ph", "f"), ("bh", "b"), ("sh", "s"), ("aa", "a"), ("ee", "i"),
+        ("ii", "i"), ("oo", "u"), ("ou", "u"), ("w", "v"), ("z", "j"), ("q", "k"),
+        ("ck", "k"), ("x", "ks"),
+    ];
+
+    // Doubled consonants go before folding so "rameshh" folds like
+    // "ramesh"; doubled vowels are left for the folds ("oo" -> "u").
+    let mut folded = collapse_doubles(word, |c| !"aeiou".contains(c));
+    for (from, to) in FOLDS {
+        folded = folded.replace(from, to);
+    }
+    let mut folded = collapse_doubles(&folded, |_| true);
+
+    if folded.len() > 3 && (folded.ends_with('a') || folded.ends_with('y')) {
+        folded.pop();
+    }
+    folded
+}
+
+fn collapse_doubles(word: &str, applies: impl Fn(char) -> bool) -> String {
+    let mut collapsed = String::with_capacity(word.len());
+    for c in word.chars() {
+        if !(applies(c) && collapsed.ends_with(c)) {
+            collapsed.push(c);
+        }
+    }
+    collapsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(raw: &str) -> Vec<String> {
+        PartyName::new(raw).tokens
+    }
+
+    fn similarity(a: &str, b: &str) -> f64 {
+        PartyName::new(a).similarity(&PartyName::new(b))
+    }
+
+    #[test]
+    fn titles_and_punctuation_are_dropped() {
+        assert_eq!(tokens("Sh. Ramesh Kumar"), ["ramesh", "kumar"]);
+        assert_eq!(tokens("SMT. SUNITA DEVI"), ["sunita", "devi"]);
+        assert_eq!(tokens("M/s Sharma Traders"), ["sharma", "traders"]);
+    }
+
+    #[test]
+    fn a_title_alone_is_kept_as_the_name() {
+        assert_eq!(tokens("Dr"), ["dr"]);
+        assert_eq!(tokens("M/s"), ["m", "s"]);
+    }
+
+    #[test]
+    fn single_initials_are_not_titles() {
+        assert_eq!(tokens("S Kumar"), ["s", "kumar"]);
+        assert_eq!(tokens("M Kumar"), ["m", "kumar"]);
+    }
+
+    #[test]
+    fn relations_end_the_name() {
+        assert_eq!(tokens("Ramesh s/o Suresh Kumar"), ["ramesh"]);
+        assert_eq!(tokens("Sunita W/O Ramesh"), ["sunita"]);
+        assert_eq!(tokens("Raju alias Rajesh"), ["raju"]);
+    }
+
+    #[test]
+    fn spelling_variants_are_merged() {
+        assert_eq!(tokens("Mohd. Salim"), tokens("Mohammad Salim"));
+        assert_eq!(similarity("Mohd. Salim", "Mohammad Salim"), 1.0);
+    }
+
+    #[test]
+    fn same_party_scores_at_the_threshold_or_above() {
+        assert_eq!(similarity("Sh. Ramesh Kumar", "Ramesh Kumar"), 1.0);
+        assert_eq!(similarity("Ramesh Kumar s/o Suresh", "Ramesh Kumar"), 1.0);
+        assert!(similarity("Ramesh Kr.", "Rameshh Kumar") >= DEFAULT_MATCH_THRESHOLD);
+        assert!(similarity("R. Kumar", "Ramesh Kumar") >= DEFAULT_MATCH_THRESHOLD);
+    }
+
+    #[test]
+    fn different_parties_score_below_the_threshold() {
+        assert!(similarity("S Kumar", "M Kumar") < DEFAULT_MATCH_THRESHOLD);
+        assert!(similarity("Ramesh Kumar", "Suresh Kumar") < DEFAULT_MATCH_THRESHOLD);
+        assert_eq!(similarity("", "Ramesh"), 0.0);
+    }
+}
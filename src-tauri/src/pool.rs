@@ -0,0 +1,111 @@
+use rusqlite::Connection;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use crate::error::AppResult;
+
+/// How many read connections a register keeps open at most. Reads beyond
+/// this wait for one to come free; the writer is never one of them.
+pub const READERS: usize = 4;
+
+/// How long a connection waits on a lock held by another one (e.g. during a
+/// checkpoint) before giving up with SQLITE_BUSY.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Takes `mutex` even if a thread panicked while holding it. Nothing behind
+/// our locks can be left half-updated: a connection rolls back an
+/// unfinished transaction when it is dropped, and everything else is a plain
+/// value replaced whole. Failing every later command instead would leave the
+/// counter with a dead app until it is restarted.
+pub(crate) fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Opens a connection to the register at `path` with the settings every
+/// connection shares. Readers are put in `query_only` mode so a slip in a
+/// read path can't write around the writer.
+pub(crate) fn open_connection(path: &Path, read_only: bool) -> rusqlite::Result<Connection> {
+    let conn = Connection::open(path)?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    conn.pragma_update(None, "foreign_keys", true)?;
+    if read_only {
+        conn.pragma_update(None, "query_only", true)?;
+    }
+    Ok(conn)
+}
+
+/// A small pool of read-only connections. Connections are opened as they
+/// are first needed and handed back when the `PooledConnection` drops.
+pub(crate) struct ReaderPool {
+    path: PathBuf,
+    idle: Mutex<Idle>,
+    returned: Condvar,
+}
+
+struct Idle {
+    connections: Vec<Connection>,
+    opened: usize,
+}
+
+impl ReaderPool {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            idle: Mutex::new(Idle {
+                connections: Vec::new(),
+                opened: 0,
+            }),
+            returned: Condvar::new(),
+        }
+    }
+
+    pub fn get(&self) -> AppResult<PooledConnection<'_>> {
+        let mut idle = lock(&self.idle);
+        loop {
+            if let Some(conn) = idle.connections.pop() {
+                return Ok(PooledConnection { conn: Some(conn), pool: self });
+            }
+
+            if idle.opened < READERS {
+                idle.opened += 1;
+                drop(idle);
+                return match open_connection(&self.path, true) {
+                    Ok(conn) => Ok(PooledConnection { conn: Some(conn), pool: self }),
+                    Err(e) => {
+                        lock(&self.idle).opened -= 1;
+                        Err(e.into())
+                    }
+                };
+            }
+
+            idle = self
+                .returned
+                .wait(idle)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+}
+
+pub(crate) struct PooledConnection<'a> {
+    conn: Option<Connection>,
+    pool: &'a ReaderPool,
+}
+
+impl Deref for PooledConnection<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn.as_ref().expect("pooled connection used after release")
+    }
+}
+
+impl Drop for PooledConnection<'_> {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            lock(&self.pool.idle).connections.push(conn);
+            self.pool.returned.notify_one();
+        }
+    }
+}
@@ -0,0 +1,749 @@
+use std::fs;
+use std::path::Path;
+
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime};
+use printpdf::image_crate::{self, imageops::FilterType, DynamicImage};
+use printpdf::{
+    BuiltinFont, Color, Greyscale, Image, ImageTransform, IndirectFontRef, Line, Mm, PdfDocument,
+    PdfLayerReference, Point, Rect,
+};
+use serde::Serialize;
+
+use crate::case_query::{CaseFilter, CasePage, CasePageRequest, CaseSummary, DisposalFilter, SortKey};
+use crate::database_init::{Case, Db};
+use crate::error::{AppError, AppResult};
+use crate::mediators::find_mediator;
+
+const LOGO_PNG: &[u8] = include_bytes!("../../src/assets/handshake.png");
+
+// Page geometry in millimetres, measured from the top left: A4 landscape,
+// which is what fits the ten columns of the case table.
+const PAGE_WIDTH: f32 = 297.0;
+const PAGE_HEIGHT: f32 = 210.0;
+const MARGIN: f32 = 12.0;
+const CONTENT_WIDTH: f32 = PAGE_WIDTH - 2.0 * MARGIN;
+const LOGO_WIDTH: f32 = 16.0;
+/// Print resolution for the logo; the asset itself is much finer.
+const LOGO_DPI: f32 = 200.0;
+const HEADER_RULE: f32 = MARGIN + 17.0;
+const BODY_TOP: f32 = HEADER_RULE + 5.0;
+const FOOTER_RULE: f32 = PAGE_HEIGHT - MARGIN - 5.0;
+const BODY_BOTTOM: f32 = FOOTER_RULE - 3.0;
+
+const PT: f32 = 25.4 / 72.0;
+const CELL_PADDING: f32 = 1.2;
+const TABLE_TEXT: f32 = 7.0;
+const LINE_SPACING: f32 = 1.25;
+
+#[derive(Debug, Serialize)]
+pub struct ReportInfo {
+    pub path: String,
+    pub pages: usize,
+    pub cases: usize,
+}
+
+/// One column of a report table. `weight` is its share of the page width.
+pub struct Column {
+    pub title: String,
+    pub weight: f32,
+    /// Right-aligned, for counts.
+    pub numeric: bool,
+}
+
+impl Column {
+    pub fn text(title: impl Into<String>, weight: f32) -> Self {
+        Column { title: title.into(), weight, numeric: false }
+    }
+
+    pub fn count(title: impl Into<String>, weight: f32) -> Self {
+        Column { title: title.into(), weight, numeric: true }
+    }
+}
+
+/// What gets drawn, in page coordinates from the top left. `y` is the text
+/// baseline for `Text` and the top edge for `Shade`.
+enum Mark {
+    Text { x: f32, y: f32, size: f32, bold: bool, text: String },
+    Rule { x1: f32, x2: f32, y: f32 },
+    Shade { x: f32, y: f32, width: f32, height: f32 },
+}
+
+/// A printed report: laid out page by page as sections are added, then
+/// given the centre's letterhead, a "Page x of y" footer and written out.
+pub struct Report {
+    /// Printed at the head of every page.
+    centre: String,
+    title: String,
+    prepared_by: String,
+    generated_at: NaiveDateTime,
+    pages: Vec<Vec<Mark>>,
+    /// Where the next section starts on the last page.
+    y: f32,
+}
+
+impl Report {
+    pub fn new(centre: &str, title: &str, prepared_by: &str, generated_at: NaiveDateTime) -> Self {
+        Report {
+            centre: centre.to_string(),
+            title: title.to_string(),
+            prepared_by: prepared_by.to_string(),
+            generated_at,
+            pages: vec![Vec::new()],
+            y: BODY_TOP,
+        }
+    }
+
+    fn marks(&mut self) -> &mut Vec<Mark> {
+        self.pages.last_mut().expect("a report always has a page")
+    }
+
+    fn new_page(&mut self) {
+        self.pages.push(Vec::new());
+        self.y = BODY_TOP;
+    }
+
+    /// Starts a new page unless `height` more fits on this one.
+    fn reserve(&mut self, height: f32) {
+        if self.y + height > BODY_BOTTOM && self.y > BODY_TOP {
+            self.new_page();
+        }
+    }
+
+    pub fn gap(&mut self, height: f32) {
+        self.y += height;
+    }
+
+    /// A paragraph wrapped to the page width.
+    pub fn paragraph(&mut self, text: &str, size: f32, bold: bool) {
+        let line_height = size * PT * LINE_SPACING;
+        for line in wrap(text, size, bold, CONTENT_WIDTH) {
+            self.reserve(line_height);
+            self.y += line_height;
+            let y = self.y - line_height * 0.25;
+            self.marks().push(Mark::Text { x: MARGIN, y, size, bold, text: line });
+        }
+    }
+
+    /// A section title, kept on the same page as the first rows under it.
+    pub fn heading(&mut self, text: &str) {
+        self.reserve(30.0);
+        self.gap(3.0);
+        self.paragraph(text, 10.0, true);
+        self.gap(1.0);
+    }
+
+    /// A table with a shaded header row, repeated on every page it runs
+    /// onto. Cells wrap within their column.
+    pub fn table(&mut self, columns: &[Column], rows: &[Vec<String>]) {
+        let total_weight: f32 = columns.iter().map(|c| c.weight).sum();
+        let widths: Vec<f32> = columns
+            .iter()
+            .map(|c| CONTENT_WIDTH * c.weight / total_weight)
+            .collect();
+        let titles: Vec<String> = columns.iter().map(|c| c.title.clone()).collect();
+
+        let header_height = row_height(&widths, &titles, true);
+        let first_row = rows.first().map_or(0.0, |row| row_height(&widths, row, false));
+        self.reserve(header_height + first_row);
+        self.table_header(columns, &widths, &titles, header_height);
+
+        for row in rows {
+            let height = row_height(&widths, row, false);
+            if self.y + height > BODY_BOTTOM {
+                self.new_page();
+                self.table_header(columns, &widths, &titles, header_height);
+            }
+            self.table_row(columns, &widths, row, false, height);
+        }
+    }
+
+    fn table_header(&mut self, columns: &[Column], widths: &[f32], titles: &[String], height: f32) {
+        let y = self.y;
+        self.marks().push(Mark::Shade { x: MARGIN, y, width: CONTENT_WIDTH, height });
+        self.table_row(columns, widths, titles, true, height);
+    }
+
+    fn table_row(&mut self, columns: &[Column], widths: &[f32], cells: &[String], bold: bool, height: f32) {
+        let top = self.y;
+        let line_height = TABLE_TEXT * PT * LINE_SPACING;
+        let mut x = MARGIN;
+
+        for ((column, width), cell) in columns.iter().zip(widths).zip(cells) {
+            let inner = width - 2.0 * CELL_PADDING;
+            for (i, line) in wrap(cell, TABLE_TEXT, bold, inner).into_iter().enumerate() {
+                let line_x = if column.numeric {
+                    x + width - CELL_PADDING - text_width(&line, TABLE_TEXT, bold)
+                } else {
+                    x + CELL_PADDING
+                };
+                let y = top + CELL_PADDING + (i + 1) as f32 * line_height - line_height * 0.25;
+                self.marks().push(Mark::Text { x: line_x, y, size: TABLE_TEXT, bold, text: line });
+            }
+            x += width;
+        }
+
+        self.y += height;
+        let y = self.y;
+        self.marks().push(Mark::Rule { x1: MARGIN, x2: MARGIN + CONTENT_WIDTH, y });
+    }
+
+    /// Lines to sign on, side by side: `(role, name)`, with the name left
+    /// blank for whoever signs by hand.
+    pub fn signatures(&mut self, blocks: &[(&str, &str)]) {
+        const LINE_WIDTH: f32 = 65.0;
+        self.reserve(32.0);
+        self.gap(20.0);
+
+        let step = if blocks.len() > 1 {
+            (CONTENT_WIDTH - LINE_WIDTH) / (blocks.len() - 1) as f32
+        } else {
+            0.0
+        };
+        let y = self.y;
+        for (i, (role, name)) in blocks.iter().enumerate() {
+            let x = MARGIN + step * i as f32;
+            let marks = self.marks();
+            marks.push(Mark::Rule { x1: x, x2: x + LINE_WIDTH, y });
+            marks.push(Mark::Text { x, y: y + 4.5, size: 8.0, bold: true, text: role.to_string() });
+            marks.push(Mark::Text { x, y: y + 8.5, size: 8.0, bold: false, text: name.to_string() });
+        }
+        self.y += 10.0;
+    }
+
+    /// Letterhead and footer for page `number` of `total`.
+    fn page_furniture(&self, number: usize, total: usize) -> Vec<Mark> {
+        let centred = |y: f32, size: f32, bold: bool, text: &str| Mark::Text {
+            x: (PAGE_WIDTH - text_width(text, size, bold)) / 2.0,
+            y,
+            size,
+            bold,
+            text: text.to_string(),
+        };
+        let right = |y: f32, size: f32, text: String| Mark::Text {
+            x: PAGE_WIDTH - MARGIN - text_width(&text, size, false),
+            y,
+            size,
+            bold: false,
+            text,
+        };
+
+        vec![
+            centred(MARGIN + 5.0, 13.0, true, &self.centre),
+            centred(MARGIN + 12.0, 11.0, true, &self.title),
+            right(MARGIN + 5.0, 8.0, format!("Generated {}", self.generated_at.format("%d.%m.%Y %H:%M"))),
+            Mark::Rule { x1: MARGIN, x2: PAGE_WIDTH - MARGIN, y: HEADER_RULE },
+            Mark::Rule { x1: MARGIN, x2: PAGE_WIDTH - MARGIN, y: FOOTER_RULE },
+            Mark::Text {
+                x: MARGIN,
+                y: FOOTER_RULE + 4.0,
+                size: 8.0,
+                bold: false,
+                text: format!("Prepared by {}", self.prepared_by),
+            },
+            right(FOOTER_RULE + 4.0, 8.0, format!("Page {} of {}", number, total)),
+        ]
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    pub fn render(&self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let (doc, first_page, first_layer) =
+            PdfDocument::new(&self.title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Report");
+        let regular = doc.add_builtin_font(BuiltinFont::Helvetica)?;
+        let bold = doc.add_builtin_font(BuiltinFont::HelveticaBold)?;
+
+        let logo = image_crate::load_from_memory(LOGO_PNG)?;
+        let logo_px = (LOGO_WIDTH / 25.4 * LOGO_DPI).round() as u32;
+        let logo = logo.resize(logo_px, logo_px, FilterType::Triangle);
+
+        let total = self.pages.len();
+        for (i, marks) in self.pages.iter().enumerate() {
+            let (page, layer) = if i == 0 {
+                (first_page, first_layer)
+            } else {
+                doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Report")
+            };
+            let layer = doc.get_page(page).get_layer(layer);
+
+            draw_logo(layer.clone(), &logo);
+            for mark in self.page_furniture(i + 1, total).iter().chain(marks) {
+                draw(&layer, mark, &regular, &bold);
+            }
+        }
+
+        Ok(doc.save_to_bytes()?)
+    }
+
+    /// Renders the report and writes it to `path`, returning the page count.
+    pub fn write(&self, path: &Path) -> AppResult<usize> {
+        let bytes = self.render().map_err(|e| AppError::ReportFailed {
+            path: path.display().to_string(),
+            detail: e.to_string(),
+        })?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, bytes)?;
+
+        Ok(self.page_count())
+    }
+}
+
+fn draw_logo(layer: PdfLayerReference, logo: &DynamicImage) {
+    let height = logo.height() as f32 * 25.4 / LOGO_DPI;
+    Image::from_dynamic_image(logo).add_to_layer(
+        layer,
+        ImageTransform {
+            translate_x: Some(Mm(MARGIN)),
+            translate_y: Some(Mm(PAGE_HEIGHT - MARGIN - height)),
+            dpi: Some(LOGO_DPI),
+            ..Default::default()
+        },
+    );
+}
+
+fn draw(layer: &PdfLayerReference, mark: &Mark, regular: &IndirectFontRef, bold: &IndirectFontRef) {
+    // printpdf measures up from the bottom of the page.
+    let flip = |y: f32| Mm(PAGE_HEIGHT - y);
+    match mark {
+        Mark::Text { x, y, size, bold: is_bold, text } => {
+            let font = if *is_bold { bold } else { regular };
+            layer.use_text(text.as_str(), *size, Mm(*x), flip(*y), font);
+        }
+        Mark::Rule { x1, x2, y } => {
+            layer.set_outline_color(Color::Greyscale(Greyscale::new(0.55, None)));
+            layer.set_outline_thickness(0.4);
+            layer.add_line(Line {
+                points: vec![(Point::new(Mm(*x1), flip(*y)), false), (Point::new(Mm(*x2), flip(*y)), false)],
+                is_closed: false,
+            });
+        }
+        Mark::Shade { x, y, width, height } => {
+            layer.set_fill_color(Color::Greyscale(Greyscale::new(0.88, None)));
+            layer.add_rect(Rect::new(Mm(*x), flip(y + height), Mm(x + width), flip(*y)));
+            layer.set_fill_color(Color::Greyscale(Greyscale::new(0.0, None)));
+        }
+    }
+}
+
+fn row_height(widths: &[f32], cells: &[String], bold: bool) -> f32 {
+    let lines = widths
+        .iter()
+        .zip(cells)
+        .map(|(width, cell)| wrap(cell, TABLE_TEXT, bold, width - 2.0 * CELL_PADDING).len())
+        .max()
+        .unwrap_or(1)
+        .max(1);
+    lines as f32 * TABLE_TEXT * PT * LINE_SPACING + 2.0 * CELL_PADDING
+}
+
+/// Splits `text` into lines no wider than `width`, breaking at spaces and
+/// at explicit newlines, and inside words only when one word won't fit.
+fn wrap(text: &str, size: f32, bold: bool, width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for paragraph in printable(text).split('\n') {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() { word.to_string() } else { format!("{} {}", line, word) };
+            if text_width(&candidate, size, bold) <= width {
+                line = candidate;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            for c in word.chars() {
+                if !line.is_empty() && text_width(&format!("{}{}", line, c), size, bold) > width {
+                    lines.push(std::mem::take(&mut line));
+                }
+                line.push(c);
+            }
+        }
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// The built-in PDF fonts only carry Latin characters; anything else is
+/// shown as `?` rather than silently dropped.
+fn printable(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\n' => '\n',
+            c if c.is_whitespace() => ' ',
+            ' '..='~' => c,
+            _ => '?',
+        })
+        .collect()
+}
+
+/// Width in millimetres of `text` set in Helvetica at `size` points.
+fn text_width(text: &str, size: f32, bold: bool) -> f32 {
+    let widths = if bold { &HELVETICA_BOLD_WIDTHS } else { &HELVETICA_WIDTHS };
+    let units: u32 = text
+        .chars()
+        .map(|c| match c {
+            ' '..='~' => widths[c as usize - 32] as u32,
+            _ => widths['?' as usize - 32] as u32,
+        })
+        .sum();
+    units as f32 / 1000.0 * size * PT
+}
+
+/// Advance widths of ASCII 32..=126 from the standard Helvetica metrics,
+/// in thousandths of the font size.
+#[rustfmt::skip]
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278,
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556,
+    1015, 667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778,
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469, 556,
+    333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556,
+    556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+#[rustfmt::skip]
+const HELVETICA_BOLD_WIDTHS: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278,
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611,
+    975, 722, 722, 722, 722, 667, 611, 778, 722, 278, 556, 722, 611, 833, 722, 778,
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 333, 278, 333, 584, 556,
+    333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556, 278, 889, 611, 611,
+    611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+];
+
+impl Db {
+    /// Prints every case matching `filter` to `path` as a PDF, with the
+    /// counts from `query_cases_filtered` after the listing.
+    pub fn write_case_report(
+        &self,
+        title: &str,
+        filter: &CaseFilter,
+        sort: Vec<SortKey>,
+        path: &Path,
+    ) -> AppResult<ReportInfo> {
+        let page = self.query_cases_filtered(filter, &CasePageRequest { sort, limit: None, offset: 0 })?;
+        let report = case_report(
+            &self.centre_name()?,
+            title,
+            &self.describe_filter(filter)?,
+            &page,
+            &self.operator(),
+            Local::now().naive_local(),
+        );
+        let pages = report.write(path)?;
+
+        println!("Wrote {} ({} case(s), {} page(s))", path.display(), page.cases.len(), pages);
+        Ok(ReportInfo {
+            path: path.display().to_string(),
+            pages,
+            cases: page.cases.len(),
+        })
+    }
+
+    /// `describe_filter`, with the mediator looked up by name.
+    pub fn describe_filter(&self, filter: &CaseFilter) -> AppResult<Vec<String>> {
+        let mediator = match filter.mediator_id {
+            Some(id) => Some(find_mediator(&*self.reader()?, id)?.full_name()),
+            None => None,
+        };
+        Ok(describe_filter(filter, mediator.as_deref()))
+    }
+}
+
+/// The case listing, its counts overall, by nature of case and by mediator,
+/// and the signature lines.
+pub fn case_report(
+    centre: &str,
+    title: &str,
+    criteria: &[String],
+    page: &CasePage,
+    prepared_by: &str,
+    generated_at: NaiveDateTime,
+) -> Report {
+    let mut report = Report::new(centre, title, prepared_by, generated_at);
+
+    if criteria.is_empty() {
+        report.paragraph("All cases on the register.", 8.0, false);
+    } else {
+        report.paragraph(&criteria.join("; "), 8.0, false);
+    }
+    report.paragraph(&format!("{} case(s)", page.total), 8.0, true);
+    report.gap(2.0);
+
+    if page.cases.is_empty() {
+        report.paragraph("No cases match.", 9.0, false);
+    } else {
+        let columns = [
+            Column::text("Case No", 20.0),
+            Column::text("Nature", 26.0),
+            Column::text("Received From", 34.0),
+            Column::text("Intake", 24.0),
+            Column::text("Party 1", 37.0),
+            Column::text("Party 2", 37.0),
+            Column::text("Mediator", 32.0),
+            Column::text("NDOH", 24.0),
+            Column::text("Disposal", 25.0),
+            Column::text("Connected", 16.0),
+        ];
+        let rows: Vec<Vec<String>> = page.cases.iter().map(case_cells).collect();
+        report.table(&columns, &rows);
+    }
+
+    report.heading("Summary");
+    report.table(&count_columns(None, &page.summary), &[count_cells(None, &page.summary)]);
+
+    if !page.by_nature.is_empty() {
+        report.heading("By Nature of Case");
+        let rows: Vec<Vec<String>> = page
+            .by_nature
+            .iter()
+            .map(|n| count_cells(Some(n.nature_of_case.label()), &n.counts))
+            .collect();
+        report.table(&count_columns(Some("Nature"), &page.summary), &rows);
+    }
+
+    if !page.by_mediator.is_empty() {
+        report.heading("By Mediator");
+        let rows: Vec<Vec<String>> = page
+            .by_mediator
+            .iter()
+            .map(|m| count_cells(Some(&m.mediator), &m.counts))
+            .collect();
+        report.table(&count_columns(Some("Mediator"), &page.summary), &rows);
+    }
+
+    report.signatures(&[("Prepared by", prepared_by), ("Coordinator", "")]);
+    report
+}
+
+fn case_cells(case: &Case) -> Vec<String> {
+    let disposal = match (case.disposal_of_case, case.disposal_date) {
+        (Some(d), Some(date)) => format!("{} on {}", d.label(), format_date(date)),
+        (Some(d), None) => d.label().to_string(),
+        (None, _) => String::new(),
+    };
+
+    vec![
+        format!("{}/{}", case.case_no, case.year),
+        case.nature_of_case.label().to_string(),
+        case.received_from.clone(),
+        format_listing(case.date, Some(case.time_slot)),
+        case.party1.clone(),
+        case.party2.clone(),
+        case.assigned_to.clone(),
+        case.ndoh_date.map_or(String::new(), |date| format_listing(date, case.ndoh_time)),
+        disposal,
+        if case.connected == 0 { "No".to_string() } else { case.connected.to_string() },
+    ]
+}
+
+/// Count columns for a `CaseSummary`, after a label column when `first`
+/// names one.
+pub fn count_columns(first: Option<&str>, like: &CaseSummary) -> Vec<Column> {
+    let mut columns = Vec::new();
+    if let Some(title) = first {
+        columns.push(Column::text(title, 40.0));
+    }
+    columns.extend(like.count_titles().into_iter().enumerate().map(|(i, title)| {
+        let weight = match i {
+            0 => 16.0,
+            1 => 18.0,
+            _ => 20.0,
+        };
+        Column::count(title, weight)
+    }));
+    columns
+}
+
+pub fn count_cells(first: Option<&str>, summary: &CaseSummary) -> Vec<String> {
+    let mut cells: Vec<String> = first.map(str::to_string).into_iter().collect();
+    cells.extend(summary.counts().into_iter().map(|n| n.to_string()));
+    cells
+}
+
+/// One line per filter that narrows the report, for its first page.
+pub fn describe_filter(filter: &CaseFilter, mediator: Option<&str>) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    if let Some(nature) = filter.nature_of_case {
+        lines.push(format!("Nature of case: {}", nature.label()));
+    }
+    if let Some(name) = mediator {
+        match filter.assigned_as_of {
+            Some(as_of) => lines.push(format!("Mediator: {} (as of {})", name, format_date(as_of))),
+            None => lines.push(format!("Mediator: {}", name)),
+        }
+    }
+    if let Some(court) = filter.received_from.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        lines.push(format!("Received from contains \"{}\"", court));
+    }
+    if let Some(party) = filter.party.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        lines.push(format!("Party name contains \"{}\"", party));
+    }
+    if !filter.disposal.is_empty() {
+        let accepted: Vec<&str> = filter
+            .disposal
+            .iter()
+            .map(|d| match d {
+                DisposalFilter::Undisposed => "Not yet disposed",
+                DisposalFilter::Is(disposal) => disposal.label(),
+            })
+            .collect();
+        lines.push(format!("Disposal: {}", accepted.join(" or ")));
+    }
+    if let Some(connected) = filter.connected {
+        lines.push(if connected { "Connected cases only" } else { "Unconnected cases only" }.to_string());
+    }
+    match (filter.case_no_from, filter.case_no_to) {
+        (Some(from), Some(to)) => lines.push(format!("Case no. {} to {}", from, to)),
+        (Some(from), None) => lines.push(format!("Case no. {} onwards", from)),
+        (None, Some(to)) => lines.push(format!("Case no. up to {}", to)),
+        (None, None) => {}
+    }
+    if let Some(year) = filter.year {
+        lines.push(format!("Year: {}", year));
+    }
+    if let Some(range) = filter.date_range {
+        let label = range.field.label();
+        lines.push(match (range.start, range.end) {
+            (Some(start), Some(end)) if start == end => format!("{}: {}", label, format_date(start)),
+            (Some(start), Some(end)) => format!("{}: {} to {}", label, format_date(start), format_date(end)),
+            (Some(start), None) => format!("{}: from {}", label, format_date(start)),
+            (None, Some(end)) => format!("{}: up to {}", label, format_date(end)),
+            (None, None) => format!("{}: any", label),
+        });
+    }
+
+    lines
+}
+
+pub fn format_date(date: NaiveDate) -> String {
+    date.format("%d.%m.%Y").to_string()
+}
+
+fn format_listing(date: NaiveDate, time: Option<NaiveTime>) -> String {
+    match time {
+        Some(time) => format!("{} {}", format_date(date), time.format("%H:%M")),
+        None => format_date(date),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> Report {
+        let generated_at = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap().and_hms_opt(10, 30, 0).unwrap();
+        Report::new("Test Mediation Centre", "Report", "Tester", generated_at)
+    }
+
+    fn shades_on(page: &[Mark]) -> Vec<f32> {
+        page.iter()
+            .filter_map(|mark| match mark {
+                Mark::Shade { y, .. } => Some(*y),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn texts_on(page: &[Mark]) -> Vec<&str> {
+        page.iter()
+            .filter_map(|mark| match mark {
+                Mark::Text { text, .. } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn wrap_keeps_every_line_within_the_width() {
+        let text = "Ram Kumar and others versus the State of NCT of Delhi through its Chief Secretary";
+        let width = 40.0;
+        let lines = wrap(text, TABLE_TEXT, false, width);
+
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|line| text_width(line, TABLE_TEXT, false) <= width));
+        assert_eq!(lines.join(" "), text);
+    }
+
+    #[test]
+    fn wrap_breaks_a_word_wider_than_the_column() {
+        let word = "MACT/123456789/2025/ROHINI/NORTH-WEST";
+        let width = 20.0;
+        let lines = wrap(word, TABLE_TEXT, true, width);
+
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|line| text_width(line, TABLE_TEXT, true) <= width));
+        assert_eq!(lines.concat(), word);
+    }
+
+    #[test]
+    fn wrap_starts_a_new_line_at_each_newline() {
+        assert_eq!(wrap("Sh A\nSmt B", TABLE_TEXT, false, 100.0), ["Sh A", "Smt B"]);
+        assert_eq!(wrap("", TABLE_TEXT, false, 100.0), [""]);
+    }
+
+    #[test]
+    fn characters_the_font_lacks_are_printed_as_question_marks() {
+        assert_eq!(printable("राम\tKumar"), "??? Kumar");
+        assert_eq!(wrap("Café", TABLE_TEXT, false, 100.0), ["Caf?"]);
+    }
+
+    #[test]
+    fn a_long_table_runs_onto_more_pages_with_its_header_on_each() {
+        let columns = [Column::text("Case", 1.0), Column::count("Hearings", 1.0)];
+        let rows: Vec<Vec<String>> = (1..=80).map(|i| vec![format!("MACT {}", i), i.to_string()]).collect();
+        let mut report = report();
+        report.table(&columns, &rows);
+
+        assert!(report.page_count() > 1);
+        for page in &report.pages {
+            assert_eq!(shades_on(page), [BODY_TOP]);
+            assert_eq!(texts_on(page)[..2], ["Case", "Hearings"]);
+        }
+        let printed: usize =
+            report.pages.iter().map(|page| texts_on(page).iter().filter(|t| t.starts_with("MACT ")).count()).sum();
+        assert_eq!(printed, rows.len());
+        assert!(report.y <= BODY_BOTTOM);
+    }
+
+    #[test]
+    fn a_table_that_will_not_fit_moves_to_the_next_page_whole() {
+        let columns = [Column::text("Case", 1.0)];
+        let mut report = report();
+        report.gap(BODY_BOTTOM - BODY_TOP - 5.0);
+        report.table(&columns, &[vec!["MACT 1".to_string()]]);
+
+        assert_eq!(report.page_count(), 2);
+        assert!(shades_on(&report.pages[0]).is_empty());
+        assert_eq!(shades_on(&report.pages[1]), [BODY_TOP]);
+    }
+
+    #[test]
+    fn reserving_at_the_top_of_a_page_adds_no_blank_page() {
+        let mut report = report();
+        report.reserve(BODY_BOTTOM);
+        report.heading("Summary");
+
+        assert_eq!(report.page_count(), 1);
+        assert_eq!(texts_on(&report.pages[0]), ["Summary"]);
+    }
+
+    #[test]
+    fn every_page_carries_the_centre_name_and_its_number() {
+        let mut report = report();
+        report.table(&[Column::text("Case", 1.0)], &vec![vec!["MACT".to_string()]; 80]);
+        let total = report.page_count();
+
+        let furniture = report.page_furniture(2, total);
+        let texts = texts_on(&furniture);
+        assert!(texts.contains(&"Test Mediation Centre"));
+        assert!(texts.contains(&format!("Page 2 of {}", total).as_str()));
+    }
+}
@@ -0,0 +1,317 @@
+use chrono::NaiveDate;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::audit;
+use crate::database_init::{parse_optional_date_column, Db};
+use crate::error::{AppError, AppResult};
+use crate::mediators::{find_mediator, parse_legacy_name};
+
+/// Written into every exported roster; files from a newer app are refused.
+const ROSTER_VERSION: u32 = 1;
+
+/// Looked for next to the database the first time it is opened with no
+/// mediators, so a centre can ship its panel with the install.
+const FIRST_RUN_FILES: &[&str] = &["mediators.toml", "mediators.json"];
+
+/// A centre's panel of mediators as kept in a `.toml` or `.json` file.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Roster {
+    #[serde(default = "roster_version")]
+    pub version: u32,
+    /// Printed at the head of every report; left as it is when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub centre_name: Option<String>,
+    #[serde(default)]
+    pub mediators: Vec<RosterEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RosterEntry {
+    pub display_name: String,
+    #[serde(default)]
+    pub honorific: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub joined_on: Option<NaiveDate>,
+    #[serde(default = "default_active")]
+    pub active: bool,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct RosterImportSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+}
+
+fn roster_version() -> u32 {
+    ROSTER_VERSION
+}
+
+fn default_active() -> bool {
+    true
+}
+
+enum RosterFormat {
+    Json,
+    Toml,
+}
+
+impl RosterFormat {
+    fn for_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => RosterFormat::Toml,
+            _ => RosterFormat::Json,
+        }
+    }
+}
+
+impl Roster {
+    /// Builds a roster from names typed one per line on the setup screen,
+    /// e.g. "Sh Surender Singh".
+    pub fn from_names<'a>(names: impl IntoIterator<Item = &'a str>) -> Self {
+        let mediators = names
+            .into_iter()
+            .map(parse_legacy_name)
+            .filter(|(_, display_name)| !display_name.is_empty())
+            .map(|(honorific, display_name)| RosterEntry {
+                display_name,
+                honorific,
+                phone: None,
+                email: None,
+                joined_on: None,
+                active: true,
+            })
+            .collect();
+
+        Roster {
+            version: ROSTER_VERSION,
+            centre_name: None,
+            mediators,
+        }
+    }
+
+    pub fn read(path: &Path) -> AppResult<Self> {
+        let text = fs::read_to_string(path)?;
+        let invalid = |detail: String| AppError::InvalidRoster {
+            path: path.display().to_string(),
+            detail,
+        };
+
+        let roster: Roster = match RosterFormat::for_path(path) {
+            RosterFormat::Toml => toml::from_str(&text).map_err(|e| invalid(e.to_string()))?,
+            RosterFormat::Json => serde_json::from_str(&text).map_err(|e| invalid(e.to_string()))?,
+        };
+
+        if roster.version > ROSTER_VERSION {
+            return Err(invalid(format!(
+                "roster version {} is newer than this app supports ({})",
+                roster.version, ROSTER_VERSION
+            )));
+        }
+
+        if let Some(i) = roster
+            .mediators
+            .iter()
+            .position(|m| m.display_name.trim().is_empty())
+        {
+            return Err(invalid(format!("entry {} has no display_name", i + 1)));
+        }
+
+        Ok(roster)
+    }
+
+    pub fn write(&self, path: &Path) -> AppResult<()> {
+        let invalid = |detail: String| AppError::InvalidRoster {
+            path: path.display().to_string(),
+            detail,
+        };
+
+        let text = match RosterFormat::for_path(path) {
+            RosterFormat::Toml => toml::to_string_pretty(self).map_err(|e| invalid(e.to_string()))?,
+            RosterFormat::Json => serde_json::to_string_pretty(self).map_err(|e| invalid(e.to_string()))?,
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, text)?;
+        Ok(())
+    }
+}
+
+impl Db {
+    /// True until at least one mediator has been set up.
+    pub fn needs_setup(&self) -> AppResult<bool> {
+        let conn = self.reader()?;
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM mediators", [], |row| row.get(0))?;
+        Ok(count == 0)
+    }
+
+    /// On a register with no mediators yet, imports the first roster file
+    /// found in `dir`. Returns the file that was used, if any.
+    pub fn seed_from_config(&self, dir: &Path) -> AppResult<Option<PathBuf>> {
+        if !self.needs_setup()? {
+            return Ok(None);
+        }
+
+        let Some(path) = FIRST_RUN_FILES.iter().map(|f| dir.join(f)).find(|p| p.exists()) else {
+            return Ok(None);
+        };
+
+        let summary = self.import_roster(Roster::read(&path)?)?;
+        println!(
+            "Seeded {} mediator(s) from {}",
+            summary.added,
+            path.display()
+        );
+        Ok(Some(path))
+    }
+
+    /// Adds every mediator in `roster` that isn't on the register yet and
+    /// brings the details of those that are in line with the file, matching
+    /// on display name. Mediators missing from the file are left alone, and
+    /// so is the centre name unless the file gives one.
+    pub fn import_roster(&self, roster: Roster) -> AppResult<RosterImportSummary> {
+        let operator = self.operator();
+        let conn = self.writer();
+        let tx = conn.unchecked_transaction()?;
+        let mut summary = RosterImportSummary::default();
+
+        if let Some(name) = roster.centre_name.as_deref().map(str::trim).filter(|n| !n.is_empty()) {
+            update_centre_name(&tx, &operator, name)?;
+        }
+
+        for entry in &roster.mediators {
+            let display_name = entry.display_name.trim();
+            let joined_on = entry.joined_on.map(|d| d.to_string());
+
+            let existing: Option<i32> = tx
+                .query_row(
+                    "SELECT id FROM mediators WHERE display_name = ?1",
+                    [display_name],
+                    |row| row.get(0),
+                )
+                .optional()?;
+
+            let Some(id) = existing else {
+                tx.execute(
+                    "INSERT INTO mediators (display_name, honorific, phone, email, active, joined_on)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    params![display_name, entry.honorific, entry.phone, entry.email, entry.active, joined_on],
+                )?;
+                let added = find_mediator(&tx, tx.last_insert_rowid() as i32)?;
+                audit::record(&tx, &operator, "import_roster", None, None, Some(&json!(added)))?;
+                summary.added += 1;
+                continue;
+            };
+
+            let before = find_mediator(&tx, id)?;
+            let updated = tx.execute(
+                "UPDATE mediators
+                 SET honorific = ?2, phone = ?3, email = ?4, active = ?5, joined_on = ?6
+                 WHERE id = ?1
+                   AND (honorific IS NOT ?2 OR phone IS NOT ?3 OR email IS NOT ?4
+                        OR active IS NOT ?5 OR joined_on IS NOT ?6)",
+                params![id, entry.honorific, entry.phone, entry.email, entry.active, joined_on],
+            )?;
+
+            if updated > 0 {
+                let after = find_mediator(&tx, id)?;
+                audit::record(&tx, &operator, "import_roster", None, Some(&json!(before)), Some(&json!(after)))?;
+                summary.updated += 1;
+            } else {
+                summary.unchanged += 1;
+            }
+        }
+
+        tx.commit()?;
+
+        println!(
+            "Roster imported: {} added, {} updated, {} unchanged",
+            summary.added, summary.updated, summary.unchanged
+        );
+        Ok(summary)
+    }
+
+    /// Every mediator, retired ones included, in the order they were added.
+    pub fn export_roster(&self) -> AppResult<Roster> {
+        let conn = self.reader()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT display_name, honorific, phone, email, joined_on, active
+             FROM mediators
+             ORDER BY id",
+        )?;
+
+        let mediators = stmt
+            .query_map([], |row| {
+                Ok(RosterEntry {
+                    display_name: row.get(0)?,
+                    honorific: row.get(1)?,
+                    phone: row.get(2)?,
+                    email: row.get(3)?,
+                    joined_on: parse_optional_date_column(row, 4)?,
+                    active: row.get(5)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(Roster {
+            version: ROSTER_VERSION,
+            centre_name: Some(centre_name(&conn)?),
+            mediators,
+        })
+    }
+
+    /// Name printed at the head of every report, return and export.
+    pub fn centre_name(&self) -> AppResult<String> {
+        Ok(centre_name(&*self.reader()?)?)
+    }
+
+    pub fn set_centre_name(&self, name: &str) -> AppResult<String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(AppError::InvalidValue { field: "centre_name", value: name.to_string() });
+        }
+
+        let operator = self.operator();
+        let conn = self.writer();
+        let tx = conn.unchecked_transaction()?;
+        update_centre_name(&tx, &operator, name)?;
+        tx.commit()?;
+
+        println!("Centre name set to {:?}", name);
+        Ok(name.to_string())
+    }
+}
+
+pub(crate) fn centre_name(conn: &Connection) -> rusqlite::Result<String> {
+    conn.query_row("SELECT name FROM centre_details WHERE id = 1", [], |row| row.get(0))
+}
+
+/// Sets the centre name within `conn`'s transaction and records the change
+/// in the audit log. Giving the name it already has does nothing.
+pub(crate) fn update_centre_name(conn: &Connection, operator: &str, name: &str) -> rusqlite::Result<()> {
+    let before = centre_name(conn)?;
+    if before == name {
+        return Ok(());
+    }
+
+    conn.execute("UPDATE centre_details SET name = ?1 WHERE id = 1", [name])?;
+    audit::record(
+        conn,
+        operator,
+        "set_centre_name",
+        None,
+        Some(&json!({ "centre_name": before })),
+        Some(&json!({ "centre_name": name })),
+    )
+}
@@ -0,0 +1,135 @@
+use rusqlite::params;
+use serde::Serialize;
+
+use crate::database_init::{case_from_row, Case, Db, CASE_COLUMNS, CASE_COLUMN_COUNT};
+use crate::error::{AppError, AppResult};
+
+/// Marks around each matched word in `CaseSearchHit::snippet`. Control
+/// characters can't occur in typed names, so the UI can split on them
+/// without escaping anything.
+pub const HIGHLIGHT_START: &str = "\u{2}";
+pub const HIGHLIGHT_END: &str = "\u{3}";
+
+/// Hits returned when the caller doesn't ask for a number, and the most it
+/// may ask for.
+pub const DEFAULT_SEARCH_LIMIT: u32 = 50;
+pub const MAX_SEARCH_LIMIT: u32 = 500;
+
+/// bm25 weights for case_no, year, party1, party2, received_from and notes:
+/// a hit on a party's name counts for far more than one in a hearing remark.
+const COLUMN_WEIGHTS: &str = "5.0, 1.0, 10.0, 10.0, 3.0, 1.0";
+
+#[derive(Debug, Serialize)]
+pub struct CaseSearchHit {
+    pub case: Case,
+    /// The best-matching stretch of text, with matches wrapped in
+    /// `HIGHLIGHT_START` / `HIGHLIGHT_END`.
+    pub snippet: String,
+    /// bm25 score; lower is a better match.
+    pub rank: f64,
+}
+
+impl Db {
+    /// Cases whose parties, referring court, number or hearing remarks
+    /// contain every word of `query`, best match first. Each word also
+    /// matches as a prefix, so "Ram Kum" finds "Ramesh Kumar".
+    pub fn search_cases(&self, query: &str, limit: u32) -> AppResult<Vec<CaseSearchHit>> {
+        let fts_query = fts_query(query).ok_or_else(|| AppError::InvalidValue {
+            field: "query",
+            value: query.to_string(),
+        })?;
+
+        let conn = self.reader()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {}, s.snippet, s.rank
+             FROM case_table
+             JOIN (
+                SELECT rowid AS case_id,
+                       snippet(case_search, -1, ?2, ?3, '…', 12) AS snippet,
+                       bm25(case_search, {}) AS rank
+                FROM case_search
+                WHERE case_search MATCH ?1
+             ) s ON s.case_id = case_table.id
+             ORDER BY s.rank
+             LIMIT ?4",
+            CASE_COLUMNS, COLUMN_WEIGHTS
+        ))?;
+
+        let hits = stmt
+            .query_map(
+                params![fts_query, HIGHLIGHT_START, HIGHLIGHT_END, limit],
+                |row| {
+                    Ok(CaseSearchHit {
+                        case: case_from_row(row)?,
+                        snippet: row.get(CASE_COLUMN_COUNT)?,
+                        rank: row.get(CASE_COLUMN_COUNT + 1)?,
+                    })
+                },
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(hits)
+    }
+}
+
+/// Turns what the clerk typed into an FTS5 query: every run of letters or
+/// digits becomes a quoted prefix term, and the terms must all match.
+/// Punctuation is dropped rather than passed through, so "Mohd." or
+/// "123/2024" can't be read as FTS5 syntax. `None` if nothing searchable is left.
+fn fts_query(input: &str) -> Option<String> {
+    let terms: Vec<String> = input
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("\"{}\"*", word))
+        .collect();
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::Connection;
+
+    #[test]
+    fn punctuation_alone_leaves_nothing_to_search() {
+        assert_eq!(fts_query(""), None);
+        assert_eq!(fts_query("./-  ()*\""), None);
+    }
+
+    #[test]
+    fn words_become_quoted_prefix_terms() {
+        assert_eq!(
+            fts_query("Mohd. 123/2024").as_deref(),
+            Some("\"Mohd\"* \"123\"* \"2024\"*")
+        );
+    }
+
+    #[test]
+    fn fts_syntax_is_taken_as_plain_words() {
+        assert_eq!(fts_query("\"OR* (").as_deref(), Some("\"OR\"*"));
+        assert_eq!(
+            fts_query("ram NEAR(kumar) -x").as_deref(),
+            Some("\"ram\"* \"NEAR\"* \"kumar\"* \"x\"*")
+        );
+
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE VIRTUAL TABLE t USING fts5(body);
+             INSERT INTO t (body) VALUES ('order of the court');",
+        )
+        .unwrap();
+        let hits: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM t WHERE t MATCH ?1",
+                [fts_query("\"OR* (").unwrap()],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(hits, 1);
+    }
+}
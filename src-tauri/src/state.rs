@@ -0,0 +1,157 @@
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::database_init::Db;
+use crate::error::{AppError, AppResult};
+use crate::pool::lock;
+
+/// How many registers the "recent databases" list remembers.
+const MAX_RECENT: usize = 10;
+
+/// App-wide state managed by Tauri and handed to every command.
+///
+/// At most one register is open at a time. Commands work on a clone of its
+/// `Db` handle, so opening another register (or closing this one) never
+/// waits for them; the old connection closes once the last of them is done.
+pub struct AppState {
+    db: Mutex<Option<Db>>,
+    /// Who is at the desk. Kept here so it carries over to the next register.
+    operator: Mutex<Option<String>>,
+    /// JSON file holding the recent databases list, in the app data folder.
+    recent_file: PathBuf,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentDatabase {
+    pub path: String,
+    pub last_opened: String,
+    /// Whether the file is still there, e.g. the archive drive is plugged in.
+    #[serde(skip_deserializing, default)]
+    pub exists: bool,
+}
+
+impl AppState {
+    pub fn new(recent_file: PathBuf) -> Self {
+        Self {
+            db: Mutex::new(None),
+            operator: Mutex::new(None),
+            recent_file,
+        }
+    }
+
+    /// The open register, or `DbNotInitialised` if none is.
+    pub fn db(&self) -> AppResult<Db> {
+        lock(&self.db).clone().ok_or(AppError::DbNotInitialised)
+    }
+
+    /// Opens the register at `path`, creating an empty one if the file does
+    /// not exist yet, and makes it the active one in place of whatever was
+    /// open before. `seed` is turned off for a register that is about to
+    /// have an archive imported into it, which must start with no mediators.
+    pub fn open(&self, path: PathBuf, seed: bool) -> AppResult<Db> {
+        if !path.exists() {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::File::create(&path)?;
+        }
+
+        println!("Opening database at: {}", path.display());
+        let db = Db::init(path.clone())?;
+
+        // A mediators.toml/json shipped next to a brand-new register sets up
+        // the panel without going through the setup screen.
+        if let Some(dir) = path.parent().filter(|_| seed) {
+            db.seed_from_config(dir)?;
+        }
+
+        if let Some(operator) = lock(&self.operator).as_deref() {
+            db.set_operator(operator);
+        }
+
+        *lock(&self.db) = Some(db.clone());
+        println!("Database opened successfully at: {}", path.display());
+
+        // The list is a convenience; failing to update it must not stop the
+        // register from opening.
+        if let Err(e) = self.remember(&path) {
+            eprintln!("Could not update the recent databases list: {}", e);
+        }
+
+        Ok(db)
+    }
+
+    /// Closes the active register, returning its path if one was open.
+    pub fn close(&self) -> Option<PathBuf> {
+        let closed = lock(&self.db).take().map(|db| db.path.clone());
+        if let Some(path) = &closed {
+            println!("Closed database at: {}", path.display());
+        }
+        closed
+    }
+
+    pub fn open_path(&self) -> Option<PathBuf> {
+        lock(&self.db).as_ref().map(|db| db.path.clone())
+    }
+
+    pub fn set_operator(&self, operator: &str) {
+        *lock(&self.operator) = Some(operator.to_string());
+        if let Some(db) = lock(&self.db).as_ref() {
+            db.set_operator(operator);
+        }
+    }
+
+    /// Registers opened before, most recent first.
+    pub fn recent_databases(&self) -> AppResult<Vec<RecentDatabase>> {
+        let mut recent = self.read_recent();
+        for entry in &mut recent {
+            entry.exists = Path::new(&entry.path).is_file();
+        }
+        Ok(recent)
+    }
+
+    pub fn forget_recent(&self, path: &str) -> AppResult<Vec<RecentDatabase>> {
+        let mut recent = self.read_recent();
+        recent.retain(|entry| entry.path != path);
+        self.write_recent(&recent)?;
+        self.recent_databases()
+    }
+
+    fn remember(&self, path: &Path) -> AppResult<()> {
+        let path = path.display().to_string();
+        let mut recent = self.read_recent();
+        recent.retain(|entry| entry.path != path);
+        recent.insert(
+            0,
+            RecentDatabase {
+                path,
+                last_opened: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+                exists: true,
+            },
+        );
+        recent.truncate(MAX_RECENT);
+        self.write_recent(&recent)
+    }
+
+    fn read_recent(&self) -> Vec<RecentDatabase> {
+        let Ok(text) = fs::read_to_string(&self.recent_file) else {
+            return Vec::new();
+        };
+        serde_json::from_str(&text).unwrap_or_else(|e| {
+            eprintln!("Ignoring unreadable {}: {}", self.recent_file.display(), e);
+            Vec::new()
+        })
+    }
+
+    fn write_recent(&self, recent: &[RecentDatabase]) -> AppResult<()> {
+        if let Some(parent) = self.recent_file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let text = serde_json::to_string_pretty(recent).map_err(std::io::Error::other)?;
+        fs::write(&self.recent_file, text)?;
+        Ok(())
+    }
+}
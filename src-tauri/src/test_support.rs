@@ -0,0 +1,71 @@
+use chrono::{NaiveDate, NaiveTime};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::database_init::{CaseIntake, CaseKey, CaseUpdate, Db, DisposalOfCase, NatureOfCase};
+use crate::disposal::DisposalDetails;
+use crate::roster::Roster;
+
+/// A folder of the test's own under the system temp directory, emptied when
+/// it is made and removed again when it drops.
+pub(crate) struct ScratchDir {
+    path: PathBuf,
+}
+
+impl ScratchDir {
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("register-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn join(&self, name: &str) -> PathBuf {
+        self.path.join(name)
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// A new register in `dir` with one mediator, id 1, on its roster.
+pub(crate) fn register(dir: &ScratchDir) -> Db {
+    let db = Db::init(dir.join("cases.db")).unwrap();
+    db.import_roster(Roster::from_names(["Sh Tarun Shokeen"])).unwrap();
+    db
+}
+
+/// A MACT case for mediator 1, taken in today.
+pub(crate) fn intake(case_no: i32, year: i32) -> CaseIntake {
+    CaseIntake {
+        case_no,
+        year,
+        nature_of_case: NatureOfCase::MactCase,
+        received_from: "Court".to_string(),
+        time_slot: NaiveTime::from_hms_opt(10, 0, 0).unwrap(),
+        party1: "A".to_string(),
+        party2: "B".to_string(),
+        mediator_id: 1,
+    }
+}
+
+/// Records a sitting on `date` with `outcome`; a final outcome is disposed
+/// of that day with the first disposal reason.
+pub(crate) fn dispose(db: &Db, case_no: i32, year: i32, outcome: DisposalOfCase, date: NaiveDate) {
+    let update = CaseUpdate {
+        ndoh_date: date,
+        ndoh_time: NaiveTime::from_hms_opt(11, 0, 0).unwrap(),
+        disposal_of_case: outcome,
+        connected: None,
+        remarks: None,
+        disposal: outcome.is_final().then_some(DisposalDetails { date, reason_id: 1, remarks: None }),
+    };
+    db.update_case_details(CaseKey { case_no, year }, update).unwrap();
+}
@@ -1,9 +1,12 @@
-import { useEffect } from "react";
+import { useCallback, useEffect, useState } from "react";
 import { resolve, appDataDir } from "@tauri-apps/api/path";
 import { invoke } from "@tauri-apps/api/core";
 import { setupLogger } from "./lib/logs";
+import { errorMessage } from "./lib/errors";
 import InitialPage from "./landing/landing";
+import SetupMediators from "./forms/setupMediators";
 import { AssignedToProvider, useAssignedTo } from "./lib/assignedContext";
+import type { RecentDatabase } from "./forms/openRegister";
 
 import "./App.css";
 
@@ -11,29 +14,63 @@ function AppContent() {
 
     setupLogger();
     const { fetchOptions } = useAssignedTo();
+    const [needsSetup, setNeedsSetup] = useState(false);
+    const [register, setRegister] = useState<string | null>(null);
+
+    // Opens (or switches to) the register at `path`; throws if it can't.
+    // `seed` off leaves a new register without the roster file next to it,
+    // ready for an archive import.
+    const openRegister = useCallback(async (path: string, seed = true) => {
+      await invoke("open_db", { path, seed });
+      console.log("✅ Database opened at:", path);
+      setRegister(path);
+
+      setNeedsSetup(await invoke<boolean>("needs_setup"));
+      await fetchOptions(); // Now safe — DB is ready
+    }, [fetchOptions]);
 
     useEffect(() => {
     const init = async () => {
       try {
-        const appDataDirPath = await appDataDir();
-        const path = await resolve(appDataDirPath, "cases.db");
+        const operator = localStorage.getItem("operator");
+        if (operator) {
+          await invoke("set_operator", { operator });
+        }
 
-        await invoke("init_db", { path });
-        console.log("✅ Database initialized at:", path);
+        // Reopen whichever register was used last, falling back to the
+        // default one in the app data folder.
+        const recent = await invoke<RecentDatabase[]>("get_recent_databases");
+        const last = recent.find((r) => r.exists);
+        const path = last ? last.path : await resolve(await appDataDir(), "cases.db");
 
-        await fetchOptions(); // Now safe — DB is ready
+        await openRegister(path);
       } catch (error) {
         console.error("❌ Failed to initialize DB:", error);
+        alert(errorMessage(error));
       }
     };
 
     init();
-    }, [fetchOptions]);
+    }, [openRegister]);
 
 
   return (
     <main className="container">
-      <InitialPage />
+      <InitialPage
+        register={register}
+        onOpenRegister={openRegister}
+        onCloseRegister={async () => {
+          await invoke("close_db");
+          setRegister(null);
+        }}
+      />
+      <SetupMediators
+        isOpen={needsSetup}
+        onDone={() => {
+          setNeedsSetup(false);
+          fetchOptions();
+        }}
+      />
     </main>
   );
 }
@@ -1,7 +1,8 @@
 import React, { useEffect, useState } from "react";
 import { CircleX,ChevronDown } from "lucide-react";
 import { invoke } from "@tauri-apps/api/core";
-import { NatureOfCase } from "../types/case";
+import { useNatureOfCase } from "../lib/natureOfCase";
+import { errorMessage } from "../lib/errors";
 interface ModalFormProps {
   isOpen: boolean;
   onClose: () => void;
@@ -10,6 +11,23 @@ interface ModalFormProps {
 }
 import { useAssignedTo } from "../lib/assignedContext";
 
+interface SimilarParty {
+  case_id: number;
+  case_no: number;
+  year: number;
+  party: string;
+  role: "party1" | "party2";
+  score: number;
+  open: boolean;
+  date: string;
+  ndoh_date: string | null;
+}
+
+interface RegisteredCase {
+  id: number;
+  open_matches: SimilarParty[];
+}
+
 // Assumes SelectField is defined and imported correctly
 // Example:
 // import { SelectField } from "./SelectField";
@@ -21,10 +39,7 @@ const InitialForm: React.FC<ModalFormProps> = ({ isOpen, onClose, title, formId
   
     
 
-    const natureOfCaseOptions = Object.values(NatureOfCase).map((option) => ({
-    value: option,
-    label: option.replace(/([a-z])([A-Z])/g, "$1 $2"), // adds space between camel-case words
-    }));
+    const { options: natureOfCaseOptions } = useNatureOfCase();
 
 
   useEffect(() => {
@@ -71,22 +86,31 @@ const InitialForm: React.FC<ModalFormProps> = ({ isOpen, onClose, title, formId
       time_slot: formData.get("time-of-assignment") || "",
       party1: formData.get("party1") || "",
       party2: formData.get("party2") || "",
-      assigned_to: formData.get("assigned-to") || "",
+      mediator_id: Number(formData.get("assigned-to") || 0),
     },
   })
-    .then(() => {
+    .then((result) => {
+      const { open_matches } = result as RegisteredCase;
       console.log("Case added successfully");
+      if (open_matches.length > 0) {
+        alert(
+          "Case added. These parties may already have open cases:\n\n" +
+            open_matches
+              .map((m) => `${m.case_no}/${m.year}: ${m.party} (${m.role === "party1" ? "Party 1" : "Party 2"})`)
+              .join("\n")
+        );
+      }
+      form.reset();
+      onClose();
     })
     .catch((error) => {
       console.error("Error adding case:", error);
+      alert(errorMessage(error));
     });
 
   for (const [key, value] of formData.entries()) {
     console.log(`${key}: ${value}`);
   }
-
-  form.reset();
-  onClose();
 };
 
 
@@ -384,7 +408,7 @@ const InitialForm: React.FC<ModalFormProps> = ({ isOpen, onClose, title, formId
 
 type OptionType = string | { value: string; label: string };
 
-export const SelectField: React.FC<{ id: string; label: string; options: OptionType[] }> = ({ id, label, options }) => {
+export const SelectField: React.FC<{ id: string; label: string; options: OptionType[]; defaultValue?: string }> = ({ id, label, options, defaultValue = "" }) => {
   return (
     <div>
       <label htmlFor={id} className="block text-sm font-medium text-gray-700 mb-1">
@@ -394,7 +418,7 @@ export const SelectField: React.FC<{ id: string; label: string; options: OptionT
         <select
           id={id}
           name={id}
-          defaultValue=""
+          defaultValue={defaultValue}
           className="appearance-none mt-1 block w-full px-4 py-3 border border-gray-300 rounded-lg shadow-sm focus:outline-none focus:ring-blue-500 focus:border-blue-500 sm:text-sm bg-white"
         >
           <option disabled value="">
@@ -0,0 +1,164 @@
+import React, { useState } from "react";
+import { CircleX } from "lucide-react";
+import { invoke } from "@tauri-apps/api/core";
+import { errorMessage } from "../lib/errors";
+
+interface AuditLogProps {
+  isOpen: boolean;
+  onClose: () => void;
+  title: string;
+  formId: string;
+}
+
+type AuditEntry = {
+  id: number;
+  recorded_at: string;
+  operator: string;
+  command: string;
+  case_no: number | null;
+  year: number | null;
+  before: Record<string, unknown> | null;
+  after: Record<string, unknown> | null;
+  hash: string;
+};
+
+type AuditVerification = {
+  ok: boolean;
+  entries_checked: number;
+  first_bad_id: number | null;
+  problem: string | null;
+  anchored_to: string | null;
+};
+
+const inputClass = "block w-full px-4 py-3 border border-gray-300 rounded-lg shadow-sm";
+
+const AuditLog: React.FC<AuditLogProps> = ({ isOpen, onClose, title, formId }) => {
+  const [entries, setEntries] = useState<AuditEntry[]>([]);
+  const [verification, setVerification] = useState<AuditVerification | null>(null);
+
+  const handleSearch = async (e: React.FormEvent<HTMLFormElement>) => {
+    e.preventDefault();
+    const formData = new FormData(e.currentTarget);
+    const caseNo = formData.get("case-no")?.toString().trim();
+    const year = formData.get("year")?.toString().trim();
+
+    try {
+      const found = await invoke<AuditEntry[]>("query_audit_log", {
+        payload: {
+          case_no: caseNo ? parseInt(caseNo, 10) : null,
+          year: caseNo && year ? parseInt(year, 10) : null,
+          from_date: formData.get("from-date") || null,
+          to_date: formData.get("to-date") || null,
+        },
+      });
+      setEntries(found);
+    } catch (error) {
+      alert(errorMessage(error));
+    }
+  };
+
+  const handleVerify = async () => {
+    try {
+      setVerification(await invoke<AuditVerification>("verify_audit_log"));
+    } catch (error) {
+      alert(errorMessage(error));
+    }
+  };
+
+  return (
+    <div
+      style={{
+        position: "fixed",
+        top: -100,
+        left: 0,
+        right: 0,
+        bottom: 0,
+        background: "#00000088",
+        display: isOpen ? "flex" : "none",
+        justifyContent: "center",
+        alignItems: "flex-start",
+        paddingTop: "100px",
+        zIndex: 50,
+        overflowY: "auto",
+      }}
+    >
+      <div className="bg-white p-6 rounded-xl shadow-lg w-[90%]">
+        <div className="flex justify-end">
+          <button
+            onClick={() => {
+              setEntries([]);
+              setVerification(null);
+              onClose();
+            }}
+            className="text-gray-600 hover:text-red-600"
+          >
+            <CircleX />
+          </button>
+        </div>
+
+        <h1 className="text-3xl font-bold text-gray-800 mb-8">{title}</h1>
+
+        <form id={formId} className="space-y-4 mb-6" onSubmit={handleSearch}>
+          <div className="flex space-x-4">
+            <input type="text" name="case-no" placeholder="Case number (optional)" className={inputClass} inputMode="numeric" />
+            <input type="text" name="year" placeholder="Year" defaultValue={new Date().getFullYear()} className={inputClass} inputMode="numeric" />
+          </div>
+          <div className="flex space-x-4">
+            <input type="date" name="from-date" title="From" className={inputClass} />
+            <input type="date" name="to-date" title="To" className={inputClass} />
+          </div>
+          <div className="flex space-x-4">
+            <button type="submit" className="flex-1 py-3 rounded-lg text-white bg-blue-600 hover:bg-blue-700">
+              Search
+            </button>
+            <button type="button" onClick={handleVerify} className="flex-1 py-3 rounded-lg text-white bg-slate-600 hover:bg-slate-500">
+              Verify Log
+            </button>
+          </div>
+        </form>
+
+        {verification && (
+          <p className={`mb-4 font-medium ${verification.ok ? "text-green-700" : "text-red-700"}`}>
+            {verification.ok
+              ? `Audit log intact: ${verification.entries_checked} entries checked` +
+                (verification.anchored_to
+                  ? `, and it still holds every entry the backup of ${verification.anchored_to} saw.`
+                  : ". No backup has recorded the log yet, so entries cut off the end would not show.")
+              : verification.problem}
+          </p>
+        )}
+
+        {entries.length > 0 && (
+          <div className="max-h-96 overflow-y-auto">
+            <table className="w-full text-sm text-left">
+              <thead className="bg-slate-100">
+                <tr>
+                  <th className="p-2">When</th>
+                  <th className="p-2">Operator</th>
+                  <th className="p-2">Action</th>
+                  <th className="p-2">Case</th>
+                  <th className="p-2">Before</th>
+                  <th className="p-2">After</th>
+                </tr>
+              </thead>
+              <tbody>
+                {entries.map((entry) => (
+                  <tr key={entry.id} className="border-b align-top">
+                    <td className="p-2 whitespace-nowrap">{entry.recorded_at}</td>
+                    <td className="p-2">{entry.operator}</td>
+                    <td className="p-2">{entry.command}</td>
+                    <td className="p-2">{entry.case_no !== null ? `${entry.case_no}/${entry.year}` : ""}</td>
+                    <td className="p-2"><pre className="whitespace-pre-wrap text-xs">{entry.before ? JSON.stringify(entry.before, null, 1) : ""}</pre></td>
+                    <td className="p-2"><pre className="whitespace-pre-wrap text-xs">{entry.after ? JSON.stringify(entry.after, null, 1) : ""}</pre></td>
+                  </tr>
+                ))}
+              </tbody>
+            </table>
+          </div>
+        )}
+      </div>
+    </div>
+  );
+};
+
+export default AuditLog;
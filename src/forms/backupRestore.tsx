@@ -0,0 +1,291 @@
+import React, { useEffect, useState } from "react";
+import { CircleX } from "lucide-react";
+import { invoke } from "@tauri-apps/api/core";
+import { useAssignedTo } from "../lib/assignedContext";
+import { errorMessage } from "../lib/errors";
+
+interface BackupRestoreProps {
+  isOpen: boolean;
+  onClose: () => void;
+  title: string;
+  formId: string;
+}
+
+type BackupInfo = {
+  path: string;
+  size_bytes: number;
+  created_at: string;
+};
+
+type RestoreInfo = {
+  restored_from: string;
+  previous_copy: string;
+  schema_version: number;
+};
+
+// Returned by export_json and import_json.
+type ArchiveInfo = {
+  path: string;
+  mediators: number;
+  cases: number;
+  hearings: number;
+  audit_entries: number;
+  audit_verified: boolean;
+};
+
+type BackupSchedule = {
+  folder: string | null;
+  on_start: boolean;
+  daily_at: string | null;
+  keep_daily: number;
+  keep_weekly: number;
+  keep_monthly: number;
+};
+
+type BackupRun = {
+  id: number;
+  started_at: string;
+  kind: string;
+  path: string | null;
+  size_bytes: number | null;
+  pruned: number;
+  error: string | null;
+  audit_entries: number | null;
+  audit_head: string | null;
+};
+
+type BackupStatus = {
+  folder: string;
+  last_success: BackupRun | null;
+  last_run: BackupRun | null;
+};
+
+const inputClass = "block w-full px-4 py-3 border border-gray-300 rounded-lg shadow-sm";
+
+const BackupRestore: React.FC<BackupRestoreProps> = ({ isOpen, onClose, title, formId }) => {
+  const { fetchOptions } = useAssignedTo();
+  const [status, setStatus] = useState<string | null>(null);
+  const [schedule, setSchedule] = useState<BackupSchedule | null>(null);
+  const [backupStatus, setBackupStatus] = useState<BackupStatus | null>(null);
+
+  const fetchSchedule = async () => {
+    try {
+      setSchedule(await invoke<BackupSchedule>("get_backup_schedule"));
+      setBackupStatus(await invoke<BackupStatus>("get_backup_status"));
+    } catch (error) {
+      console.error("Failed to fetch backup schedule:", errorMessage(error));
+    }
+  };
+
+  useEffect(() => {
+    if (isOpen) fetchSchedule();
+  }, [isOpen]);
+
+  const handleSchedule = async (e: React.FormEvent<HTMLFormElement>) => {
+    e.preventDefault();
+    const formData = new FormData(e.currentTarget);
+    try {
+      await invoke<BackupSchedule>("set_backup_schedule", {
+        payload: {
+          folder: formData.get("schedule-folder") || null,
+          on_start: formData.get("on-start") === "on",
+          daily_at: formData.get("daily-at") || null,
+          keep_daily: Number(formData.get("keep-daily")),
+          keep_weekly: Number(formData.get("keep-weekly")),
+          keep_monthly: Number(formData.get("keep-monthly")),
+        },
+      });
+      setStatus("Backup schedule saved.");
+      await fetchSchedule();
+    } catch (error) {
+      alert(errorMessage(error));
+    }
+  };
+
+  const handleBackup = async (e: React.FormEvent<HTMLFormElement>) => {
+    e.preventDefault();
+    const formData = new FormData(e.currentTarget);
+    try {
+      const info = await invoke<BackupInfo>("backup_db", {
+        path: formData.get("backup-path") || null,
+      });
+      setStatus(`Backup saved to ${info.path} (${Math.ceil(info.size_bytes / 1024)} KB) at ${info.created_at}.`);
+      await fetchSchedule();
+    } catch (error) {
+      alert(errorMessage(error));
+    }
+  };
+
+  const handleRestore = async (e: React.FormEvent<HTMLFormElement>) => {
+    e.preventDefault();
+    const form = e.currentTarget;
+    const path = (new FormData(form).get("restore-path") as string).trim();
+    if (!confirm(`Replace the current register with ${path}? A copy of the current register is kept first.`)) return;
+
+    try {
+      const info = await invoke<RestoreInfo>("restore_db", { path });
+      setStatus(`Restored from ${info.restored_from}. The previous register was kept at ${info.previous_copy}.`);
+      form.reset();
+      await fetchOptions();
+    } catch (error) {
+      alert(errorMessage(error));
+    }
+  };
+
+  const handleExportJson = async (e: React.FormEvent<HTMLFormElement>) => {
+    e.preventDefault();
+    const path = (new FormData(e.currentTarget).get("archive-export-path") as string).trim();
+    try {
+      const info = await invoke<ArchiveInfo>("export_json", { path });
+      setStatus(`Archived ${info.cases} case(s), ${info.hearings} hearing(s) and ${info.audit_entries} audit entries to ${info.path}.`);
+    } catch (error) {
+      alert(errorMessage(error));
+    }
+  };
+
+  const handleImportJson = async (e: React.FormEvent<HTMLFormElement>) => {
+    e.preventDefault();
+    const form = e.currentTarget;
+    const path = (new FormData(form).get("archive-import-path") as string).trim();
+    if (!confirm(`Rebuild this register from ${path}? This only works on a new, empty register.`)) return;
+
+    try {
+      const info = await invoke<ArchiveInfo>("import_json", { path });
+      setStatus(
+        `Imported ${info.cases} case(s) and ${info.mediators} mediator(s) from ${info.path}.` +
+          (info.audit_verified ? "" : " The archived audit log does not verify; check it under the audit log."),
+      );
+      form.reset();
+      await fetchOptions();
+    } catch (error) {
+      alert(errorMessage(error));
+    }
+  };
+
+  return (
+    <div
+      style={{
+        position: "fixed",
+        top: -100,
+        left: 0,
+        right: 0,
+        bottom: 0,
+        background: "#00000088",
+        display: isOpen ? "flex" : "none",
+        justifyContent: "center",
+        alignItems: "flex-start",
+        paddingTop: "100px",
+        zIndex: 50,
+      }}
+    >
+      <div className="bg-white p-6 rounded-xl shadow-lg w-[75%]">
+        <div className="flex justify-end">
+          <button
+            onClick={() => {
+              setStatus(null);
+              onClose();
+            }}
+            className="text-gray-600 hover:text-red-600"
+          >
+            <CircleX />
+          </button>
+        </div>
+
+        <h1 className="text-3xl font-bold text-gray-800 mb-8">{title}</h1>
+
+        <form id={formId} className="space-y-4 mb-8" onSubmit={handleBackup}>
+          <label htmlFor="backup-path" className="block text-sm font-medium text-gray-700">Back Up To (optional)</label>
+          <input type="text" id="backup-path" name="backup-path" placeholder="Leave blank for the backups folder next to the register" className={inputClass} />
+          <button type="submit" className="w-full py-3 rounded-lg text-white bg-blue-600 hover:bg-blue-700">
+            Back Up Now
+          </button>
+        </form>
+
+        {schedule && (
+          <form key={JSON.stringify(schedule)} className="space-y-4 mb-8" onSubmit={handleSchedule}>
+            <h3 className="text-lg font-semibold">Automatic Backups</h3>
+            {backupStatus && (
+              <div className="text-sm text-slate-600">
+                <p>
+                  Last successful backup:{" "}
+                  {backupStatus.last_success
+                    ? `${backupStatus.last_success.started_at} (${backupStatus.last_success.path})`
+                    : "none yet"}
+                </p>
+                {backupStatus.last_success?.audit_head && (
+                  <p>
+                    Audit log at that backup: {backupStatus.last_success.audit_entries} entries, ending{" "}
+                    <span className="font-mono">{backupStatus.last_success.audit_head.slice(0, 16)}</span>
+                  </p>
+                )}
+                {backupStatus.last_run?.error && (
+                  <p className="text-red-700 font-medium">
+                    Last attempt at {backupStatus.last_run.started_at} failed: {backupStatus.last_run.error}
+                  </p>
+                )}
+              </div>
+            )}
+            <label htmlFor="schedule-folder" className="block text-sm font-medium text-gray-700">Backup Folder</label>
+            <input type="text" id="schedule-folder" name="schedule-folder" defaultValue={schedule.folder ?? ""} placeholder={backupStatus?.folder ?? "e.g. a folder on a USB drive"} className={inputClass} />
+            <div className="flex space-x-4 items-center">
+              <label className="flex items-center gap-2 text-sm text-gray-700">
+                <input type="checkbox" name="on-start" defaultChecked={schedule.on_start} />
+                Back up when the app starts
+              </label>
+              <label htmlFor="daily-at" className="text-sm text-gray-700">Daily at (blank for never)</label>
+              <input type="time" id="daily-at" name="daily-at" defaultValue={schedule.daily_at?.slice(0, 5) ?? ""} className="px-3 py-2 border border-gray-300 rounded-lg shadow-sm" />
+            </div>
+            <div className="flex space-x-4">
+              <label className="flex-1 text-sm text-gray-700">
+                Keep daily
+                <input type="number" name="keep-daily" min={1} defaultValue={schedule.keep_daily} className={inputClass} required />
+              </label>
+              <label className="flex-1 text-sm text-gray-700">
+                Keep weekly
+                <input type="number" name="keep-weekly" min={0} defaultValue={schedule.keep_weekly} className={inputClass} required />
+              </label>
+              <label className="flex-1 text-sm text-gray-700">
+                Keep monthly
+                <input type="number" name="keep-monthly" min={0} defaultValue={schedule.keep_monthly} className={inputClass} required />
+              </label>
+            </div>
+            <button type="submit" className="w-full py-3 rounded-lg text-white bg-blue-600 hover:bg-blue-700">
+              Save Schedule
+            </button>
+          </form>
+        )}
+
+        <form className="space-y-4" onSubmit={handleRestore}>
+          <label htmlFor="restore-path" className="block text-sm font-medium text-gray-700">Restore From</label>
+          <input type="text" id="restore-path" name="restore-path" placeholder="Full path to a backup .db file" className={inputClass} required />
+          <button type="submit" className="w-full py-3 rounded-lg text-white bg-red-600 hover:bg-red-500">
+            Restore
+          </button>
+        </form>
+
+        <div className="space-y-4 mt-8">
+          <h3 className="text-lg font-semibold">JSON Archive</h3>
+          <p className="text-sm text-slate-600">
+            The whole register, history included, as one readable file that a new register can be rebuilt from.
+          </p>
+          <form className="flex gap-2" onSubmit={handleExportJson}>
+            <input type="text" name="archive-export-path" placeholder="Full path for the .json archive" className={inputClass} required />
+            <button type="submit" className="px-6 rounded-lg text-white bg-blue-600 hover:bg-blue-700 whitespace-nowrap">
+              Export JSON
+            </button>
+          </form>
+          <form className="flex gap-2" onSubmit={handleImportJson}>
+            <input type="text" name="archive-import-path" placeholder="Full path to a .json archive" className={inputClass} required />
+            <button type="submit" className="px-6 rounded-lg text-white bg-red-600 hover:bg-red-500 whitespace-nowrap">
+              Import JSON
+            </button>
+          </form>
+        </div>
+
+        {status && <p className="mt-6 text-green-700 font-medium">{status}</p>}
+      </div>
+    </div>
+  );
+};
+
+export default BackupRestore;
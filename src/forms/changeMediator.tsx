@@ -1,7 +1,9 @@
 import React from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { appDataDir, resolve } from "@tauri-apps/api/path";
 import { CircleX } from "lucide-react";
-import { useAssignedTo } from "../lib/assignedContext";
+import { Mediator, mediatorName, useAssignedTo } from "../lib/assignedContext";
+import { errorMessage } from "../lib/errors";
 
 interface ManageAssignedToProps {
   isOpen: boolean;
@@ -9,7 +11,7 @@ interface ManageAssignedToProps {
 }
 
 const ManageAssignedTo: React.FC<ManageAssignedToProps> = ({ isOpen, onClose }) => {
-    const { options, loading } = useAssignedTo();
+    const { mediators, loading, fetchOptions } = useAssignedTo();
   
     if (loading) {
       return <p>Loading...</p>;
@@ -19,18 +21,64 @@ const ManageAssignedTo: React.FC<ManageAssignedToProps> = ({ isOpen, onClose })
   const handleAdd = async (e: React.FormEvent<HTMLFormElement>) => {
     e.preventDefault();
     const formData = new FormData(e.currentTarget);
-    const newName = formData.get("new-assigned-to") as string;
+    const newName = formData.get("display-name") as string;
     if (!newName.trim()) return;
 
-    await invoke("add_assigned_to", { name: newName.trim() });
-    console.log(`Added: ${newName}`);
-    e.currentTarget.reset();
+    const form = e.currentTarget;
+    try {
+      await invoke("add_mediator", {
+        payload: {
+          display_name: newName.trim(),
+          honorific: formData.get("honorific") || null,
+          phone: formData.get("phone") || null,
+          email: formData.get("email") || null,
+          joined_on: formData.get("joined-on") || null,
+        },
+      });
+      console.log(`Added: ${newName}`);
+      form.reset();
+      await fetchOptions();
+    } catch (error) {
+      alert(errorMessage(error));
+    }
+  };
+
+  const handleSetActive = async (mediator: Mediator, active: boolean) => {
+    const name = mediatorName(mediator);
+    if (!active && !confirm(`Retire "${name}"? Their cases stay on record but they won't be offered for new cases.`)) return;
+    try {
+      await invoke("set_mediator_active", { id: mediator.id, active });
+      console.log(`${active ? "Reactivated" : "Retired"}: ${name}`);
+      await fetchOptions();
+    } catch (error) {
+      alert(errorMessage(error));
+    }
+  };
+
+  const rosterPath = async (form: HTMLFormElement) => {
+    const typed = (new FormData(form).get("roster-path") as string).trim();
+    return typed || resolve(await appDataDir(), "mediators-export.toml");
+  };
+
+  const handleImportRoster = async (e: React.MouseEvent<HTMLButtonElement>) => {
+    const path = await rosterPath(e.currentTarget.form!);
+    try {
+      const summary = await invoke<{ added: number; updated: number; unchanged: number }>("import_roster", { path });
+      alert(`Roster imported: ${summary.added} added, ${summary.updated} updated, ${summary.unchanged} unchanged.`);
+      await fetchOptions();
+    } catch (error) {
+      alert(errorMessage(error));
+    }
   };
 
-  const handleDelete = async (name: string) => {
-    if (!confirm(`Are you sure you want to delete "${name}"?`)) return;
-    await invoke("delete_assigned_to", { name });
-    console.log(`Deleted: ${name}`);
+  const handleExportRoster = async (e: React.MouseEvent<HTMLButtonElement>) => {
+    const path = await rosterPath(e.currentTarget.form!);
+    try {
+      const count = await invoke<number>("export_roster", { path });
+      alert(`Exported ${count} mediator(s) to ${path}`);
+    } catch (error) {
+      alert(errorMessage(error));
+    }
   };
 
   return (
@@ -61,45 +109,95 @@ const ManageAssignedTo: React.FC<ManageAssignedToProps> = ({ isOpen, onClose })
           </button>
         </div>
 
-        <h1 className="text-2xl font-bold mb-4">Manage Assigned To</h1>
+        <h1 className="text-2xl font-bold mb-4">Manage Mediators</h1>
 
-        <form onSubmit={handleAdd} className="flex gap-2 mb-6">
-          <input
-            type="text"
-            name="new-assigned-to"
-            placeholder="Add new assigned to..."
-            className="flex-grow border px-4 py-2 rounded shadow-sm"
-            required
-          />
+        <form onSubmit={handleAdd} className="space-y-2 mb-6">
+          <div className="flex gap-2">
+            <input
+              type="text"
+              name="honorific"
+              placeholder="Sh / Ms"
+              className="w-24 border px-4 py-2 rounded shadow-sm"
+            />
+            <input
+              type="text"
+              name="display-name"
+              placeholder="Add new mediator..."
+              className="flex-grow border px-4 py-2 rounded shadow-sm"
+              required
+            />
+          </div>
+          <div className="flex gap-2">
+            <input type="tel" name="phone" placeholder="Phone" className="flex-1 border px-4 py-2 rounded shadow-sm" />
+            <input type="email" name="email" placeholder="Email" className="flex-1 border px-4 py-2 rounded shadow-sm" />
+            <input type="date" name="joined-on" title="Joined on" className="border px-4 py-2 rounded shadow-sm" />
+          </div>
           <button
             type="submit"
-            className="bg-blue-600 text-white px-4 py-2 rounded shadow-sm hover:bg-blue-700"
+            className="w-full bg-blue-600 text-white px-4 py-2 rounded shadow-sm hover:bg-blue-700"
           >
             Add
           </button>
         </form>
 
-        <h3 className="text-lg font-semibold mb-2">Current Assigned To:</h3>
-        {options.length === 0 ? (
+        <h3 className="text-lg font-semibold mb-2">Mediators:</h3>
+        {mediators.length === 0 ? (
           <p className="text-gray-500">No entries found.</p>
         ) : (
           <ul className="space-y-2 max-h-60 overflow-y-auto">
-            {options.map((option) => (
+            {mediators.map((mediator) => (
               <li
-                key={option.value}
+                key={mediator.id}
                 className="flex justify-between items-center border-b py-1"
               >
-                <span>{option.label}</span>
-                <button
-                  onClick={() => handleDelete(option.value)}
-                  className="bg-red-600 text-white px-2 py-1 rounded text-sm hover:bg-red-400"
-                >
-                  Delete
-                </button>
+                <span className={mediator.active ? "" : "text-gray-400"}>
+                  {mediatorName(mediator)}
+                  {mediator.phone && <span className="text-sm text-gray-500"> · {mediator.phone}</span>}
+                  {!mediator.active && " (inactive)"}
+                </span>
+                {mediator.active ? (
+                  <button
+                    onClick={() => handleSetActive(mediator, false)}
+                    className="bg-red-600 text-white px-2 py-1 rounded text-sm hover:bg-red-400"
+                  >
+                    Retire
+                  </button>
+                ) : (
+                  <button
+                    onClick={() => handleSetActive(mediator, true)}
+                    className="bg-green-600 text-white px-2 py-1 rounded text-sm hover:bg-green-500"
+                  >
+                    Reactivate
+                  </button>
+                )}
               </li>
             ))}
           </ul>
         )}
+
+        <h3 className="text-lg font-semibold mt-6 mb-2">Roster File:</h3>
+        <form className="flex gap-2" onSubmit={(e) => e.preventDefault()}>
+          <input
+            type="text"
+            name="roster-path"
+            placeholder="Path to .toml or .json (default: app data folder)"
+            className="flex-grow border px-4 py-2 rounded shadow-sm"
+          />
+          <button
+            type="button"
+            onClick={handleImportRoster}
+            className="bg-blue-600 text-white px-4 py-2 rounded shadow-sm hover:bg-blue-700"
+          >
+            Import
+          </button>
+          <button
+            type="button"
+            onClick={handleExportRoster}
+            className="bg-slate-600 text-white px-4 py-2 rounded shadow-sm hover:bg-slate-500"
+          >
+            Export
+          </button>
+        </form>
       </div>
     </div>
   );
@@ -0,0 +1,104 @@
+import React, { useEffect, useState } from "react";
+import { CircleX } from "lucide-react";
+import { invoke } from "@tauri-apps/api/core";
+import { errorMessage } from "../lib/errors";
+import { DisposalReason } from "../types/case";
+
+interface DisposalReasonsProps {
+  isOpen: boolean;
+  onClose: () => void;
+  title: string;
+  formId: string;
+}
+
+const inputClass = "block w-full px-4 py-3 border border-gray-300 rounded-lg shadow-sm";
+
+const DisposalReasons: React.FC<DisposalReasonsProps> = ({ isOpen, onClose, title, formId }) => {
+  const [reasons, setReasons] = useState<DisposalReason[]>([]);
+
+  const fetchReasons = async () => {
+    try {
+      setReasons(await invoke<DisposalReason[]>("get_disposal_reasons", { includeInactive: true }));
+    } catch (error) {
+      console.error("Error loading disposal reasons:", error);
+    }
+  };
+
+  useEffect(() => {
+    if (isOpen) fetchReasons();
+  }, [isOpen]);
+
+  const handleAdd = async (e: React.FormEvent<HTMLFormElement>) => {
+    e.preventDefault();
+    const form = e.currentTarget;
+    const label = (new FormData(form).get("label") as string).trim();
+    try {
+      await invoke("add_disposal_reason", { label });
+      form.reset();
+      await fetchReasons();
+    } catch (error) {
+      alert(errorMessage(error));
+    }
+  };
+
+  const handleSetActive = async (reason: DisposalReason, active: boolean) => {
+    if (!active && !confirm(`Retire "${reason.label}"? Cases already disposed of with it keep it.`)) return;
+    try {
+      await invoke("set_disposal_reason_active", { id: reason.id, active });
+      await fetchReasons();
+    } catch (error) {
+      alert(errorMessage(error));
+    }
+  };
+
+  return (
+    <div
+      style={{
+        position: "fixed",
+        top: -100,
+        left: 0,
+        right: 0,
+        bottom: 0,
+        background: "#00000088",
+        display: isOpen ? "flex" : "none",
+        justifyContent: "center",
+        alignItems: "flex-start",
+        paddingTop: "100px",
+        zIndex: 50,
+      }}
+    >
+      <div className="bg-white p-6 rounded-xl shadow-lg w-[60%]">
+        <div className="flex justify-end">
+          <button onClick={onClose} className="text-gray-600 hover:text-red-600">
+            <CircleX />
+          </button>
+        </div>
+
+        <h1 className="text-3xl font-bold text-gray-800 mb-8">{title}</h1>
+
+        <form id={formId} className="flex gap-2 mb-6" onSubmit={handleAdd}>
+          <input type="text" name="label" placeholder="New disposal reason" className={inputClass} required />
+          <button type="submit" className="bg-blue-600 text-white px-6 rounded-lg shadow-sm hover:bg-blue-700">
+            Add
+          </button>
+        </form>
+
+        <ul className="divide-y max-h-[24rem] overflow-y-auto">
+          {reasons.map((reason) => (
+            <li key={reason.id} className="flex justify-between items-center py-2">
+              <span className={reason.active ? "" : "text-slate-400 line-through"}>{reason.label}</span>
+              <button
+                onClick={() => handleSetActive(reason, !reason.active)}
+                className="text-sm text-blue-600 hover:underline"
+              >
+                {reason.active ? "Retire" : "Reinstate"}
+              </button>
+            </li>
+          ))}
+        </ul>
+      </div>
+    </div>
+  );
+};
+
+export default DisposalReasons;
@@ -0,0 +1,195 @@
+import React, { useState } from "react";
+import { CircleX } from "lucide-react";
+import { invoke } from "@tauri-apps/api/core";
+import { SelectField } from "./addCase";
+import { useAssignedTo } from "../lib/assignedContext";
+import { useNatureOfCase } from "../lib/natureOfCase";
+import { errorMessage } from "../lib/errors";
+
+interface EditCaseFormProps {
+  isOpen: boolean;
+  onClose: () => void;
+  title: string;
+  formId: string;
+}
+
+type LoadedCase = {
+  case_no: number;
+  year: number;
+  nature_of_case: string;
+  received_from: string;
+  time_slot: string;
+  party1: string;
+  party2: string;
+  mediator_id: number;
+};
+
+const timeSlots = [
+  "10:00", "10:30", "11:00", "11:30", "12:00", "12:30",
+  "13:00", "13:30", "14:00", "14:30", "15:00", "15:30",
+  "16:00", "16:30", "17:00",
+];
+
+const inputClass = "block w-full px-4 py-3 border border-gray-300 rounded-lg shadow-sm";
+
+const EditCaseForm: React.FC<EditCaseFormProps> = ({ isOpen, onClose, title, formId }) => {
+  const { options, allOptions } = useAssignedTo();
+  const { options: natureOfCaseOptions } = useNatureOfCase();
+  const [loaded, setLoaded] = useState<LoadedCase | null>(null);
+
+  // A retired mediator stays selectable only on the cases they already hold.
+  const current = allOptions.find((o) => o.value === String(loaded?.mediator_id));
+  const mediatorOptions =
+    current && !options.some((o) => o.value === current.value) ? [...options, current] : options;
+
+  const close = () => {
+    setLoaded(null);
+    onClose();
+  };
+
+  const handleLoad = async (e: React.FormEvent<HTMLFormElement>) => {
+    e.preventDefault();
+    const formData = new FormData(e.currentTarget);
+    try {
+      const found = await invoke<LoadedCase>("get_case", {
+        caseNo: parseInt(formData.get("case-no")?.toString() || "0", 10),
+        year: parseInt(formData.get("year")?.toString() || "0", 10),
+      });
+      setLoaded(found);
+    } catch (error) {
+      setLoaded(null);
+      alert(errorMessage(error));
+    }
+  };
+
+  const handleSubmit = async (e: React.FormEvent<HTMLFormElement>) => {
+    e.preventDefault();
+    if (!loaded) return;
+    const formData = new FormData(e.currentTarget);
+
+    try {
+      await invoke("edit_case", {
+        payload: {
+          case_no: loaded.case_no,
+          year: loaded.year,
+          corrected: {
+            case_no: parseInt(formData.get("new-case-no")?.toString() || "0", 10),
+            year: parseInt(formData.get("new-year")?.toString() || "0", 10),
+            nature_of_case: formData.get("nature-of-case") || "",
+            received_from: formData.get("received-from") || "",
+            time_slot: formData.get("time-slot") || "",
+            party1: formData.get("party1") || "",
+            party2: formData.get("party2") || "",
+            mediator_id: Number(formData.get("assigned-to") || 0),
+          },
+          edited_by: formData.get("edited-by") || "",
+          reason: formData.get("reason") || "",
+        },
+      });
+      console.log("Case edited successfully");
+      close();
+    } catch (error) {
+      console.error("Error editing case:", error);
+      alert(errorMessage(error));
+    }
+  };
+
+  return (
+    <div
+      style={{
+        position: "fixed",
+        top: -100,
+        left: 0,
+        right: 0,
+        bottom: 0,
+        background: "#00000088",
+        display: isOpen ? "flex" : "none",
+        justifyContent: "center",
+        alignItems: "flex-start",
+        paddingTop: "100px",
+        zIndex: 50,
+        overflowY: "auto",
+      }}
+    >
+      <div className="bg-white p-6 rounded-xl shadow-lg w-[75%]">
+        <div className="flex justify-end">
+          <button onClick={close} className="text-gray-600 hover:text-red-600">
+            <CircleX />
+          </button>
+        </div>
+
+        <h1 className="text-3xl font-bold text-gray-800 mb-8">{title}</h1>
+
+        <form className="flex space-x-4 mb-6" onSubmit={handleLoad}>
+          <input type="text" name="case-no" placeholder="Case number" className={inputClass} inputMode="numeric" required />
+          <input type="text" name="year" placeholder="Year" defaultValue={new Date().getFullYear()} className={inputClass} inputMode="numeric" required />
+          <button type="submit" className="bg-blue-600 text-white px-6 rounded-lg shadow-sm hover:bg-blue-700">
+            Load
+          </button>
+        </form>
+
+        {loaded && (
+          <form
+            id={formId}
+            key={`${loaded.case_no}/${loaded.year}`}
+            className="space-y-6"
+            onSubmit={handleSubmit}
+          >
+            <div className="flex space-x-4">
+              <div className="flex-1">
+                <label htmlFor="new-case-no" className="block text-sm font-medium text-gray-700 mb-1">Case No.</label>
+                <input type="text" id="new-case-no" name="new-case-no" defaultValue={loaded.case_no} className={inputClass} inputMode="numeric" required />
+              </div>
+              <div className="flex-1">
+                <label htmlFor="new-year" className="block text-sm font-medium text-gray-700 mb-1">Year</label>
+                <input type="text" id="new-year" name="new-year" defaultValue={loaded.year} className={inputClass} inputMode="numeric" required />
+              </div>
+            </div>
+
+            <SelectField id="nature-of-case" label="Nature of Case" options={natureOfCaseOptions} defaultValue={loaded.nature_of_case} />
+
+            <div>
+              <label htmlFor="received-from" className="block text-sm font-medium text-gray-700 mb-1">Received From</label>
+              <input type="text" id="received-from" name="received-from" defaultValue={loaded.received_from} className={inputClass} required />
+            </div>
+
+            <SelectField id="time-slot" label="Time of Assignment" options={timeSlots} defaultValue={loaded.time_slot.slice(0, 5)} />
+
+            <div className="flex justify-between">
+              <div className="w-1/2 pr-2">
+                <label htmlFor="party1" className="block text-sm font-medium text-gray-700 mb-1">Party 1</label>
+                <input type="text" id="party1" name="party1" defaultValue={loaded.party1} className={inputClass} />
+              </div>
+              <div className="w-1/2 pl-2">
+                <label htmlFor="party2" className="block text-sm font-medium text-gray-700 mb-1">Party 2</label>
+                <input type="text" id="party2" name="party2" defaultValue={loaded.party2} className={inputClass} />
+              </div>
+            </div>
+
+            <SelectField id="assigned-to" label="Assigned To" options={mediatorOptions} defaultValue={String(loaded.mediator_id)} />
+
+            <div className="flex space-x-4">
+              <div className="flex-1">
+                <label htmlFor="edited-by" className="block text-sm font-medium text-gray-700 mb-1">Corrected By</label>
+                <input type="text" id="edited-by" name="edited-by" className={inputClass} required />
+              </div>
+              <div className="flex-1">
+                <label htmlFor="reason" className="block text-sm font-medium text-gray-700 mb-1">Reason</label>
+                <input type="text" id="reason" name="reason" className={inputClass} required />
+              </div>
+            </div>
+
+            <button
+              type="submit"
+              className="w-full flex justify-center py-3 px-4 border border-transparent rounded-lg shadow-sm text-sm font-medium text-white bg-blue-600 hover:bg-blue-700"
+            >
+              Save Correction
+            </button>
+          </form>
+        )}
+      </div>
+    </div>
+  );
+};
+
+export default EditCaseForm;
@@ -1,14 +1,30 @@
 import React, { useState, useEffect } from "react";
 import { CircleX } from "lucide-react";
 import { SelectField } from "./addCase";
-import { NatureOfCase } from "../types/case";
+import { useNatureOfCase } from "../lib/natureOfCase";
+import { useDisposalOfCase } from "../lib/disposalOfCase";
+import { errorMessage } from "../lib/errors";
 import { invoke } from "@tauri-apps/api/core";
-import jsPDF from "jspdf";
-import autoTable from "jspdf-autotable";
+import { appDataDir, resolve } from "@tauri-apps/api/path";
 import { useAssignedTo } from "../lib/assignedContext";
-import handshake from "../assets/handshake.png"; // Adjust the path as necessary
+import { ExportInfo, ReportInfo } from "../types/case";
 
 
+const dateFieldOptions = [
+  { value: "any", label: "Intake or hearing date" },
+  { value: "intake", label: "Intake date" },
+  { value: "hearing", label: "Hearing date" },
+  { value: "disposal", label: "Disposal date" },
+  { value: "listed", label: "Intake or next hearing date" },
+];
+
+// Each value doubles as the extension of the default file name.
+const outputFormatOptions = [
+  { value: "pdf", label: "PDF report" },
+  { value: "xlsx", label: "Excel workbook (.xlsx)" },
+  { value: "csv", label: "CSV" },
+];
+
 interface GenerateSpecificReportProps {
   isOpen: boolean;
   onClose: () => void;
@@ -20,107 +36,50 @@ const GenerateSpecificReportForm: React.FC<GenerateSpecificReportProps> = ({ isO
   const [currentDate, setCurrentDate] = useState("");
   const [currentDay, setCurrentDay] = useState("");
 
-  const { options, loading } = useAssignedTo();
+  const { allOptions, loading } = useAssignedTo();
 
 
 
 
 
-  const natureOfCaseOptions = Object.values(NatureOfCase).map((option) => ({
-    value: option,
-    label: option.replace(/([a-z])([A-Z])/g, "$1 $2"), // adds space between camel-case words
-  }));
+  const { options: natureOfCaseOptions } = useNatureOfCase();
+  const { options: disposalOfCaseOptions } = useDisposalOfCase();
+  const disposalOptions = [{ value: "None", label: "No disposal yet" }, ...disposalOfCaseOptions];
 
 
 async function handleFilteredCasesReportPDF(formData: FormData, startDate: string, endDate: string) {
   try {
     const payload = {
       nature_of_case: formData.get("natureOfCase") || null,
-      assigned_to: formData.get("assignedTo") || null,
-      start_date: startDate,
-      end_date: endDate,
+      mediator_id: formData.get("assignedTo") ? Number(formData.get("assignedTo")) : null,
+      assigned_as_of: formData.get("assignedAsOf") || null,
+      received_from: formData.get("receivedFrom") || null,
+      party: formData.get("party") || null,
+      year: formData.get("year") ? Number(formData.get("year")) : null,
+      disposal: formData.get("disposal") ? [formData.get("disposal")] : [],
+      date_field: formData.get("dateField") || null,
+      start_date: startDate || null,
+      end_date: endDate || null,
+      sort: [
+        { field: "year", descending: false },
+        { field: "case_no", descending: false },
+      ],
     };
 
-    const result = await invoke("query_cases_with_filters", { payload }) as {
-      cases: Array<any>,
-      summary: {
-        settled: number;
-        not_settled: number;
-        not_fit: number;
-        pending: number;
-      }
-    };
+    const format = (formData.get("outputFormat") as string | null) || "pdf";
+    const typed = (formData.get("reportPath") as string | null)?.trim();
+    const path = typed || await resolve(await appDataDir(), `filtered_case_report.${format}`);
 
-    const sanitize = (text: any) => String(text ?? "").replace(/_/g, " ");
-
-    const doc = new jsPDF();
-    const img = new Image();
-    img.src = handshake;
-
-    await new Promise((resolve) => {
-      img.onload = resolve;
-    });
-
-    const canvas = document.createElement('canvas');
-    canvas.width = img.width;
-    canvas.height = img.height;
-    const ctx = canvas.getContext('2d')!;
-    ctx.drawImage(img, 0, 0);
-
-    const logoBase64 = canvas.toDataURL('image/png');
-
-    // ✅ Add image at the top
-    doc.addImage(logoBase64, 'PNG', 95, 10, 20, 15);
-
-    doc.setFontSize(14);
-    doc.text("Filtered Case Report", 85, 30);
-
-    const headers = [
-      "Case No",
-      "Nature",
-      "Received From",
-      "Date & Time",
-      "Party 1",
-      "Party 2",
-      "Assigned To",
-      "NDOH",
-      "Disposal",
-      "Connected",
-    ];
-
-    const rows = result.cases.map((c) => [
-      sanitize(c.case_no),
-      sanitize(c.nature_of_case),
-      sanitize(c.received_from),
-      sanitize(`${c.date} ${c.time_slot ?? ""}`),
-      sanitize(c.party1),
-      sanitize(c.party2),
-      sanitize(c.assigned_to),
-      c.ndoh_date ? sanitize(`${c.ndoh_date} ${c.ndoh_time ?? ""}`) : "",
-      sanitize(c.disposal_of_case),
-      c.connected ? sanitize(c.connected.toString()) : "No",
-    ]);
-
-    autoTable(doc, {
-      startY: 40, // Lowered to make space for the image
-      head: [headers],
-      body: rows,
-      styles: { fontSize: 6 },
-      // headStyles: { fillColor: [41, 128, 185] },
-    });
-
-    const finalY = (doc as any).lastAutoTable.finalY + 10;
-    doc.setFontSize(12);
-    doc.text("Summary", 14, finalY);
-    doc.setFontSize(10);
-    doc.text(`Settled: ${result.summary.settled}`, 14, finalY + 6);
-    doc.text(`Not Settled: ${result.summary.not_settled}`, 14, finalY + 12);
-    doc.text(`Not Fit: ${result.summary.not_fit}`, 14, finalY + 18);
-    doc.text(`Pending ${result.summary.pending}`, 14, finalY + 24);
-
-    doc.save("filtered_case_report.pdf");
+    if (format === "pdf") {
+      const report = await invoke<ReportInfo>("write_filtered_report", { payload, path });
+      alert(`Saved ${report.cases} case(s) on ${report.pages} page(s) to ${report.path}`);
+    } else {
+      const exported = await invoke<ExportInfo>("export_cases", { payload, path });
+      alert(`Exported ${exported.cases} case(s) to ${exported.path}`);
+    }
   } catch (error) {
     console.error("Error generating filtered PDF report:", error);
+    alert(errorMessage(error));
   }
 }
 
@@ -224,7 +183,68 @@ async function handleFilteredCasesReportPDF(formData: FormData, startDate: strin
           <SelectField
             id="assignedTo"
             label="Assigned To"
-            options={options}
+            options={allOptions}
+          />
+
+          <div>
+            <label htmlFor="assignedAsOf" className="block text-sm font-medium text-gray-700 mb-1">
+              Mediator As Of (optional)
+            </label>
+            <input
+              type="date"
+              id="assignedAsOf"
+              name="assignedAsOf"
+              className="block w-full px-4 py-3 border border-gray-300 rounded-lg shadow-sm"
+            />
+          </div>
+
+          <div>
+            <label htmlFor="receivedFrom" className="block text-sm font-medium text-gray-700 mb-1">
+              Received From contains (optional)
+            </label>
+            <input
+              type="text"
+              id="receivedFrom"
+              name="receivedFrom"
+              className="block w-full px-4 py-3 border border-gray-300 rounded-lg shadow-sm"
+            />
+          </div>
+
+          <div>
+            <label htmlFor="party" className="block text-sm font-medium text-gray-700 mb-1">
+              Party name contains (optional)
+            </label>
+            <input
+              type="text"
+              id="party"
+              name="party"
+              className="block w-full px-4 py-3 border border-gray-300 rounded-lg shadow-sm"
+            />
+          </div>
+
+          <div>
+            <label htmlFor="year" className="block text-sm font-medium text-gray-700 mb-1">
+              Year (optional)
+            </label>
+            <input
+              type="number"
+              id="year"
+              name="year"
+              className="block w-full px-4 py-3 border border-gray-300 rounded-lg shadow-sm"
+            />
+          </div>
+
+          <SelectField
+            id="disposal"
+            label="Disposal"
+            options={disposalOptions}
+          />
+
+          <SelectField
+            id="dateField"
+            label="Dates apply to"
+            options={dateFieldOptions}
+            defaultValue="intake"
           />
 
           <div>
@@ -251,6 +271,25 @@ async function handleFilteredCasesReportPDF(formData: FormData, startDate: strin
             />
           </div>
 
+          <SelectField
+            id="outputFormat"
+            label="Output"
+            options={outputFormatOptions}
+            defaultValue="pdf"
+          />
+
+          <div>
+            <label htmlFor="reportPath" className="block text-sm font-medium text-gray-700 mb-1">
+              Save To (optional)
+            </label>
+            <input
+              type="text"
+              id="reportPath"
+              name="reportPath"
+              placeholder="filtered_case_report.pdf (or .xlsx, .csv) in the app data folder"
+              className="block w-full px-4 py-3 border border-gray-300 rounded-lg shadow-sm"
+            />
+          </div>
 
           <button
             type="submit"
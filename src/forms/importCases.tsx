@@ -0,0 +1,367 @@
+import React, { useEffect, useState } from "react";
+import { CircleX } from "lucide-react";
+import { invoke } from "@tauri-apps/api/core";
+import { useAssignedTo } from "../lib/assignedContext";
+import { errorMessage } from "../lib/errors";
+import { ImportFieldOption, ImportMapping, ImportPreview, ImportProfile, ImportReport } from "../types/case";
+
+interface ImportCasesProps {
+  isOpen: boolean;
+  onClose: () => void;
+  title: string;
+  formId: string;
+}
+
+const inputClass = "block w-full px-4 py-3 border border-gray-300 rounded-lg shadow-sm";
+
+const emptyMapping: ImportMapping = {
+  sheet: null,
+  columns: {},
+  date_format: null,
+  default_time_slot: null,
+  create_missing: false,
+};
+
+const ImportCases: React.FC<ImportCasesProps> = ({ isOpen, onClose, title, formId }) => {
+  const { fetchOptions } = useAssignedTo();
+  const [fields, setFields] = useState<ImportFieldOption[]>([]);
+  const [profiles, setProfiles] = useState<ImportProfile[]>([]);
+  const [path, setPath] = useState("");
+  const [preview, setPreview] = useState<ImportPreview | null>(null);
+  const [mapping, setMapping] = useState<ImportMapping>(emptyMapping);
+  const [profileName, setProfileName] = useState("");
+  const [report, setReport] = useState<ImportReport | null>(null);
+  const [busy, setBusy] = useState(false);
+
+  const fetchProfiles = async () => {
+    try {
+      setProfiles(await invoke<ImportProfile[]>("get_import_profiles"));
+    } catch (error) {
+      console.error("Error loading import profiles:", error);
+    }
+  };
+
+  useEffect(() => {
+    if (!isOpen) return;
+    invoke<ImportFieldOption[]>("get_import_fields").then(setFields);
+    fetchProfiles();
+  }, [isOpen]);
+
+  const handlePreview = async (sheet: string | null) => {
+    try {
+      const loaded = await invoke<ImportPreview>("preview_import", { path, sheet });
+      setPreview(loaded);
+      setReport(null);
+      // Keep what was mapped by hand or from a profile; fill the rest from
+      // headers that already name a field.
+      setMapping((current) => ({
+        ...current,
+        sheet: loaded.sheet,
+        columns: { ...loaded.suggested, ...current.columns },
+      }));
+    } catch (error) {
+      alert(errorMessage(error));
+    }
+  };
+
+  const setColumn = (field: string, header: string) => {
+    setMapping((current) => {
+      const columns = { ...current.columns };
+      if (header) columns[field] = header;
+      else delete columns[field];
+      return { ...current, columns };
+    });
+  };
+
+  const handleLoadProfile = (id: string) => {
+    const profile = profiles.find((p) => String(p.id) === id);
+    if (!profile) return;
+    setMapping(profile.mapping);
+    setProfileName(profile.name);
+  };
+
+  const handleSaveProfile = async () => {
+    try {
+      await invoke<ImportProfile>("save_import_profile", { name: profileName, mapping });
+      await fetchProfiles();
+    } catch (error) {
+      alert(errorMessage(error));
+    }
+  };
+
+  const handleDeleteProfile = async () => {
+    const profile = profiles.find((p) => p.name.toLowerCase() === profileName.trim().toLowerCase());
+    if (!profile || !confirm(`Delete the profile "${profile.name}"?`)) return;
+    try {
+      await invoke("delete_import_profile", { id: profile.id });
+      setProfileName("");
+      await fetchProfiles();
+    } catch (error) {
+      alert(errorMessage(error));
+    }
+  };
+
+  const handleImport = async (dryRun: boolean) => {
+    if (!dryRun && !confirm(`Import the cases from ${path} into the register?`)) return;
+    setBusy(true);
+    try {
+      const result = await invoke<ImportReport>("import_cases", { path, mapping, dryRun });
+      setReport(result);
+      if (result.committed) await fetchOptions();
+    } catch (error) {
+      alert(errorMessage(error));
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const fieldLabel = (code: string) => fields.find((f) => f.code === code)?.label ?? code;
+
+  return (
+    <div
+      style={{
+        position: "fixed",
+        top: -100,
+        left: 0,
+        right: 0,
+        bottom: 0,
+        background: "#00000088",
+        display: isOpen ? "flex" : "none",
+        justifyContent: "center",
+        alignItems: "flex-start",
+        paddingTop: "100px",
+        zIndex: 50,
+      }}
+    >
+      <div className="bg-white p-6 rounded-xl shadow-lg w-[75%] max-h-[85vh] overflow-y-auto">
+        <div className="flex justify-end">
+          <button onClick={onClose} className="text-gray-600 hover:text-red-600">
+            <CircleX />
+          </button>
+        </div>
+
+        <h1 className="text-3xl font-bold text-gray-800 mb-8">{title}</h1>
+
+        <form
+          id={formId}
+          className="flex gap-2 mb-6"
+          onSubmit={(e) => {
+            e.preventDefault();
+            handlePreview(null);
+          }}
+        >
+          <input
+            type="text"
+            value={path}
+            onChange={(e) => setPath(e.target.value)}
+            placeholder="Path to a .csv, .xlsx or .xls register"
+            className={inputClass}
+            required
+          />
+          <button type="submit" className="bg-blue-600 text-white px-6 rounded-lg shadow-sm hover:bg-blue-700">
+            Preview
+          </button>
+        </form>
+
+        {preview && (
+          <div className="space-y-6">
+            <p className="text-sm text-slate-600">
+              {preview.rows} row(s) under {preview.headers.length} column(s)
+              {preview.sheet ? ` on sheet "${preview.sheet}"` : ""}.
+            </p>
+
+            {preview.sheets.length > 1 && (
+              <div>
+                <label htmlFor="importSheet" className="block text-sm font-medium text-gray-700 mb-1">Sheet</label>
+                <select
+                  id="importSheet"
+                  value={preview.sheet ?? ""}
+                  onChange={(e) => handlePreview(e.target.value)}
+                  className={inputClass}
+                >
+                  {preview.sheets.map((sheet) => (
+                    <option key={sheet} value={sheet}>{sheet}</option>
+                  ))}
+                </select>
+              </div>
+            )}
+
+            <div className="overflow-x-auto">
+              <table className="text-xs border">
+                <thead>
+                  <tr>
+                    {preview.headers.map((header, i) => (
+                      <th key={i} className="border px-2 py-1 bg-slate-100">{header}</th>
+                    ))}
+                  </tr>
+                </thead>
+                <tbody>
+                  {preview.sample.map((row, i) => (
+                    <tr key={i}>
+                      {row.map((cell, j) => (
+                        <td key={j} className="border px-2 py-1 whitespace-nowrap">{cell}</td>
+                      ))}
+                    </tr>
+                  ))}
+                </tbody>
+              </table>
+            </div>
+
+            <div className="flex gap-2 items-end">
+              <div className="flex-1">
+                <label htmlFor="importProfile" className="block text-sm font-medium text-gray-700 mb-1">Profile</label>
+                <select
+                  id="importProfile"
+                  value=""
+                  onChange={(e) => handleLoadProfile(e.target.value)}
+                  className={inputClass}
+                >
+                  <option value="">Load a saved mapping</option>
+                  {profiles.map((profile) => (
+                    <option key={profile.id} value={profile.id}>{profile.name}</option>
+                  ))}
+                </select>
+              </div>
+              <input
+                type="text"
+                value={profileName}
+                onChange={(e) => setProfileName(e.target.value)}
+                placeholder="Profile name"
+                className={`${inputClass} flex-1`}
+              />
+              <button
+                type="button"
+                onClick={handleSaveProfile}
+                disabled={!profileName.trim()}
+                className="bg-blue-600 text-white px-6 py-3 rounded-lg shadow-sm hover:bg-blue-700 disabled:opacity-50"
+              >
+                Save
+              </button>
+              <button type="button" onClick={handleDeleteProfile} className="text-sm text-red-600 hover:underline px-2 py-3">
+                Delete
+              </button>
+            </div>
+
+            <div className="grid grid-cols-2 gap-4">
+              {fields.map((field) => (
+                <div key={field.code}>
+                  <label htmlFor={`map-${field.code}`} className="block text-sm font-medium text-gray-700 mb-1">
+                    {field.label}{field.required ? " *" : ""}
+                  </label>
+                  <select
+                    id={`map-${field.code}`}
+                    value={mapping.columns[field.code] ?? ""}
+                    onChange={(e) => setColumn(field.code, e.target.value)}
+                    className={inputClass}
+                  >
+                    <option value="">Not in this file</option>
+                    {preview.headers.filter((h) => h).map((header) => (
+                      <option key={header} value={header}>{header}</option>
+                    ))}
+                  </select>
+                </div>
+              ))}
+            </div>
+
+            <div className="grid grid-cols-2 gap-4">
+              <div>
+                <label htmlFor="importDateFormat" className="block text-sm font-medium text-gray-700 mb-1">
+                  Date Format (optional, e.g. %d.%m.%Y)
+                </label>
+                <input
+                  id="importDateFormat"
+                  type="text"
+                  value={mapping.date_format ?? ""}
+                  onChange={(e) => setMapping({ ...mapping, date_format: e.target.value || null })}
+                  className={inputClass}
+                />
+              </div>
+              <div>
+                <label htmlFor="importTimeSlot" className="block text-sm font-medium text-gray-700 mb-1">
+                  Time Slot for rows without one (optional)
+                </label>
+                <input
+                  id="importTimeSlot"
+                  type="time"
+                  value={mapping.default_time_slot ?? ""}
+                  onChange={(e) => setMapping({ ...mapping, default_time_slot: e.target.value || null })}
+                  className={inputClass}
+                />
+              </div>
+            </div>
+
+            <label className="flex items-center gap-2 text-sm text-gray-700">
+              <input
+                type="checkbox"
+                checked={mapping.create_missing}
+                onChange={(e) => setMapping({ ...mapping, create_missing: e.target.checked })}
+              />
+              Add mediators and disposal reasons not on the register yet (as retired)
+            </label>
+
+            <div className="flex gap-4">
+              <button
+                type="button"
+                disabled={busy}
+                onClick={() => handleImport(true)}
+                className="flex-1 py-3 px-4 rounded-lg shadow-sm text-sm font-medium text-blue-700 border border-blue-600 hover:bg-blue-50 disabled:opacity-50"
+              >
+                Check (Dry Run)
+              </button>
+              <button
+                type="button"
+                disabled={busy}
+                onClick={() => handleImport(false)}
+                className="flex-1 py-3 px-4 rounded-lg shadow-sm text-sm font-medium text-white bg-blue-600 hover:bg-blue-700 disabled:opacity-50"
+              >
+                Import
+              </button>
+            </div>
+          </div>
+        )}
+
+        {report && (
+          <div className="mt-6 space-y-4 text-sm">
+            <p className={report.errors.length ? "text-red-600 font-medium" : "text-slate-700"}>
+              {report.committed
+                ? `Imported ${report.imported} of ${report.rows} row(s).`
+                : `${report.valid} of ${report.rows} row(s) ready to import, ${report.errors.length} error(s). Nothing has been written.`}
+              {report.conflicts.length > 0 && ` ${report.conflicts.length} row(s) already on the register are skipped.`}
+            </p>
+
+            {report.new_mediators.length > 0 && (
+              <p>New mediators: {report.new_mediators.join(", ")}</p>
+            )}
+            {report.new_disposal_reasons.length > 0 && (
+              <p>New disposal reasons: {report.new_disposal_reasons.join(", ")}</p>
+            )}
+
+            {report.errors.length > 0 && (
+              <ul className="divide-y max-h-[16rem] overflow-y-auto border rounded-lg">
+                {report.errors.map((issue, i) => (
+                  <li key={i} className="px-3 py-1">
+                    Row {issue.row}, {fieldLabel(issue.field)}
+                    {issue.value ? ` "${issue.value}"` : ""}: {issue.message}
+                  </li>
+                ))}
+              </ul>
+            )}
+
+            {report.conflicts.length > 0 && (
+              <ul className="divide-y max-h-[12rem] overflow-y-auto border rounded-lg">
+                {report.conflicts.map((conflict) => (
+                  <li key={conflict.row} className="px-3 py-1 text-slate-600">
+                    Row {conflict.row}: case {conflict.case_no} of {conflict.year} is already registered
+                    ({conflict.party1} vs {conflict.party2})
+                  </li>
+                ))}
+              </ul>
+            )}
+          </div>
+        )}
+      </div>
+    </div>
+  );
+};
+
+export default ImportCases;
@@ -0,0 +1,177 @@
+import React, { useState } from "react";
+import { CircleX } from "lucide-react";
+import { invoke } from "@tauri-apps/api/core";
+import { appDataDir, resolve } from "@tauri-apps/api/path";
+import { errorMessage } from "../lib/errors";
+import { MonthlyReturn as MonthlyReturnData, ReturnCounts, ReturnFileInfo } from "../types/case";
+
+interface MonthlyReturnProps {
+  isOpen: boolean;
+  onClose: () => void;
+  title: string;
+  formId: string;
+}
+
+const inputClass = "block w-full px-4 py-3 border border-gray-300 rounded-lg shadow-sm";
+
+const returnColumns = [
+  "Opening Balance",
+  "Received",
+  "Total",
+  "Settled",
+  "Not Settled",
+  "Not Fit",
+  "Returned",
+  "Total Disposed",
+  "Closing Balance",
+];
+
+const countCells = (c: ReturnCounts) => [
+  c.opening,
+  c.received,
+  c.opening + c.received,
+  c.settled,
+  c.not_settled,
+  c.not_fit,
+  c.returned,
+  c.disposed,
+  c.closing,
+];
+
+// The month before this one, as "YYYY-MM": the return is usually sent
+// once the month is over.
+const lastMonth = () => {
+  const now = new Date();
+  const date = new Date(now.getFullYear(), now.getMonth() - 1, 1);
+  return `${date.getFullYear()}-${String(date.getMonth() + 1).padStart(2, "0")}`;
+};
+
+const MonthlyReturn: React.FC<MonthlyReturnProps> = ({ isOpen, onClose, title, formId }) => {
+  const [period, setPeriod] = useState(lastMonth());
+  const [statement, setStatement] = useState<MonthlyReturnData | null>(null);
+
+  const yearMonth = () => {
+    const [year, month] = period.split("-").map(Number);
+    return { year, month };
+  };
+
+  const handleShow = async (e: React.FormEvent<HTMLFormElement>) => {
+    e.preventDefault();
+    try {
+      setStatement(await invoke<MonthlyReturnData>("monthly_return", yearMonth()));
+    } catch (error) {
+      alert(errorMessage(error));
+    }
+  };
+
+  const handleSave = async (format: "pdf" | "xlsx") => {
+    try {
+      const path = await resolve(await appDataDir(), `monthly_return_${period}.${format}`);
+      const info = await invoke<ReturnFileInfo>("write_monthly_return", { ...yearMonth(), path });
+      alert(`Saved the return to ${info.path}`);
+    } catch (error) {
+      alert(errorMessage(error));
+    }
+  };
+
+  const table = (first: string, rows: [string, ReturnCounts][]) => (
+    <table className="w-full text-sm border mb-6">
+      <thead>
+        <tr>
+          <th className="border px-2 py-1 bg-slate-100 text-left">{first}</th>
+          {returnColumns.map((column) => (
+            <th key={column} className="border px-2 py-1 bg-slate-100">{column}</th>
+          ))}
+        </tr>
+      </thead>
+      <tbody>
+        {rows.map(([label, counts]) => (
+          <tr key={label}>
+            <td className="border px-2 py-1">{label}</td>
+            {countCells(counts).map((n, i) => (
+              <td key={i} className="border px-2 py-1 text-right">{n}</td>
+            ))}
+          </tr>
+        ))}
+      </tbody>
+    </table>
+  );
+
+  return (
+    <div
+      style={{
+        position: "fixed",
+        top: -100,
+        left: 0,
+        right: 0,
+        bottom: 0,
+        background: "#00000088",
+        display: isOpen ? "flex" : "none",
+        justifyContent: "center",
+        alignItems: "flex-start",
+        paddingTop: "100px",
+        zIndex: 50,
+      }}
+    >
+      <div className="bg-white p-6 rounded-xl shadow-lg w-[75%] max-h-[85vh] overflow-y-auto">
+        <div className="flex justify-end">
+          <button onClick={onClose} className="text-gray-600 hover:text-red-600">
+            <CircleX />
+          </button>
+        </div>
+
+        <h1 className="text-3xl font-bold text-gray-800 mb-8">{title}</h1>
+
+        <form id={formId} className="flex gap-2 mb-6" onSubmit={handleShow}>
+          <input
+            type="month"
+            value={period}
+            onChange={(e) => {
+              setPeriod(e.target.value);
+              setStatement(null);
+            }}
+            className={inputClass}
+            required
+          />
+          <button type="submit" className="bg-blue-600 text-white px-6 rounded-lg shadow-sm hover:bg-blue-700">
+            Show
+          </button>
+        </form>
+
+        {statement && (
+          <div>
+            <p className="text-sm text-slate-600 mb-4">
+              {statement.period_start} to {statement.period_end}. Settled includes partial settlements; Returned covers
+              cases withdrawn, returned unserved or referred to the Lok Adalat.
+            </p>
+
+            {table("", [["All cases", statement.totals]])}
+            {statement.by_nature.length > 0 &&
+              table("Nature of Case", statement.by_nature.map((n): [string, ReturnCounts] => [n.label, n.counts]))}
+            {statement.by_court.length > 0 &&
+              table("Referring Court", statement.by_court.map((c): [string, ReturnCounts] => [c.received_from, c.counts]))}
+
+            <div className="flex gap-4">
+              <button
+                type="button"
+                onClick={() => handleSave("pdf")}
+                className="flex-1 py-3 px-4 rounded-lg shadow-sm text-sm font-medium text-white bg-blue-600 hover:bg-blue-700"
+              >
+                Save as PDF
+              </button>
+              <button
+                type="button"
+                onClick={() => handleSave("xlsx")}
+                className="flex-1 py-3 px-4 rounded-lg shadow-sm text-sm font-medium text-blue-700 border border-blue-600 hover:bg-blue-50"
+              >
+                Save as Excel
+              </button>
+            </div>
+          </div>
+        )}
+      </div>
+    </div>
+  );
+};
+
+export default MonthlyReturn;
@@ -0,0 +1,150 @@
+import React, { useEffect, useState } from "react";
+import { CircleX } from "lucide-react";
+import { invoke } from "@tauri-apps/api/core";
+import { errorMessage } from "../lib/errors";
+
+export type RecentDatabase = {
+  path: string;
+  last_opened: string;
+  exists: boolean;
+};
+
+interface OpenRegisterProps {
+  isOpen: boolean;
+  onClose: () => void;
+  title: string;
+  formId: string;
+  register: string | null;
+  onOpenRegister: (path: string, seed?: boolean) => Promise<void>;
+  onCloseRegister: () => Promise<void>;
+}
+
+const inputClass = "block w-full px-4 py-3 border border-gray-300 rounded-lg shadow-sm";
+
+const OpenRegister: React.FC<OpenRegisterProps> = ({ isOpen, onClose, title, formId, register, onOpenRegister, onCloseRegister }) => {
+  const [recent, setRecent] = useState<RecentDatabase[]>([]);
+
+  const fetchRecent = async () => {
+    try {
+      setRecent(await invoke<RecentDatabase[]>("get_recent_databases"));
+    } catch (error) {
+      console.error("Failed to fetch recent databases:", errorMessage(error));
+    }
+  };
+
+  useEffect(() => {
+    if (isOpen) fetchRecent();
+  }, [isOpen]);
+
+  const open = async (path: string, seed = true) => {
+    try {
+      await onOpenRegister(path, seed);
+      onClose();
+    } catch (error) {
+      alert(errorMessage(error));
+    }
+  };
+
+  const handleSubmit = async (e: React.FormEvent<HTMLFormElement>) => {
+    e.preventDefault();
+    const form = e.currentTarget;
+    const formData = new FormData(form);
+    const path = (formData.get("register-path") as string).trim();
+    await open(path, formData.get("leave-empty") === null);
+    form.reset();
+  };
+
+  const handleForget = async (path: string) => {
+    try {
+      setRecent(await invoke<RecentDatabase[]>("forget_recent_database", { path }));
+    } catch (error) {
+      alert(errorMessage(error));
+    }
+  };
+
+  const handleCloseRegister = async () => {
+    if (!confirm("Close the current register? Nothing can be entered until a register is opened again.")) return;
+    try {
+      await onCloseRegister();
+      await fetchRecent();
+    } catch (error) {
+      alert(errorMessage(error));
+    }
+  };
+
+  return (
+    <div
+      style={{
+        position: "fixed",
+        top: -100,
+        left: 0,
+        right: 0,
+        bottom: 0,
+        background: "#00000088",
+        display: isOpen ? "flex" : "none",
+        justifyContent: "center",
+        alignItems: "flex-start",
+        paddingTop: "100px",
+        zIndex: 50,
+      }}
+    >
+      <div className="bg-white p-6 rounded-xl shadow-lg w-[75%]">
+        <div className="flex justify-end">
+          <button onClick={onClose} className="text-gray-600 hover:text-red-600">
+            <CircleX />
+          </button>
+        </div>
+
+        <h1 className="text-3xl font-bold text-gray-800 mb-4">{title}</h1>
+        <p className="text-slate-600 mb-6">
+          Open now: <span className="font-medium">{register ?? "no register"}</span>
+        </p>
+
+        <form id={formId} className="mb-8" onSubmit={handleSubmit}>
+          <div className="flex gap-2">
+            <input type="text" name="register-path" placeholder="Full path to a register .db file (a new one is created if missing)" className={inputClass} required />
+            <button type="submit" className="bg-blue-600 text-white px-6 rounded-lg shadow-sm hover:bg-blue-700">
+              Open
+            </button>
+          </div>
+          <label className="flex items-center gap-2 mt-2 text-sm text-slate-600">
+            <input type="checkbox" name="leave-empty" />
+            Leave it empty, to import a JSON archive into (skips any mediators.toml next to a new register)
+          </label>
+        </form>
+
+        <h3 className="text-lg font-semibold mb-2">Recent Registers</h3>
+        <ul className="divide-y mb-6">
+          {recent.map((entry) => (
+            <li key={entry.path} className="flex items-center justify-between py-2 gap-4">
+              <div className={entry.exists ? "" : "text-gray-400"}>
+                <p className="font-medium break-all">{entry.path}</p>
+                <p className="text-xs">Last opened {entry.last_opened}{entry.exists ? "" : " (file not found)"}</p>
+              </div>
+              <div className="flex gap-2">
+                <button
+                  onClick={() => open(entry.path)}
+                  disabled={!entry.exists || entry.path === register}
+                  className="bg-blue-600 text-white px-4 py-1 rounded-lg hover:bg-blue-700 disabled:bg-gray-300"
+                >
+                  Open
+                </button>
+                <button onClick={() => handleForget(entry.path)} className="bg-slate-200 px-4 py-1 rounded-lg hover:bg-slate-300">
+                  Forget
+                </button>
+              </div>
+            </li>
+          ))}
+        </ul>
+
+        {register && (
+          <button onClick={handleCloseRegister} className="w-full py-3 rounded-lg text-white bg-red-600 hover:bg-red-500">
+            Close Current Register
+          </button>
+        )}
+      </div>
+    </div>
+  );
+};
+
+export default OpenRegister;
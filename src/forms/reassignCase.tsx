@@ -0,0 +1,106 @@
+import React from "react";
+import { CircleX } from "lucide-react";
+import { invoke } from "@tauri-apps/api/core";
+import { SelectField } from "./addCase";
+import { useAssignedTo } from "../lib/assignedContext";
+import { errorMessage } from "../lib/errors";
+
+interface ReassignCaseFormProps {
+  isOpen: boolean;
+  onClose: () => void;
+  title: string;
+  formId: string;
+}
+
+const inputClass = "block w-full px-4 py-3 border border-gray-300 rounded-lg shadow-sm";
+
+const ReassignCaseForm: React.FC<ReassignCaseFormProps> = ({ isOpen, onClose, title, formId }) => {
+  const { options } = useAssignedTo();
+
+  const handleSubmit = async (e: React.FormEvent<HTMLFormElement>) => {
+    e.preventDefault();
+    const form = e.currentTarget;
+    const formData = new FormData(form);
+
+    try {
+      await invoke("reassign_case", {
+        payload: {
+          case_no: parseInt(formData.get("case-no")?.toString() || "0", 10),
+          year: parseInt(formData.get("year")?.toString() || "0", 10),
+          to_mediator_id: Number(formData.get("to-mediator") || 0),
+          transferred_on: formData.get("transferred-on") || "",
+          reason: formData.get("reason") || "",
+        },
+      });
+      console.log("Case reassigned successfully");
+      form.reset();
+      onClose();
+    } catch (error) {
+      console.error("Error reassigning case:", error);
+      alert(errorMessage(error));
+    }
+  };
+
+  return (
+    <div
+      style={{
+        position: "fixed",
+        top: -100,
+        left: 0,
+        right: 0,
+        bottom: 0,
+        background: "#00000088",
+        display: isOpen ? "flex" : "none",
+        justifyContent: "center",
+        alignItems: "flex-start",
+        paddingTop: "100px",
+        zIndex: 50,
+      }}
+    >
+      <div className="bg-white p-6 rounded-xl shadow-lg w-[75%]">
+        <div className="flex justify-end">
+          <button
+            onClick={() => {
+              const form = document.getElementById(formId) as HTMLFormElement | null;
+              if (form) form.reset();
+              onClose();
+            }}
+            className="text-gray-600 hover:text-red-600"
+          >
+            <CircleX />
+          </button>
+        </div>
+
+        <h1 className="text-3xl font-bold text-gray-800 mb-8">{title}</h1>
+
+        <form id={formId} className="space-y-6" onSubmit={handleSubmit}>
+          <div className="flex space-x-4">
+            <input type="text" name="case-no" placeholder="Enter case number" className={inputClass} pattern="\d{6}" maxLength={6} inputMode="numeric" required />
+            <input type="text" name="year" placeholder="Enter year" defaultValue={new Date().getFullYear()} className={inputClass} pattern="\d{4}" maxLength={4} inputMode="numeric" required />
+          </div>
+
+          <SelectField id="to-mediator" label="New Mediator" options={options} />
+
+          <div>
+            <label htmlFor="transferred-on" className="block text-sm font-medium text-gray-700 mb-1">With Effect From</label>
+            <input type="date" id="transferred-on" name="transferred-on" className={inputClass} required />
+          </div>
+
+          <div>
+            <label htmlFor="reason" className="block text-sm font-medium text-gray-700 mb-1">Reason</label>
+            <input type="text" id="reason" name="reason" placeholder="e.g. mediator on leave" className={inputClass} required />
+          </div>
+
+          <button
+            type="submit"
+            className="w-full flex justify-center py-3 px-4 border border-transparent rounded-lg shadow-sm text-sm font-medium text-white bg-blue-600 hover:bg-blue-700"
+          >
+            Reassign
+          </button>
+        </form>
+      </div>
+    </div>
+  );
+};
+
+export default ReassignCaseForm;
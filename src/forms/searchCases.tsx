@@ -0,0 +1,138 @@
+import React, { useState } from "react";
+import { CircleX } from "lucide-react";
+import { invoke } from "@tauri-apps/api/core";
+import { errorMessage } from "../lib/errors";
+import { useNatureOfCase } from "../lib/natureOfCase";
+
+interface SearchCasesProps {
+  isOpen: boolean;
+  onClose: () => void;
+  title: string;
+  formId: string;
+}
+
+type CaseSearchHit = {
+  case: {
+    id: number;
+    case_no: number;
+    year: number;
+    nature_of_case: string;
+    received_from: string;
+    date: string;
+    party1: string;
+    party2: string;
+    assigned_to: string;
+    ndoh_date: string | null;
+    disposal_of_case: string | null;
+  };
+  snippet: string;
+  rank: number;
+};
+
+// Matches come back wrapped in \u0002 ... \u0003 (see src-tauri/src/search.rs).
+const Snippet: React.FC<{ text: string }> = ({ text }) => (
+  <>
+    {text.split("\u0002").map((part, i) => {
+      if (i === 0) return <span key={i}>{part}</span>;
+      const [match, rest] = part.split("\u0003");
+      return (
+        <span key={i}>
+          <mark className="bg-yellow-200">{match}</mark>
+          {rest}
+        </span>
+      );
+    })}
+  </>
+);
+
+const inputClass = "block w-full px-4 py-3 border border-gray-300 rounded-lg shadow-sm";
+
+const SearchCases: React.FC<SearchCasesProps> = ({ isOpen, onClose, title, formId }) => {
+  const [hits, setHits] = useState<CaseSearchHit[] | null>(null);
+  const { labelFor } = useNatureOfCase();
+
+  const handleSearch = async (e: React.FormEvent<HTMLFormElement>) => {
+    e.preventDefault();
+    const query = (new FormData(e.currentTarget).get("query") as string).trim();
+    try {
+      setHits(await invoke<CaseSearchHit[]>("search_cases", { query }));
+    } catch (error) {
+      alert(errorMessage(error));
+    }
+  };
+
+  return (
+    <div
+      style={{
+        position: "fixed",
+        top: -100,
+        left: 0,
+        right: 0,
+        bottom: 0,
+        background: "#00000088",
+        display: isOpen ? "flex" : "none",
+        justifyContent: "center",
+        alignItems: "flex-start",
+        paddingTop: "100px",
+        zIndex: 50,
+        overflowY: "auto",
+      }}
+    >
+      <div className="bg-white p-6 rounded-xl shadow-lg w-[90%]">
+        <div className="flex justify-end">
+          <button
+            onClick={() => {
+              setHits(null);
+              onClose();
+            }}
+            className="text-gray-600 hover:text-red-600"
+          >
+            <CircleX />
+          </button>
+        </div>
+
+        <h1 className="text-3xl font-bold text-gray-800 mb-8">{title}</h1>
+
+        <form id={formId} className="flex gap-2 mb-6" onSubmit={handleSearch}>
+          <input type="text" name="query" placeholder="Party name, court, case number or remark" className={inputClass} required autoFocus />
+          <button type="submit" className="bg-blue-600 text-white px-6 rounded-lg shadow-sm hover:bg-blue-700">
+            Search
+          </button>
+        </form>
+
+        {hits && hits.length === 0 && <p className="text-slate-600">No matching cases.</p>}
+
+        {hits && hits.length > 0 && (
+          <div className="max-h-[32rem] overflow-y-auto">
+            <table className="w-full text-sm text-left">
+              <thead className="bg-slate-100">
+                <tr>
+                  <th className="p-2">Case</th>
+                  <th className="p-2">Parties</th>
+                  <th className="p-2">Nature</th>
+                  <th className="p-2">Mediator</th>
+                  <th className="p-2">Next Date</th>
+                  <th className="p-2">Match</th>
+                </tr>
+              </thead>
+              <tbody>
+                {hits.map(({ case: c, snippet }) => (
+                  <tr key={c.id} className="border-b align-top">
+                    <td className="p-2 whitespace-nowrap">{c.case_no}/{c.year}</td>
+                    <td className="p-2">{c.party1} vs {c.party2}</td>
+                    <td className="p-2">{labelFor(c.nature_of_case)}</td>
+                    <td className="p-2">{c.assigned_to}</td>
+                    <td className="p-2 whitespace-nowrap">{c.ndoh_date ?? ""}</td>
+                    <td className="p-2"><Snippet text={snippet} /></td>
+                  </tr>
+                ))}
+              </tbody>
+            </table>
+          </div>
+        )}
+      </div>
+    </div>
+  );
+};
+
+export default SearchCases;
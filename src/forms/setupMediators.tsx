@@ -0,0 +1,102 @@
+import React from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { errorMessage } from "../lib/errors";
+
+interface SetupMediatorsProps {
+  isOpen: boolean;
+  onDone: () => void;
+}
+
+type RosterImportSummary = {
+  added: number;
+  updated: number;
+  unchanged: number;
+};
+
+const inputClass = "block w-full px-4 py-3 border border-gray-300 rounded-lg shadow-sm";
+
+// Shown on first run, while the register has no mediators at all.
+const SetupMediators: React.FC<SetupMediatorsProps> = ({ isOpen, onDone }) => {
+  const handleNames = async (e: React.FormEvent<HTMLFormElement>) => {
+    e.preventDefault();
+    const formData = new FormData(e.currentTarget);
+    const names = (formData.get("names") as string)
+      .split("\n")
+      .map((n) => n.trim())
+      .filter((n) => n.length > 0);
+    const centreName = (formData.get("centre-name") as string).trim();
+
+    try {
+      const summary = await invoke<RosterImportSummary>("setup_mediators", {
+        names,
+        centreName: centreName || null,
+      });
+      console.log(`Set up ${summary.added} mediator(s)`);
+      onDone();
+    } catch (error) {
+      alert(errorMessage(error));
+    }
+  };
+
+  const handleImport = async (e: React.FormEvent<HTMLFormElement>) => {
+    e.preventDefault();
+    const formData = new FormData(e.currentTarget);
+
+    try {
+      const summary = await invoke<RosterImportSummary>("import_roster", {
+        path: (formData.get("roster-path") as string).trim(),
+      });
+      console.log(`Imported ${summary.added} mediator(s)`);
+      onDone();
+    } catch (error) {
+      alert(errorMessage(error));
+    }
+  };
+
+  return (
+    <div
+      style={{
+        position: "fixed",
+        top: 0,
+        left: 0,
+        right: 0,
+        bottom: 0,
+        background: "#00000088",
+        display: isOpen ? "flex" : "none",
+        justifyContent: "center",
+        alignItems: "flex-start",
+        paddingTop: "60px",
+        zIndex: 60,
+      }}
+    >
+      <div className="bg-white p-6 rounded-xl shadow-lg w-[75%] max-w-2xl">
+        <h1 className="text-3xl font-bold text-gray-800 mb-2">Set Up Mediators</h1>
+        <p className="text-gray-600 mb-6">
+          No mediators are registered yet. Enter the panel below, one per line (e.g. "Sh Surender Singh"),
+          or import a roster file.
+        </p>
+
+        <form className="space-y-4 mb-8" onSubmit={handleNames}>
+          <input type="text" name="centre-name" placeholder="Centre name printed on reports (optional)" className={inputClass} />
+          <textarea name="names" rows={8} className={inputClass} placeholder={"Judge In Charge\nSh Surender Singh\nMs Shashi Jaiswal"} required />
+          <button
+            type="submit"
+            className="w-full flex justify-center py-3 px-4 rounded-lg shadow-sm text-sm font-medium text-white bg-blue-600 hover:bg-blue-700"
+          >
+            Save Mediators
+          </button>
+        </form>
+
+        <h3 className="text-lg font-semibold mb-2">Import Roster File (.toml or .json)</h3>
+        <form className="flex gap-2" onSubmit={handleImport}>
+          <input type="text" name="roster-path" placeholder="Full path to mediators.toml" className={inputClass} required />
+          <button type="submit" className="bg-blue-600 text-white px-6 rounded-lg shadow-sm hover:bg-blue-700">
+            Import
+          </button>
+        </form>
+      </div>
+    </div>
+  );
+};
+
+export default SetupMediators;
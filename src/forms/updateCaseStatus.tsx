@@ -1,8 +1,10 @@
 import React, { useEffect, useState } from "react";
 import { CircleX } from "lucide-react";
 import { SelectField } from "./addCase";
-import { DisposalOfCase } from "../types/case";
+import { DisposalReason } from "../types/case";
+import { useDisposalOfCase } from "../lib/disposalOfCase";
 import { invoke } from "@tauri-apps/api/core";
+import { errorMessage } from "../lib/errors";
 
 interface CaseUpdateFormProps {
   isOpen: boolean;
@@ -14,11 +16,13 @@ interface CaseUpdateFormProps {
 const CaseUpdateForm: React.FC<CaseUpdateFormProps> = ({ isOpen, onClose, title, formId }) => {
   const [currentDate, setCurrentDate] = useState("");
   const [currentDay, setCurrentDay] = useState("");
+  const [disposal, setDisposal] = useState("");
+  const [reasons, setReasons] = useState<DisposalReason[]>([]);
 
-  const caseType = Object.values(DisposalOfCase).map((option) => ({
-      value: option,
-      label: option,
-  }));
+  const { options: caseType, isFinal: isFinalCode } = useDisposalOfCase();
+
+  // A final outcome is recorded with its date, reason and remarks.
+  const isFinal = isFinalCode(disposal);
 
   useEffect(() => {
     const now = new Date();
@@ -28,9 +32,17 @@ const CaseUpdateForm: React.FC<CaseUpdateFormProps> = ({ isOpen, onClose, title,
     setCurrentDay(dayStr);
   }, []);
 
+  useEffect(() => {
+    if (!isOpen) return;
+    invoke<DisposalReason[]>("get_disposal_reasons")
+      .then(setReasons)
+      .catch((err) => console.error("Error loading disposal reasons:", err));
+  }, [isOpen]);
+
   const handleSubmit = (e: React.FormEvent<HTMLFormElement>) => {
     e.preventDefault();
-    const formData = new FormData(e.currentTarget);
+    const form = e.currentTarget;
+    const formData = new FormData(form);
 
     const ndohDateStr = formData.get("ndoh-date")?.toString();
     if (ndohDateStr) {
@@ -47,19 +59,25 @@ const CaseUpdateForm: React.FC<CaseUpdateFormProps> = ({ isOpen, onClose, title,
     invoke("update_case", {
       payload: {
         case_no: parseInt(formData.get("case-no")?.toString() || "0", 10),
+        year: parseInt(formData.get("year")?.toString() || "0", 10),
         disposal_of_case: formData.get("disposal-of-case"),
         ndoh_date: ndohDateStr,
         ndoh_time: formData.get("ndoh-time"),
         connected: parseInt(formData.get("connected")?.toString() || "0", 10),
+        remarks: formData.get("remarks")?.toString().trim() || null,
+        disposal_date: isFinal ? formData.get("disposal-date")?.toString() || null : null,
+        disposal_reason_id: isFinal ? Number(formData.get("disposal-reason")) || null : null,
+        disposal_remarks: isFinal ? formData.get("disposal-remarks")?.toString().trim() || null : null,
       }
     }).then(() => {
       console.log("Case updated successfully");
+      form.reset();
+      setDisposal("");
+      onClose();
     }).catch((err) => {
       console.error("Error updating case:", err);
+      alert(errorMessage(err));
     });
-
-    e.currentTarget.reset();
-    onClose();
   };
 
 
@@ -85,6 +103,7 @@ const CaseUpdateForm: React.FC<CaseUpdateFormProps> = ({ isOpen, onClose, title,
             onClick={() => {
               const form = document.getElementById(formId) as HTMLFormElement | null;
               if (form) form.reset();
+              setDisposal("");
               onClose();
             }}
             className="text-gray-600 hover:text-red-600"
@@ -101,18 +120,40 @@ const CaseUpdateForm: React.FC<CaseUpdateFormProps> = ({ isOpen, onClose, title,
           </div>
         </div>
 
-        <form id={formId} className="space-y-6" onSubmit={handleSubmit}>
-          <input
-            type="text"
-            id="case-no"
-            name="case-no"
-            placeholder="Enter case number"
-            className="block w-full px-4 py-3 border border-gray-300 rounded-lg shadow-sm"
-            pattern="\d{6}"        // ensures exactly 6 digits
-            maxLength={6}          // limits input length to 6 characters
-            inputMode="numeric"    // shows numeric keyboard on mobile
-            required
-          />
+        <form
+          id={formId}
+          className="space-y-6"
+          onSubmit={handleSubmit}
+          onChange={(e) => {
+            const target = e.target as HTMLSelectElement;
+            if (target.name === "disposal-of-case") setDisposal(target.value);
+          }}
+        >
+          <div className="flex space-x-4">
+            <input
+              type="text"
+              id="case-no"
+              name="case-no"
+              placeholder="Enter case number"
+              className="flex-1 block w-full px-4 py-3 border border-gray-300 rounded-lg shadow-sm"
+              pattern="\d{6}"        // ensures exactly 6 digits
+              maxLength={6}          // limits input length to 6 characters
+              inputMode="numeric"    // shows numeric keyboard on mobile
+              required
+            />
+            <input
+              type="text"
+              id="year"
+              name="year"
+              placeholder="Enter year"
+              defaultValue={new Date().getFullYear()}
+              className="flex-1 block w-full px-4 py-3 border border-gray-300 rounded-lg shadow-sm"
+              pattern="\d{4}"
+              maxLength={4}
+              inputMode="numeric"
+              required
+            />
+          </div>
 
           <SelectField
             id="disposal-of-case"
@@ -120,6 +161,50 @@ const CaseUpdateForm: React.FC<CaseUpdateFormProps> = ({ isOpen, onClose, title,
             options={caseType}
           />
 
+          {isFinal && (
+            <>
+              <div>
+                <label htmlFor="disposal-date" className="block text-sm font-medium text-gray-700 mb-1">Disposal Date</label>
+                <input
+                  type="date"
+                  id="disposal-date"
+                  name="disposal-date"
+                  defaultValue={new Date().toLocaleDateString("en-CA")}
+                  max={new Date().toLocaleDateString("en-CA")}
+                  className="block w-full px-4 py-3 border border-gray-300 rounded-lg shadow-sm"
+                  required
+                />
+              </div>
+
+              <div>
+                <label htmlFor="disposal-reason" className="block text-sm font-medium text-gray-700 mb-1">Disposal Reason</label>
+                <select
+                  id="disposal-reason"
+                  name="disposal-reason"
+                  defaultValue=""
+                  className="block w-full px-4 py-3 border border-gray-300 rounded-lg shadow-sm bg-white"
+                  required
+                >
+                  <option disabled value="">Select disposal reason</option>
+                  {reasons.map((r) => (
+                    <option key={r.id} value={r.id}>{r.label}</option>
+                  ))}
+                </select>
+              </div>
+
+              <div>
+                <label htmlFor="disposal-remarks" className="block text-sm font-medium text-gray-700 mb-1">Disposal Remarks</label>
+                <textarea
+                  id="disposal-remarks"
+                  name="disposal-remarks"
+                  rows={2}
+                  className="block w-full px-4 py-3 border border-gray-300 rounded-lg shadow-sm"
+                  placeholder="Terms agreed, why it failed, etc. (optional)"
+                />
+              </div>
+            </>
+          )}
+
           <div>
             <label htmlFor="connected" className="block text-sm font-medium text-gray-700 mb-1">Connected</label>
             <input
@@ -154,6 +239,17 @@ const CaseUpdateForm: React.FC<CaseUpdateFormProps> = ({ isOpen, onClose, title,
             ]}
           />
 
+          <div>
+            <label htmlFor="remarks" className="block text-sm font-medium text-gray-700 mb-1">Remarks</label>
+            <textarea
+              id="remarks"
+              name="remarks"
+              rows={2}
+              className="block w-full px-4 py-3 border border-gray-300 rounded-lg shadow-sm"
+              placeholder="Anything noted at this sitting (optional)"
+            />
+          </div>
+
           <button
             type="submit"
             className="w-full flex justify-center py-3 px-4 border border-transparent rounded-lg shadow-sm text-sm font-medium text-white bg-blue-600 hover:bg-blue-700"
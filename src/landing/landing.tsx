@@ -1,17 +1,63 @@
 import React, { useEffect, useState } from "react";
-import { CirclePlus, PenLine, BookText, FileClock, Settings } from "lucide-react";
+import { CirclePlus, PenLine, BookText, FileClock, Settings, FilePen, ArrowLeftRight, ShieldCheck, DatabaseBackup, FolderOpen, Search, ListChecks, FileUp, CalendarDays } from "lucide-react";
 import InitialForm from "../forms/addCase";
 import GenerateSpecificReportForm from "../forms/generateSpecificReport";
 import CaseUpdateForm from "../forms/updateCaseStatus";
 import ManageAssignedTo from "../forms/changeMediator";
+import EditCaseForm from "../forms/editCase";
+import ReassignCaseForm from "../forms/reassignCase";
+import AuditLog from "../forms/auditLog";
+import BackupRestore from "../forms/backupRestore";
+import OpenRegister from "../forms/openRegister";
+import SearchCases from "../forms/searchCases";
+import DisposalReasons from "../forms/disposalReasons";
+import ImportCases from "../forms/importCases";
+import MonthlyReturn from "../forms/monthlyReturn";
 import { invoke } from "@tauri-apps/api/core";
-import jsPDF from "jspdf";
-import autoTable from "jspdf-autotable"; 
-import handshake from "../assets/handshake.png";
+import { appDataDir, resolve } from "@tauri-apps/api/path";
+import { ReportInfo } from "../types/case";
+import { errorMessage } from "../lib/errors";
 
-const InitialPage: React.FC = () => {
+interface InitialPageProps {
+  register: string | null;
+  onOpenRegister: (path: string, seed?: boolean) => Promise<void>;
+  onCloseRegister: () => Promise<void>;
+}
+
+const InitialPage: React.FC<InitialPageProps> = ({ register, onOpenRegister, onCloseRegister }) => {
   const [currentDate, setCurrentDate] = useState("");
   const [openModal, setOpenModal] = useState<null | string>(null); 
+  const [operator, setOperator] = useState(() => localStorage.getItem("operator") ?? "");
+  const [lastBackup, setLastBackup] = useState<string | null>(null);
+  const [centreName, setCentreName] = useState("");
+
+  const saveOperator = async () => {
+    const name = operator.trim();
+    if (!name) return;
+    try {
+      await invoke("set_operator", { operator: name });
+      localStorage.setItem("operator", name);
+    } catch (error) {
+      alert(errorMessage(error));
+    }
+  };
+
+  useEffect(() => {
+    invoke<string>("get_centre_name")
+      .then(setCentreName)
+      .catch((error) => console.error("Failed to fetch centre name:", errorMessage(error)));
+  }, [register]);
+
+  // Printed on every report and export, so it is kept in the register.
+  const renameCentre = async () => {
+    const name = prompt("Centre name printed on reports:", centreName);
+    if (!name || !name.trim()) return;
+    try {
+      setCentreName(await invoke<string>("set_centre_name", { name }));
+    } catch (error) {
+      alert(errorMessage(error));
+    }
+  };
 
   useEffect(() => {
     const today = new Date();
@@ -22,6 +68,23 @@ const InitialPage: React.FC = () => {
     setCurrentDate(`${day}.${month}.${year} (${weekday})`);
   }, []);
 
+  // Scheduled backups run in the background, so keep the status line fresh.
+  useEffect(() => {
+    const fetchBackupStatus = async () => {
+      try {
+        const status = await invoke<{ last_success: { started_at: string } | null }>("get_backup_status");
+        setLastBackup(status.last_success?.started_at ?? null);
+      } catch (error) {
+        setLastBackup(null);
+        console.error("Failed to fetch backup status:", errorMessage(error));
+      }
+    };
+
+    fetchBackupStatus();
+    const timer = setInterval(fetchBackupStatus, 60_000);
+    return () => clearInterval(timer);
+  }, [openModal, register]);
+
   const ButtonCard = ({
     icon,
     title,
@@ -53,75 +116,14 @@ const InitialPage: React.FC = () => {
 
 
   async function handleGenerateReportPDF() {
+    const path = prompt("Save today's report to:", await resolve(await appDataDir(), "case_report.pdf"));
+    if (!path) return;
     try {
-      const result = await invoke("get_todays_cases") as Array<any>;
-
-      if (!result.length) {
-        console.warn("No cases to export.");
-        return;
-      }
-
-      const sanitize = (text: any) =>
-        String(text ?? "").replace(/_/g, " ");
-
-      const doc = new jsPDF();
-
-      // ✅ Load the image to Base64
-      const img = new Image();
-      img.src = handshake;
-
-      await new Promise((resolve) => {
-        img.onload = resolve;
-      });
-
-      const canvas = document.createElement('canvas');
-      canvas.width = img.width;
-      canvas.height = img.height;
-      const ctx = canvas.getContext('2d')!;
-      ctx.drawImage(img, 0, 0);
-
-      const logoBase64 = canvas.toDataURL('image/png');
-
-      doc.addImage(logoBase64, 'PNG', 95, 10, 20, 15);
-
-      doc.setFontSize(12);
-      doc.text("Today's Case Report", 85, 30);
-
-      const headers = [
-        "Case No",
-        "Nature",
-        "Received From",
-        "Date & Time",
-        "Party 1",
-        "Party 2",
-        "Assigned To",
-        "NDOH",
-        "Disposal"
-      ];
-
-      const rows = result.map((c) => [
-        sanitize(c.case_no),
-        sanitize(c.nature_of_case),
-        sanitize(c.received_from),
-        sanitize(`${c.date} ${c.time_slot ?? ""}`),
-        sanitize(c.party1),
-        sanitize(c.party2),
-        sanitize(c.assigned_to),
-        c.ndoh_date ? sanitize(`${c.ndoh_date} ${c.ndoh_time ?? ""}`) : "",
-        sanitize(c.disposal_of_case),
-      ]);
-
-      autoTable(doc, {
-        startY: 40, // Lower to make room for the image
-        head: [headers],
-        body: rows,
-        styles: { fontSize: 8 },
-        headStyles: { fillColor: [41, 128, 185] },
-      });
-
-      doc.save("case_report.pdf");
+      const report = await invoke<ReportInfo>("write_todays_report", { path });
+      alert(`Saved ${report.cases} case(s) on ${report.pages} page(s) to ${report.path}`);
     } catch (error) {
       console.error("Error generating PDF report:", error);
+      alert(errorMessage(error));
     }
   }
 
@@ -134,7 +136,10 @@ const InitialPage: React.FC = () => {
         <div className="bg-white shadow-xl rounded-lg p-6 md:p-8">
           <div className="mb-8 text-center">
             <p className="text-xl font-semibold text-slate-700">
-              Delhi Mediation Centre, Rohini Courts, Delhi
+              {centreName}{" "}
+              <button onClick={renameCentre} title="Rename centre" className="align-middle text-slate-400 hover:text-blue-600">
+                <PenLine size={16} />
+              </button>
             </p>
             <div className="flex justify-center items-center mt-4 ">
               <img src="handshake.png" alt="Handshake" width={100} height={100}/>
@@ -143,6 +148,22 @@ const InitialPage: React.FC = () => {
               Today's Date:{" "}
               <span className="font-bold text-blue-600">{currentDate}</span>
             </p>
+            <div className="flex justify-center items-center gap-2 mt-2">
+              <label htmlFor="operator" className="text-sm font-medium text-slate-700">Operator:</label>
+              <input
+                id="operator"
+                type="text"
+                value={operator}
+                onChange={(e) => setOperator(e.target.value)}
+                onBlur={saveOperator}
+                placeholder="Your name"
+                className="px-3 py-1 border border-gray-300 rounded-lg shadow-sm text-sm"
+              />
+            </div>
+            <p className={`text-sm mt-2 ${lastBackup ? "text-slate-600" : "text-red-600 font-medium"}`}>
+              Last successful backup: {lastBackup ?? "none yet"}
+            </p>
+            <p className="text-xs text-slate-500 mt-1 break-all">Register: {register ?? "none open"}</p>
           </div>
                 <InitialForm
                   isOpen={openModal === 'addcase'}
@@ -166,6 +187,63 @@ const InitialPage: React.FC = () => {
                   isOpen={openModal === 'changemediatornames'}
                   onClose={() => setOpenModal(null)}
                 />
+                <EditCaseForm
+                  isOpen={openModal === 'editcase'}
+                  onClose={() => setOpenModal(null)}
+                  title="Edit Case"
+                  formId="form4"
+                />
+                <ReassignCaseForm
+                  isOpen={openModal === 'reassigncase'}
+                  onClose={() => setOpenModal(null)}
+                  title="Reassign Case"
+                  formId="form5"
+                />
+                <AuditLog
+                  isOpen={openModal === 'auditlog'}
+                  onClose={() => setOpenModal(null)}
+                  title="Audit Log"
+                  formId="form6"
+                />
+                <BackupRestore
+                  isOpen={openModal === 'backup&restore'}
+                  onClose={() => setOpenModal(null)}
+                  title="Backup & Restore"
+                  formId="form7"
+                />
+                <SearchCases
+                  isOpen={openModal === 'searchcases'}
+                  onClose={() => setOpenModal(null)}
+                  title="Search Cases"
+                  formId="form9"
+                />
+                <DisposalReasons
+                  isOpen={openModal === 'disposalreasons'}
+                  onClose={() => setOpenModal(null)}
+                  title="Disposal Reasons"
+                  formId="form10"
+                />
+                <ImportCases
+                  isOpen={openModal === 'importcases'}
+                  onClose={() => setOpenModal(null)}
+                  title="Import Cases"
+                  formId="form11"
+                />
+                <MonthlyReturn
+                  isOpen={openModal === 'monthlyreturn'}
+                  onClose={() => setOpenModal(null)}
+                  title="Monthly Return"
+                  formId="form12"
+                />
+                <OpenRegister
+                  isOpen={openModal === 'switchregister'}
+                  onClose={() => setOpenModal(null)}
+                  title="Switch Register"
+                  formId="form8"
+                  register={register}
+                  onOpenRegister={onOpenRegister}
+                  onCloseRegister={onCloseRegister}
+                />
           <div className="grid grid-cols-1 md:grid-cols-2 lg:grid-cols-3 gap-6">
             <ButtonCard
               icon={<CirclePlus color="blue" />}
@@ -200,12 +278,66 @@ const InitialPage: React.FC = () => {
                 description="Generate a custom Report."
                 buttonText="Generate Report"
               />
+              <ButtonCard
+                icon={<CalendarDays color="blue" />}
+                title="Monthly Return"
+                description="Opening and closing balances for the monthly statement."
+                buttonText="Prepare Return"
+              />
+              <ButtonCard
+                icon={<FilePen color="blue" />}
+                title="Edit Case"
+                description="Correct the details recorded when a case was registered."
+                buttonText="Edit Case"
+              />
+              <ButtonCard
+                icon={<ArrowLeftRight color="blue" />}
+                title="Reassign Case"
+                description="Move a case to another mediator and record why."
+                buttonText="Reassign"
+              />
+              <ButtonCard
+                icon={<ShieldCheck color="blue" />}
+                title="Audit Log"
+                description="See who changed what, and check the log hasn't been tampered with."
+                buttonText="View Log"
+              />
+              <ButtonCard
+                icon={<DatabaseBackup color="blue" />}
+                title="Backup & Restore"
+                description="Save a copy of the register, or bring one back."
+                buttonText="Open"
+              />
               <ButtonCard
                 icon={<Settings color="blue" />}
                 title="Change Mediator Names"
                 description="Change the names of mediators in the system."
                 buttonText="Change Names"
               />
+              <ButtonCard
+                icon={<ListChecks color="blue" />}
+                title="Disposal Reasons"
+                description="Edit the reasons offered when a case is disposed of."
+                buttonText="Edit List"
+              />
+              <ButtonCard
+                icon={<Search color="blue" />}
+                title="Search Cases"
+                description="Find a case by party, court, case number or remark."
+                buttonText="Search"
+              />
+              <ButtonCard
+                icon={<FileUp color="blue" />}
+                title="Import Cases"
+                description="Bring in cases from an old Excel or CSV register."
+                buttonText="Import"
+              />
+              <ButtonCard
+                icon={<FolderOpen color="blue" />}
+                title="Switch Register"
+                description="Open last year's archive or another register file."
+                buttonText="Open"
+              />
             </div>
           </div>
         </div>
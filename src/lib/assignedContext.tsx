@@ -6,8 +6,26 @@ export type Option = {
   label: string;
 };
 
+export type Mediator = {
+  id: number;
+  display_name: string;
+  honorific: string;
+  phone: string | null;
+  email: string | null;
+  active: boolean;
+  joined_on: string | null;
+  created_at: string;
+};
+
+export const mediatorName = (m: Mediator) =>
+  m.honorific ? `${m.honorific} ${m.display_name}` : m.display_name;
+
 interface AssignedToContextType {
+  // Active mediators only, for intake and reassignment.
   options: Option[];
+  // Everyone, retired mediators included, for reports and history.
+  allOptions: Option[];
+  mediators: Mediator[];
   loading: boolean;
   fetchOptions: () => Promise<void>;
 }
@@ -15,27 +33,31 @@ interface AssignedToContextType {
 const AssignedToContext = createContext<AssignedToContextType | undefined>(undefined);
 
 export const AssignedToProvider = ({ children }: { children: ReactNode }) => {
-  const [options, setOptions] = useState<Option[]>([]);
+  const [mediators, setMediators] = useState<Mediator[]>([]);
   const [loading, setLoading] = useState(true);
 
   const fetchOptions = async () => {
     setLoading(true);
     try {
-      const rawNames = await invoke<string[]>("get_assigned_to_list");
-      const formatted = rawNames.map((name) => ({
-        value: name,
-        label: name.replace(/_/g, " "),
-      }));
-      setOptions(formatted);
+      setMediators(await invoke<Mediator[]>("get_mediators", { includeInactive: true }));
     } catch (error) {
-      console.error("Failed to fetch assigned to options:", error);
+      console.error("Failed to fetch mediators:", error);
     } finally {
       setLoading(false);
     }
   };
 
+  const options = mediators
+    .filter((m) => m.active)
+    .map((m) => ({ value: String(m.id), label: mediatorName(m) }));
+
+  const allOptions = mediators.map((m) => ({
+    value: String(m.id),
+    label: m.active ? mediatorName(m) : `${mediatorName(m)} (inactive)`,
+  }));
+
   return (
-    <AssignedToContext.Provider value={{ options, loading, fetchOptions }}>
+    <AssignedToContext.Provider value={{ options, allOptions, mediators, loading, fetchOptions }}>
       {children}
     </AssignedToContext.Provider>
   );
@@ -48,4 +70,3 @@ export const useAssignedTo = () => {
   }
   return context;
 };
-
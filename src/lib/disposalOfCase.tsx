@@ -0,0 +1,24 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { DisposalOption } from "../types/case";
+import { Option } from "./assignedContext";
+
+/**
+ * Loads the disposal list from the backend, which owns the codes and
+ * decides which outcomes are final.
+ */
+export function useDisposalOfCase() {
+  const [disposals, setDisposals] = useState<DisposalOption[]>([]);
+
+  useEffect(() => {
+    invoke<DisposalOption[]>("get_disposal_list")
+      .then(setDisposals)
+      .catch((error) => console.error("Failed to fetch disposal list:", error));
+  }, []);
+
+  const options: Option[] = disposals.map((d) => ({ value: d.code, label: d.label }));
+  const labelFor = (code: string) => disposals.find((d) => d.code === code)?.label ?? code;
+  const isFinal = (code: string) => disposals.find((d) => d.code === code)?.is_final ?? false;
+
+  return { disposals, options, labelFor, isFinal };
+}
@@ -0,0 +1,20 @@
+/**
+ * Shape of every error returned by a Tauri command (see src-tauri/src/error.rs).
+ */
+export type AppError = {
+  code: string;
+  message: string;
+  field: string | null;
+};
+
+export function isAppError(err: unknown): err is AppError {
+  return typeof err === "object" && err !== null && "code" in err && "message" in err;
+}
+
+/**
+ * Message suitable for showing to the clerk, whatever the command threw.
+ */
+export function errorMessage(err: unknown): string {
+  if (isAppError(err)) return err.message;
+  return String(err);
+}
@@ -0,0 +1,22 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { NatureOfCaseOption } from "../types/case";
+import { Option } from "./assignedContext";
+
+/**
+ * Loads the nature-of-case list from the backend, which owns the codes.
+ */
+export function useNatureOfCase() {
+  const [natures, setNatures] = useState<NatureOfCaseOption[]>([]);
+
+  useEffect(() => {
+    invoke<NatureOfCaseOption[]>("get_nature_of_case_list")
+      .then(setNatures)
+      .catch((error) => console.error("Failed to fetch nature of case list:", error));
+  }, []);
+
+  const options: Option[] = natures.map((n) => ({ value: n.code, label: n.label }));
+  const labelFor = (code: string) => natures.find((n) => n.code === code)?.label ?? code;
+
+  return { options, labelFor };
+}
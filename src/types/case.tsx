@@ -1,37 +1,116 @@
 
-// src/types/NatureOfCase.ts
-export enum NatureOfCase {
-  CivilRecovery = "Civil Recovery",
-  CivilPartition = "Civil Partition",
-  CivilInjunction = "Civil Injunction",
-  CivilPossession = "Civil Possession",
-  CivilProbate = "Civil Probate",
-  Arbitration = "Arbitration",
-  CivilAppeal = "Civil Appeal",
-  CivilExecution = "Civil Execution",
-  OtherCivilSuit = "Other Civil Suit",
-  CriminalMatter = "Criminal Matter",
-  CriminalRevision = "Criminal Revision",
-  CriminalAppeal = "Criminal Appeal",
-  PetitionForDivorce = "Petition For Divorce",
-  PetitionForMaintenance = "Petition For Maintenance",
-  PetitionForCustody = "Petition For Custody",
-  PetitionForDomesticVoilenceAct = "Petition For Domestic Voilence Act",
-  CawCellN = "CawCell (N)",
-  CawCellOD = "CawCell (OD)",
-  PetitionForRecoveryOfRent = "Petition For Recovery Of Rent",
-  PetitionUs138OfNiActNPasaAct = "Petition Us 138 of NI Act & PASA Act",
-  PetitionUnderElectricityAct = "PetitionUnderElectricityAct",
-  MactCase = "MACT Case",
-}
-
-
-export enum DisposalOfCase {
-  Settled = "Settled",
-  NotSettled = "NotSettled",
-  NotFitForMediation = "NotFitForMediation",
-  Pending = "Pending",
-}
+// Nature of case is owned by the backend; fetch it with get_nature_of_case_list.
+export type NatureOfCaseOption = {
+  code: string;
+  label: string;
+};
 
 
+// Disposal outcomes are owned by the backend too; fetch them with get_disposal_list.
+export type DisposalOption = {
+  code: string;
+  label: string;
+  is_final: boolean;
+};
 
+
+
+
+export type DisposalReason = {
+  id: number;
+  label: string;
+  active: boolean;
+  created_at: string;
+};
+
+
+// Returned by write_todays_report and write_filtered_report.
+export type ReportInfo = {
+  path: string;
+  pages: number;
+  cases: number;
+};
+
+
+// Returned by export_cases.
+export type ExportInfo = {
+  path: string;
+  format: "csv" | "xlsx";
+  cases: number;
+};
+
+
+// Register fields a legacy sheet's columns can be mapped to; from get_import_fields.
+export type ImportFieldOption = {
+  code: string;
+  label: string;
+  required: boolean;
+};
+
+// Column mapping for import_cases, saved by name as an import profile.
+export type ImportMapping = {
+  sheet: string | null;
+  columns: Record<string, string>;
+  date_format: string | null;
+  default_time_slot: string | null;
+  create_missing: boolean;
+};
+
+export type ImportProfile = {
+  id: number;
+  name: string;
+  mapping: ImportMapping;
+  updated_at: string;
+};
+
+// Returned by preview_import.
+export type ImportPreview = {
+  sheets: string[];
+  sheet: string | null;
+  headers: string[];
+  sample: string[][];
+  rows: number;
+  suggested: Record<string, string>;
+};
+
+// Returned by import_cases, for a dry run and a real import alike.
+export type ImportReport = {
+  rows: number;
+  valid: number;
+  errors: { row: number; field: string; value: string; message: string }[];
+  conflicts: { row: number; case_no: number; year: number; party1: string; party2: string }[];
+  new_mediators: string[];
+  new_disposal_reasons: string[];
+  imported: number;
+  committed: boolean;
+};
+
+
+// One line of the monthly return; opening + received - disposed = closing.
+export type ReturnCounts = {
+  opening: number;
+  received: number;
+  settled: number;
+  not_settled: number;
+  not_fit: number;
+  returned: number;
+  disposed: number;
+  closing: number;
+};
+
+// Returned by monthly_return.
+export type MonthlyReturn = {
+  year: number;
+  month: number;
+  period_start: string;
+  period_end: string;
+  totals: ReturnCounts;
+  by_nature: { nature_of_case: string; label: string; counts: ReturnCounts }[];
+  by_court: { received_from: string; counts: ReturnCounts }[];
+};
+
+// Returned by write_monthly_return.
+export type ReturnFileInfo = {
+  path: string;
+  format: "pdf" | "xlsx";
+};